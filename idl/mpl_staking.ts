@@ -1,5 +1,5 @@
 export type MplStaking = {
-  "version": "0.1.0",
+  "version": "0.2.0",
   "name": "mpl_staking",
   "docs": [
     "# Introduction",
@@ -481,6 +481,11 @@ export type MplStaking = {
           "name": "voterAuthority",
           "isMut": false,
           "isSigner": true
+        },
+        {
+          "name": "registrar",
+          "isMut": false,
+          "isSigner": false
         }
       ],
       "args": [
@@ -1290,7 +1295,7 @@ export type MplStaking = {
 };
 
 export const IDL: MplStaking = {
-  "version": "0.1.0",
+  "version": "0.2.0",
   "name": "mpl_staking",
   "docs": [
     "# Introduction",
@@ -1772,6 +1777,11 @@ export const IDL: MplStaking = {
           "name": "voterAuthority",
           "isMut": false,
           "isSigner": true
+        },
+        {
+          "name": "registrar",
+          "isMut": false,
+          "isSigner": false
         }
       ],
       "args": [
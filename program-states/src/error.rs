@@ -90,7 +90,7 @@ pub enum MplStakingError {
     #[msg("Arithmetic operation has beed overflowed")]
     ArithmeticOverflow,
     // 6029 / 0x178d
-    #[msg("Delegate must have at least 15_000_000 of own weighted stake")]
+    #[msg("Delegate must have the min own weighted stake required by the registrar")]
     InsufficientWeightedStake,
     // 6030 / 0x178e
     #[msg("Invalid delegate account")]
@@ -99,7 +99,7 @@ pub enum MplStakingError {
     #[msg("Invalid mining account")]
     InvalidMining,
     // 6032 / 0x1790
-    #[msg("Updating delegate is sooner than 5 days")]
+    #[msg("Updating delegate is sooner than allowed by the registrar")]
     DelegateUpdateIsTooSoon,
     // 6033 / 0x1791
     #[msg("Cannot change delegate to the same delegate")]
@@ -119,4 +119,82 @@ pub enum MplStakingError {
     // 6038 / 0x1796
     #[msg("TokenflowRestricted has been restricted by DAO authority")]
     TokenflowRestricted,
+    // 6039 / 0x1797
+    #[msg("Voting multipliers must be positive and not decrease with longer lockup periods")]
+    InvalidVotingMultipliers,
+    // 6040 / 0x1798
+    #[msg("All deposit entries are in use")]
+    DepositEntryFull,
+    // 6041 / 0x1799
+    #[msg("Clawback is only allowed for granted deposits that are still locked up")]
+    ClawbackNotAllowedOnDeposit,
+    // 6042 / 0x179a
+    #[msg("Batch minting has been restricted by DAO authority")]
+    BatchMintingRestricted,
+    // 6043 / 0x179b
+    #[msg("Amount must be positive and not exceed the deposited amount")]
+    InvalidAmount,
+    // 6044 / 0x179c
+    #[msg("Unlock hasn't been requested for the deposit")]
+    UnlockNotRequested,
+    // 6045 / 0x179d
+    #[msg("Auto-renew isn't enabled for the deposit")]
+    AutoRenewNotEnabled,
+    // 6046 / 0x179e
+    #[msg("Only two different staked deposits that can't be clawed back can be merged")]
+    DepositsNotMergeable,
+    // 6047 / 0x179f
+    #[msg("A voter can't have more deposit entries")]
+    TooManyDepositEntries,
+    // 6048 / 0x17a0
+    #[msg("A registrar can't have more voting mints")]
+    TooManyVotingMints,
+    // 6049 / 0x17a1
+    #[msg("The registrar must be grown to set an exchange rate for a voting mint")]
+    RegistrarNotGrown,
+    // 6050 / 0x17a2
+    #[msg("Only voting mints without deposit entries can be removed")]
+    VotingMintHasDeposits,
+    // 6051 / 0x17a3
+    #[msg("The delegated stake account of the delegate is missing")]
+    DelegatedStakeNotFound,
+    // 6052 / 0x17a4
+    #[msg("The delegate profile of the delegate is missing")]
+    DelegateProfileNotFound,
+    // 6053 / 0x17a5
+    #[msg("The delegate doesn't accept new stake")]
+    DelegateNotActive,
+    // 6054 / 0x17a6
+    #[msg("The delegate doesn't accept more stake")]
+    DelegateStakeLimitExceeded,
+    // 6055 / 0x17a7
+    #[msg("The metadata URI is too long")]
+    MetadataUriTooLong,
+    // 6056 / 0x17a8
+    #[msg("At least one deposit entry index must be given")]
+    NoDepositEntryIndices,
+    // 6057 / 0x17a9
+    #[msg("The registrar account already has the current layout")]
+    RegistrarAlreadyMigrated,
+    // 6058 / 0x17aa
+    #[msg("The baseline vote weight scaled factor of a voting mint must be positive")]
+    InvalidVoteWeightScaledFactor,
+    // 6059 / 0x17ab
+    #[msg("The token owner record of the voter losing vote weight is missing")]
+    TokenOwnerRecordNotFound,
+    // 6060 / 0x17ac
+    #[msg("Delegate voting requires the flat voting power mode")]
+    DelegateVotingRequiresFlatVotingPower,
+    // 6061 / 0x17ad
+    #[msg("More deposit entry indices were given than a voter can have")]
+    TooManyDepositEntryIndices,
+    // 6062 / 0x17ae
+    #[msg("A deposit entry index was given more than once")]
+    DuplicateDepositEntryIndex,
+    // 6063 / 0x17af
+    #[msg("Fewer open deposit entries were given than have already been counted")]
+    DepositEntriesUndercounted,
+    // 6064 / 0x17b0
+    #[msg("The delegate still has the min own weighted stake required by the registrar")]
+    DelegateStillQualified,
 }
@@ -0,0 +1,116 @@
+use crate::error::*;
+use anchor_lang::prelude::*;
+
+/// Opt-in of a voter to be chosen as a delegate by other voters.
+///
+/// Deposits can only be delegated to voters with an active profile, and only while
+/// the staked tokens delegated to them stay within `max_accepted_stake`. An
+/// inactive profile keeps the stake it already accepted, but doesn't accept new or
+/// additional stake.
+///
+/// PDA: `[registrar, "delegate-profile", delegate]`
+#[account(zero_copy)]
+pub struct DelegateProfile {
+    pub registrar: Pubkey,
+    /// Voter authority of the delegate.
+    pub delegate: Pubkey,
+    /// Staked tokens currently delegated to the delegate, in native currency.
+    pub accepted_stake: u64,
+    /// Max of `accepted_stake`, additional stake is rejected beyond it.
+    pub max_accepted_stake: u64,
+    /// URI of off-chain metadata describing the delegate, zero padded.
+    pub metadata_uri: [u8; 128],
+    /// Whether the delegate accepts new stake.
+    pub active: bool,
+    pub bump: u8,
+    pub _reserved: [u8; 38],
+}
+const_assert!(std::mem::size_of::<DelegateProfile>() == 2 * 32 + 8 + 8 + 128 + 1 + 1 + 38);
+const_assert!(std::mem::size_of::<DelegateProfile>() % 8 == 0);
+
+impl DelegateProfile {
+    pub const MAX_METADATA_URI_LEN: usize = 128;
+
+    pub fn set_metadata_uri(&mut self, metadata_uri: &str) -> Result<()> {
+        let uri = metadata_uri.as_bytes();
+        require!(
+            uri.len() <= Self::MAX_METADATA_URI_LEN,
+            MplStakingError::MetadataUriTooLong
+        );
+        let mut metadata_uri = [0; Self::MAX_METADATA_URI_LEN];
+        metadata_uri[..uri.len()].copy_from_slice(uri);
+        self.metadata_uri = metadata_uri;
+        Ok(())
+    }
+
+    /// Accepts `amount` staked tokens of a deposit that `previous_amount` was
+    /// already accepted for, and released, before.
+    ///
+    /// The deposit may keep up to its previous amount even if the profile is
+    /// inactive or full, so unlocking or slashing never depends on the delegate.
+    pub fn accept(&mut self, amount: u64, previous_amount: u64) -> Result<()> {
+        let accepted_stake = self
+            .accepted_stake
+            .checked_add(amount)
+            .ok_or_else(|| error!(MplStakingError::ArithmeticOverflow))?;
+        if amount > previous_amount {
+            require!(self.active, MplStakingError::DelegateNotActive);
+            require!(
+                accepted_stake <= self.max_accepted_stake,
+                MplStakingError::DelegateStakeLimitExceeded
+            );
+        }
+        self.accepted_stake = accepted_stake;
+        Ok(())
+    }
+
+    pub fn release(&mut self, amount: u64) -> Result<()> {
+        self.accepted_stake = self
+            .accepted_stake
+            .checked_sub(amount)
+            .ok_or_else(|| error!(MplStakingError::ArithmeticOverflow))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn test_accept_stake() {
+        let mut profile = DelegateProfile::zeroed();
+        profile.max_accepted_stake = 1_000;
+        assert!(profile.accept(100, 0).is_err());
+
+        profile.active = true;
+        profile.accept(600, 0).unwrap();
+        assert!(profile.accept(500, 0).is_err());
+        profile.accept(400, 0).unwrap();
+        assert_eq!({ profile.accepted_stake }, 1_000);
+
+        // stake that was accepted before stays, even beyond the limit or once inactive
+        profile.max_accepted_stake = 500;
+        profile.active = false;
+        profile.release(600).unwrap();
+        profile.accept(600, 600).unwrap();
+        profile.release(600).unwrap();
+        assert!(profile.accept(601, 600).is_err());
+        profile.accept(300, 600).unwrap();
+        assert_eq!({ profile.accepted_stake }, 700);
+        assert!(profile.release(701).is_err());
+    }
+
+    #[test]
+    fn test_set_metadata_uri() {
+        let mut profile = DelegateProfile::zeroed();
+        profile.set_metadata_uri("https://example.com").unwrap();
+        assert_eq!(&profile.metadata_uri[..19], b"https://example.com");
+        profile.set_metadata_uri("ab").unwrap();
+        assert_eq!(&profile.metadata_uri[..3], b"ab\0");
+        assert!(profile
+            .set_metadata_uri(&"a".repeat(DelegateProfile::MAX_METADATA_URI_LEN + 1))
+            .is_err());
+    }
+}
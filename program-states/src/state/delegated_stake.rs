@@ -0,0 +1,99 @@
+use crate::{
+    error::*,
+    state::{DynamicRegistrar, Registrar, VotingMint},
+};
+use anchor_lang::prelude::*;
+use std::ops::Deref;
+
+/// Staked tokens that other voters delegated to `delegate`.
+///
+/// Deposits changed while the registrar has `delegate_voting` enabled count
+/// toward the vote weight of the delegate instead of the one of their owners,
+/// until they're changed again with delegate voting turned off. Lockup
+/// bonuses change over time and can't be kept up to date for all delegators at
+/// once, so delegated tokens count with their baseline weight.
+///
+/// PDA: `[registrar, "delegated-stake", delegate]`
+#[account(zero_copy)]
+#[derive(Default)]
+pub struct DelegatedStake {
+    pub registrar: Pubkey,
+    /// Voter authority of the delegate.
+    pub delegate: Pubkey,
+    /// Delegated native amounts, by voting mint index.
+    pub amounts: [u64; 16],
+    pub bump: u8,
+    pub _reserved: [u8; 31],
+}
+const_assert!(std::mem::size_of::<DelegatedStake>() == 2 * 32 + 16 * 8 + 1 + 31);
+const_assert!(std::mem::size_of::<DelegatedStake>() % 8 == 0);
+const_assert!(Registrar::MAX_VOTING_MINTS == 16);
+
+impl DelegatedStake {
+    pub fn add(&mut self, voting_mint_config_idx: usize, amount: u64) -> Result<()> {
+        // amounts is a packed field, so it can't be borrowed in place
+        let mut amounts = self.amounts;
+        let delegated = amounts
+            .get_mut(voting_mint_config_idx)
+            .ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))?;
+        *delegated = delegated
+            .checked_add(amount)
+            .ok_or_else(|| error!(MplStakingError::ArithmeticOverflow))?;
+        self.amounts = amounts;
+        Ok(())
+    }
+
+    pub fn sub(&mut self, voting_mint_config_idx: usize, amount: u64) -> Result<()> {
+        let mut amounts = self.amounts;
+        let delegated = amounts
+            .get_mut(voting_mint_config_idx)
+            .ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))?;
+        *delegated = delegated
+            .checked_sub(amount)
+            .ok_or_else(|| error!(MplStakingError::ArithmeticOverflow))?;
+        self.amounts = amounts;
+        Ok(())
+    }
+
+    /// The vote weight of the delegated tokens, converted with the exchange rates
+    /// of their voting mints.
+    pub fn weight<R, M>(&self, registrar: &DynamicRegistrar<R, M>) -> Result<u64>
+    where
+        R: Deref<Target = Registrar>,
+        M: Deref<Target = [VotingMint]>,
+    {
+        let amounts = self.amounts;
+        amounts
+            .iter()
+            .enumerate()
+            .filter(|(_, amount)| **amount > 0)
+            .try_fold(0_u64, |sum, (idx, amount)| {
+                sum.checked_add(registrar.vote_weight(idx, *amount)?)
+                    .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delegated_stake_weight() {
+        let mut registrar = Registrar::default();
+        let mut extra_voting_mints = [VotingMint::new(Default::default()); 3];
+        extra_voting_mints[2].digit_shift = -1;
+        let registrar = DynamicRegistrar::new(&mut registrar, &mut extra_voting_mints[..]);
+
+        let mut delegated_stake = DelegatedStake::default();
+        delegated_stake.add(0, 1_000).unwrap();
+        delegated_stake.add(2, 500).unwrap();
+        delegated_stake.add(2, 500).unwrap();
+        assert_eq!(delegated_stake.weight(&registrar).unwrap(), 1_100);
+
+        delegated_stake.sub(0, 1_000).unwrap();
+        assert!(delegated_stake.sub(0, 1).is_err());
+        assert!(delegated_stake.add(16, 1).is_err());
+        assert_eq!(delegated_stake.weight(&registrar).unwrap(), 100);
+    }
+}
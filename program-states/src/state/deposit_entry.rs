@@ -1,6 +1,9 @@
-use crate::state::{
-    lockup::{Lockup, LockupKind},
-    LockupPeriod,
+use crate::{
+    error::MplStakingError,
+    state::{
+        lockup::{Lockup, LockupKind},
+        LockupPeriod, Registrar, VotingPowerMode,
+    },
 };
 use anchor_lang::prelude::*;
 
@@ -25,40 +28,198 @@ pub struct DepositEntry {
     pub voting_mint_config_idx: u8,
     // True if the deposit entry is being used.
     pub is_used: bool,
-    pub _reserved0: [u8; 32],
-    pub _reserved1: [u8; 6],
+    /// True if the deposit was granted and the grant authority may claw back
+    /// the tokens while they are locked up.
+    pub allow_clawback: bool,
+    /// True if the staked deposit should be locked up again for the same period
+    /// once its lockup expires, see `renew_stake`.
+    pub auto_renew: bool,
+    pub _reserved0: [u8; 4],
+    /// Amount in locked when the lockup began, in native currency.
+    /// Only used by vesting lockups, where it's the base of the vesting schedule.
+    pub amount_initially_locked_native: u64,
+    /// Amount counted toward the vote weight of `delegate` in its `DelegatedStake`,
+    /// in native currency. Either zero or `amount_deposited_native`.
+    pub delegated_vote_amount: u64,
+    /// Amount counted toward the accepted stake of `delegate` in its `DelegateProfile`,
+    /// in native currency. Either zero or `amount_deposited_native`.
+    pub accepted_delegate_amount: u64,
+    pub _reserved1: [u8; 8],
 }
-const_assert!(std::mem::size_of::<DepositEntry>() == 48 + 32 + 8 + 8 + 8 + 1 + 1 + 32 + 6);
+const_assert!(
+    std::mem::size_of::<DepositEntry>() == 48 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 4 + 8 + 8 + 8 + 8
+);
 const_assert!(std::mem::size_of::<DepositEntry>() % 8 == 0);
 
 impl DepositEntry {
     /// # Voting Power Caclulation
-    /// ### Constant Lockup
-    /// Voting Power will always be equal to 1*deposited
-    /// since we don't provide any other methods besides constant locking
-    pub fn voting_power(&self) -> Result<u64> {
-        Ok(self.amount_deposited_native)
+    /// Deposits that aren't staked (no lockup, or cooldown requested) always
+    /// have a voting power equal to 1*deposited. For staked deposits it
+    /// depends on the `VotingPowerMode`:
+    /// ### Flat
+    /// Voting Power is equal to 1*deposited.
+    /// ### PeriodMultiplier
+    /// Voting Power is equal to multiplier*deposited, where the multiplier is
+    /// the one of the lockup period, or the Flex one once the lockup expired.
+    /// ### Decaying
+    /// Voting Power starts at multiplier*deposited and decreases linearly
+    /// towards 1*deposited at the end of the lockup.
+    ///
+    /// The multipliers are the registrar's `voting_multipliers`.
+    pub fn voting_power(&self, registrar: &Registrar, curr_ts: u64) -> Result<u64> {
+        let baseline = self.amount_deposited_native;
+        if !self.is_staked() || curr_ts < self.lockup.start_ts {
+            return Ok(baseline);
+        }
+
+        let multiplier = self
+            .lockup
+            .voting_multiplier(curr_ts, &registrar.voting_multipliers);
+        match registrar.voting_power_mode {
+            VotingPowerMode::Flat => Ok(baseline),
+            VotingPowerMode::PeriodMultiplier => Ok(baseline
+                .checked_mul(multiplier)
+                .ok_or(MplStakingError::VoterWeightOverflow)?),
+            VotingPowerMode::Decaying => {
+                let period_secs = self.lockup.period.to_secs();
+                if period_secs == 0 {
+                    return Ok(baseline);
+                }
+                let seconds_left = self.lockup.seconds_left(curr_ts).min(period_secs);
+                let bonus = (baseline as u128)
+                    .checked_mul(multiplier.saturating_sub(1) as u128)
+                    .and_then(|bonus| bonus.checked_mul(seconds_left as u128))
+                    .ok_or(MplStakingError::VoterWeightOverflow)?
+                    / period_secs as u128;
+                Ok(baseline
+                    .checked_add(
+                        u64::try_from(bonus).map_err(|_| MplStakingError::VoterWeightOverflow)?,
+                    )
+                    .ok_or(MplStakingError::VoterWeightOverflow)?)
+            }
+        }
+    }
+
+    /// The earliest time at which the deposited tokens can be withdrawn,
+    /// assuming the unlock is requested as soon as possible starting from `curr_ts`
+    /// and the cooldown lasts `cooldown_secs`.
+    ///
+    /// Vesting deposits never need an unlock: their tokens become withdrawable
+    /// gradually, see `amount_locked`.
+    pub fn earliest_withdrawal_ts(&self, curr_ts: u64, cooldown_secs: u64) -> u64 {
+        if !self.is_used || self.lockup.kind == LockupKind::None || self.lockup.kind.is_vesting() {
+            return curr_ts;
+        }
+        if self.lockup.cooldown_requested {
+            return self.lockup.cooldown_ends_at.max(curr_ts);
+        }
+        self.lockup
+            .end_ts
+            .max(curr_ts)
+            .saturating_add(cooldown_secs)
+    }
+
+    /// The voting power this deposit is guaranteed to still have at `at_ts`,
+    /// assuming the owner unlocks and withdraws as early as possible starting
+    /// from `curr_ts`.
+    ///
+    /// Tokens that may be withdrawn by `at_ts` don't count. Tokens that may be
+    /// in cooldown by then only count with their baseline voting power.
+    pub fn voting_power_locked_guaranteed(
+        &self,
+        registrar: &Registrar,
+        curr_ts: u64,
+        at_ts: u64,
+    ) -> Result<u64> {
+        if self.is_used && self.lockup.kind.is_vesting() {
+            return self.amount_locked(at_ts);
+        }
+        if at_ts >= self.earliest_withdrawal_ts(curr_ts, registrar.params.cooldown_secs()) {
+            return Ok(0);
+        }
+        if at_ts >= self.lockup.end_ts {
+            return Ok(self.amount_deposited_native);
+        }
+        self.voting_power(registrar, at_ts)
+    }
+
+    /// Returns the amount of the initially locked tokens that have vested at `curr_ts`.
+    pub fn vested(&self, curr_ts: u64) -> Result<u64> {
+        if !self.lockup.kind.is_vesting() {
+            return Ok(0);
+        }
+        let periods_total = self.lockup.periods_total()?;
+        if periods_total == 0 {
+            return Ok(self.amount_initially_locked_native);
+        }
+        let period_current = self.lockup.period_current(curr_ts)?;
+        let vested = (self.amount_initially_locked_native as u128)
+            .checked_mul(period_current as u128)
+            .unwrap()
+            .checked_div(periods_total as u128)
+            .unwrap();
+        Ok(u64::try_from(vested).unwrap())
     }
 
     /// Returns native tokens still locked.
     #[inline(always)]
-    pub fn amount_locked(&self) -> u64 {
+    pub fn amount_locked(&self, curr_ts: u64) -> Result<u64> {
+        if !self.is_used {
+            return Ok(0);
+        }
+        if self.lockup.kind.is_vesting() {
+            let locked = self
+                .amount_initially_locked_native
+                .checked_sub(self.vested(curr_ts)?)
+                .unwrap();
+            return Ok(locked.min(self.amount_deposited_native));
+        }
         if self.is_staked() {
-            self.amount_deposited_native
+            Ok(self.amount_deposited_native)
         } else {
-            0
+            Ok(0)
         }
     }
 
     /// Returns native tokens that are unlocked given current vesting
     /// and previous withdraws.
     #[inline(always)]
-    pub fn amount_unlocked(&self) -> u64 {
-        if self.is_staked() {
-            0
-        } else {
-            self.amount_deposited_native
-        }
+    pub fn amount_unlocked(&self, curr_ts: u64) -> Result<u64> {
+        Ok(self
+            .amount_deposited_native
+            .saturating_sub(self.amount_locked(curr_ts)?))
+    }
+
+    /// Adds `amount` to a vesting deposit.
+    ///
+    /// The part of the vesting schedule that is in the past is cut off first, so
+    /// the already vested tokens stay withdrawable and the new tokens vest over
+    /// the remaining periods together with the still locked ones.
+    pub fn add_to_vesting(&mut self, curr_ts: u64, amount: u64) -> Result<()> {
+        require!(
+            self.lockup.kind.is_vesting(),
+            MplStakingError::InvalidLockupKind
+        );
+        let vested = self.vested(curr_ts)?;
+        self.amount_initially_locked_native = self
+            .amount_initially_locked_native
+            .checked_sub(vested)
+            .unwrap();
+        self.lockup.remove_past_periods(curr_ts)?;
+        require!(
+            !self.lockup.expired(curr_ts),
+            MplStakingError::DepositingIsForbidded
+        );
+
+        self.amount_deposited_native = self
+            .amount_deposited_native
+            .checked_add(amount)
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+        self.amount_initially_locked_native = self
+            .amount_initially_locked_native
+            .checked_add(amount)
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+        Ok(())
     }
 
     /// Returns the weighted stake for the given deposit at the specified timestamp.
@@ -71,12 +232,19 @@ impl DepositEntry {
         self.lockup.multiplier(curr_ts) * self.amount_deposited_native
     }
 
+    /// Whether the deposit is staked with another voter as its delegate. Such
+    /// deposits count toward the `DelegateProfile` of the delegate and, when the
+    /// registrar has delegate voting enabled, its `DelegatedStake`.
+    pub fn is_staked_with_delegate(&self, owner: &Pubkey) -> bool {
+        self.is_staked() && self.delegate != *owner && self.amount_deposited_native > 0
+    }
+
     /// Weighted stake can be calculated only if `DepositEntry` is active,
-    ///  and if both `LockupKind` and `LockupPeriod` not `None`.
+    ///  and if it's a `Constant` lockup with a `LockupPeriod` that's not `None`.
     #[inline(always)]
     fn is_staked(&self) -> bool {
         self.is_used
-            && self.lockup.kind.ne(&LockupKind::None)
+            && self.lockup.kind.eq(&LockupKind::Constant)
             && self.lockup.period.ne(&LockupPeriod::None)
             && !self.lockup.cooldown_requested
     }
@@ -86,10 +254,17 @@ impl DepositEntry {
 mod tests {
     use super::*;
     use crate::state::{
-        LockupKind::{Constant, None},
-        LockupPeriod,
+        LockupKind::{Constant, Daily, None},
+        LockupPeriod, COOLDOWN_SECS, SECONDS_PER_DAY,
     };
 
+    fn registrar_with_mode(voting_power_mode: VotingPowerMode) -> Registrar {
+        Registrar {
+            voting_power_mode,
+            ..Default::default()
+        }
+    }
+
     #[test]
     pub fn far_future_lockup_start_test() -> Result<()> {
         // Check that voting power stays correct even if the lockup is very far in the
@@ -115,15 +290,193 @@ mod tests {
         assert_eq!(baseline_vote_weight, 20_000);
 
         // The timestamp 100_000 is very far before the lockup_start timestamp
-        let withdrawable = deposit.amount_unlocked();
+        let withdrawable = deposit.amount_unlocked(100_000).unwrap();
         assert_eq!(withdrawable, 0);
 
-        let voting_power = deposit.voting_power().unwrap();
+        let voting_power = deposit
+            .voting_power(&registrar_with_mode(VotingPowerMode::Flat), lockup_start)
+            .unwrap();
         assert_eq!(voting_power, 20_000);
 
         Ok(())
     }
 
+    #[test]
+    fn test_voting_power_flat() {
+        let amount = 20_000;
+        let deposit = DepositEntry {
+            amount_deposited_native: amount,
+            lockup: Lockup::new(Constant, 100, LockupPeriod::OneYear).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            deposit
+                .voting_power(&registrar_with_mode(VotingPowerMode::Flat), 150)
+                .unwrap(),
+            amount
+        );
+    }
+
+    #[test]
+    fn test_voting_power_period_multiplier() {
+        let amount = 20_000;
+        let registrar = registrar_with_mode(VotingPowerMode::PeriodMultiplier);
+        let period = LockupPeriod::OneYear;
+        let mut deposit = DepositEntry {
+            amount_deposited_native: amount,
+            lockup: Lockup::new(Constant, 100, period).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            deposit.voting_power(&registrar, 150).unwrap(),
+            amount * period.multiplier()
+        );
+        // expired lockups fall back to the Flex multiplier
+        assert_eq!(
+            deposit
+                .voting_power(&registrar, 100 + period.to_secs())
+                .unwrap(),
+            amount * LockupPeriod::Flex.multiplier()
+        );
+
+        deposit.lockup.cooldown_requested = true;
+        assert_eq!(deposit.voting_power(&registrar, 150).unwrap(), amount);
+
+        let deposit = DepositEntry {
+            amount_deposited_native: amount,
+            lockup: Lockup::new(None, 100, LockupPeriod::None).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        assert_eq!(deposit.voting_power(&registrar, 150).unwrap(), amount);
+    }
+
+    #[test]
+    fn test_voting_power_decaying() {
+        let amount = 20_000;
+        let registrar = registrar_with_mode(VotingPowerMode::Decaying);
+        let period = LockupPeriod::OneYear;
+        let deposit = DepositEntry {
+            amount_deposited_native: amount,
+            lockup: Lockup::new(Constant, 100, period).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            deposit.voting_power(&registrar, 100).unwrap(),
+            amount * period.multiplier()
+        );
+        assert_eq!(
+            deposit
+                .voting_power(&registrar, 100 + period.to_secs() / 2)
+                .unwrap(),
+            amount + amount * (period.multiplier() - 1) / 2
+        );
+        assert_eq!(
+            deposit
+                .voting_power(&registrar, 100 + period.to_secs())
+                .unwrap(),
+            amount
+        );
+    }
+
+    #[test]
+    fn test_voting_power_locked_guaranteed() {
+        let amount = 20_000;
+        let registrar = registrar_with_mode(VotingPowerMode::PeriodMultiplier);
+        let period = LockupPeriod::ThreeMonths;
+        let end_ts = 100 + period.to_secs();
+        let mut deposit = DepositEntry {
+            amount_deposited_native: amount,
+            lockup: Lockup::new(Constant, 100, period).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+
+        // still locked up
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, 100, 200)
+                .unwrap(),
+            amount * period.multiplier()
+        );
+        // might be in cooldown, but can't be withdrawn yet
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, 100, end_ts + COOLDOWN_SECS - 1)
+                .unwrap(),
+            amount
+        );
+        // might be withdrawn already
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, 100, end_ts + COOLDOWN_SECS)
+                .unwrap(),
+            0
+        );
+        // the lockup has expired, the cooldown starts at the earliest now
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(
+                    &registrar,
+                    end_ts + 50,
+                    end_ts + 50 + COOLDOWN_SECS - 1
+                )
+                .unwrap(),
+            amount
+        );
+
+        deposit.lockup.cooldown_requested = true;
+        deposit.lockup.cooldown_ends_at = end_ts + 10;
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, end_ts, end_ts + 9)
+                .unwrap(),
+            amount
+        );
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, end_ts, end_ts + 10)
+                .unwrap(),
+            0
+        );
+
+        // unlocked tokens can be withdrawn at any time
+        let deposit = DepositEntry {
+            amount_deposited_native: amount,
+            lockup: Lockup::new(None, 100, LockupPeriod::None).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, 100, 100)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_earliest_withdrawal_ts() {
+        let period = LockupPeriod::ThreeMonths;
+        let end_ts = 100 + period.to_secs();
+        let mut deposit = DepositEntry {
+            amount_deposited_native: 20_000,
+            lockup: Lockup::new(Constant, 100, period).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        assert_eq!(deposit.earliest_withdrawal_ts(100, 10), end_ts + 10);
+        assert_eq!(deposit.earliest_withdrawal_ts(end_ts + 50, 10), end_ts + 60);
+
+        // the cooldown end is fixed once requested
+        deposit.lockup.cooldown_requested = true;
+        deposit.lockup.cooldown_ends_at = end_ts + 5;
+        assert_eq!(deposit.earliest_withdrawal_ts(end_ts, 10), end_ts + 5);
+    }
+
     #[test]
     fn test_weighted_stake_unused() {
         let deposit = DepositEntry {
@@ -231,4 +584,94 @@ mod tests {
         };
         assert_eq!(deposit.weighted_stake(50), 0);
     }
+
+    #[test]
+    fn test_vesting_amounts() {
+        let amount = 5_000;
+        let mut deposit = DepositEntry {
+            amount_deposited_native: amount,
+            amount_initially_locked_native: amount,
+            lockup: Lockup::new(Daily, 0, LockupPeriod::Flex).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        assert_eq!(deposit.amount_locked(0).unwrap(), amount);
+        assert_eq!(deposit.amount_unlocked(SECONDS_PER_DAY - 1).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(SECONDS_PER_DAY).unwrap(), 1_000);
+        assert_eq!(deposit.amount_locked(3 * SECONDS_PER_DAY).unwrap(), 2_000);
+        assert_eq!(
+            deposit.amount_unlocked(10 * SECONDS_PER_DAY).unwrap(),
+            amount
+        );
+
+        // withdrawing vested tokens doesn't change the schedule
+        deposit.amount_deposited_native -= 3_000;
+        assert_eq!(deposit.amount_unlocked(3 * SECONDS_PER_DAY).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(4 * SECONDS_PER_DAY).unwrap(), 1_000);
+
+        // vesting deposits are never staked
+        assert_eq!(deposit.weighted_stake(0), 0);
+        assert_eq!(
+            deposit
+                .voting_power(&registrar_with_mode(VotingPowerMode::PeriodMultiplier), 0)
+                .unwrap(),
+            2_000
+        );
+        assert_eq!(deposit.earliest_withdrawal_ts(0, COOLDOWN_SECS), 0);
+    }
+
+    #[test]
+    fn test_vesting_locked_guaranteed() {
+        let amount = 5_000;
+        let deposit = DepositEntry {
+            amount_deposited_native: amount,
+            amount_initially_locked_native: amount,
+            lockup: Lockup::new(Daily, 0, LockupPeriod::Flex).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        let registrar = registrar_with_mode(VotingPowerMode::PeriodMultiplier);
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, 0, 2 * SECONDS_PER_DAY)
+                .unwrap(),
+            3_000
+        );
+        assert_eq!(
+            deposit
+                .voting_power_locked_guaranteed(&registrar, 0, 5 * SECONDS_PER_DAY)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_add_to_vesting() {
+        let mut deposit = DepositEntry {
+            amount_deposited_native: 5_000,
+            amount_initially_locked_native: 5_000,
+            lockup: Lockup::new(Daily, 0, LockupPeriod::Flex).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+
+        let curr_ts = 2 * SECONDS_PER_DAY + 10;
+        deposit.add_to_vesting(curr_ts, 6_000).unwrap();
+        assert_eq!({ deposit.amount_deposited_native }, 11_000);
+        assert_eq!({ deposit.amount_initially_locked_native }, 9_000);
+        assert_eq!({ deposit.lockup.start_ts }, 2 * SECONDS_PER_DAY);
+        // the already vested tokens stay withdrawable
+        assert_eq!(deposit.amount_unlocked(curr_ts).unwrap(), 2_000);
+        assert_eq!(deposit.amount_unlocked(3 * SECONDS_PER_DAY).unwrap(), 5_000);
+        assert_eq!(
+            deposit.amount_unlocked(5 * SECONDS_PER_DAY).unwrap(),
+            11_000
+        );
+
+        // expired schedules can't be topped up
+        assert!(deposit.add_to_vesting(5 * SECONDS_PER_DAY, 1).is_err());
+
+        deposit.lockup.kind = Constant;
+        assert!(deposit.add_to_vesting(0, 1).is_err());
+    }
 }
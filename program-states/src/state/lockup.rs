@@ -5,9 +5,16 @@ use borsh::{BorshDeserialize, BorshSerialize};
 /// Seconds in one day.
 pub const SECONDS_PER_DAY: u64 = 86_400;
 
-/// Seconds in cooldown (5 days)
+/// Seconds in one month, as far as monthly vesting is concerned.
+pub const SECONDS_PER_MONTH: u64 = SECONDS_PER_DAY * 30;
+
+/// Seconds in cooldown (5 days), used when a registrar is created.
 pub const COOLDOWN_SECS: u64 = 86_400 * 5;
 
+/// Min seconds between two delegate changes of a deposit (5 days), used when a
+/// registrar is created.
+pub const DELEGATE_UPDATE_DIFF_THRESHOLD: u64 = SECONDS_PER_DAY * 5;
+
 #[zero_copy]
 #[derive(Default, Debug)]
 pub struct Lockup {
@@ -38,8 +45,7 @@ impl Lockup {
     /// Create lockup for a given period
     pub fn new(kind: LockupKind, start_ts: u64, period: LockupPeriod) -> Result<Self> {
         require!(
-            (kind == LockupKind::None && period == LockupPeriod::None)
-                || (kind == LockupKind::Constant && period != LockupPeriod::None),
+            (kind == LockupKind::None) == (period == LockupPeriod::None),
             MplStakingError::InvalidLockupKind
         );
 
@@ -61,6 +67,34 @@ impl Lockup {
         })
     }
 
+    /// Create a vesting lockup of `periods` periods of `kind`, starting at `start_ts`.
+    ///
+    /// Unlike `new`, the duration isn't given by a `LockupPeriod`, which is left
+    /// as `None`: vesting deposits aren't staked, so only the schedule matters.
+    pub fn new_vesting(kind: LockupKind, start_ts: u64, periods: u32) -> Result<Self> {
+        require!(
+            kind.is_vesting() && periods > 0,
+            MplStakingError::InvalidLockupKind
+        );
+
+        let end_ts = kind
+            .period_secs()
+            .checked_mul(periods as u64)
+            .and_then(|duration| start_ts.checked_add(duration))
+            .ok_or(MplStakingError::InvalidTimestampArguments)?;
+
+        Ok(Self {
+            kind,
+            start_ts,
+            end_ts,
+            period: LockupPeriod::None,
+            cooldown_ends_at: 0,
+            cooldown_requested: false,
+            _reserved0: [0; 16],
+            _reserved1: [0; 5],
+        })
+    }
+
     /// True when the lockup is finished.
     pub fn expired(&self, curr_ts: u64) -> bool {
         self.seconds_left(curr_ts) == 0
@@ -110,34 +144,36 @@ impl Lockup {
     }
 
     /// Returns the total amount of periods in the lockup.
+    ///
+    /// Periods are counted back from `end_ts`. If the lockup isn't a multiple of
+    /// the period length, the first period is shorter than the others.
     pub fn periods_total(&self) -> Result<u64> {
         let period_secs = self.kind.period_secs();
         if period_secs == 0 {
             return Ok(0);
         }
 
-        let lockup_secs = self.seconds_left(self.start_ts);
-        require_eq!(
-            lockup_secs % period_secs,
-            0,
-            MplStakingError::InvalidLockupPeriod
-        );
-
-        Ok(lockup_secs.checked_div(period_secs).unwrap())
+        Ok(self
+            .seconds_left(self.start_ts)
+            .checked_add(period_secs.saturating_sub(1))
+            .ok_or(MplStakingError::InvalidTimestampArguments)?
+            .checked_div(period_secs)
+            .unwrap())
     }
 
     /// Remove the vesting periods that are now in the past.
     pub fn remove_past_periods(&mut self, curr_ts: u64) -> Result<()> {
-        let periods = self.period_current(curr_ts)?;
+        // periods are counted back from the end, the first one may be shorter
+        let periods_left = self.periods_left(curr_ts)?;
         let period_secs = self.kind.period_secs();
         self.start_ts = self
-            .start_ts
-            .checked_add(
-                periods
+            .end_ts
+            .saturating_sub(
+                periods_left
                     .checked_mul(period_secs)
                     .ok_or(MplStakingError::InvalidTimestampArguments)?,
             )
-            .unwrap();
+            .max(self.start_ts);
         require_gte!(
             self.end_ts,
             self.start_ts,
@@ -168,6 +204,19 @@ impl Lockup {
             LockupPeriod::Flex.multiplier()
         }
     }
+
+    /// Like `multiplier`, but with the registrar's voting multipliers. Only used
+    /// for the voting power.
+    pub fn voting_multiplier(&self, curr_ts: u64, voting_multipliers: &VotingMultipliers) -> u64 {
+        if curr_ts < self.start_ts || self.cooldown_requested {
+            return 0;
+        }
+        if self.end_ts > curr_ts {
+            voting_multipliers.multiplier(self.period)
+        } else {
+            voting_multipliers.multiplier(LockupPeriod::Flex)
+        }
+    }
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -186,6 +235,15 @@ impl Default for LockupPeriod {
 }
 
 impl LockupPeriod {
+    /// All periods a deposit can be locked up for, in increasing order.
+    pub const LOCKABLE: [LockupPeriod; 4] = [
+        LockupPeriod::Flex,
+        LockupPeriod::ThreeMonths,
+        LockupPeriod::SixMonths,
+        LockupPeriod::OneYear,
+    ];
+
+    /// Duration of the period, the same as in the rewards program.
     pub fn to_secs(&self) -> u64 {
         match self {
             LockupPeriod::ThreeMonths => SECONDS_PER_DAY * 90,
@@ -196,6 +254,8 @@ impl LockupPeriod {
         }
     }
 
+    /// Multiplier of the weighted stake during the period, the same as in the
+    /// rewards program. The default voting power multiplier of the period.
     pub fn multiplier(&self) -> u64 {
         match self {
             LockupPeriod::None => 0,
@@ -207,6 +267,58 @@ impl LockupPeriod {
     }
 }
 
+/// Voting power multipliers of the lockup periods, configured per registrar.
+///
+/// The durations of the periods and the multipliers of the weighted stake stay
+/// fixed, since the rewards program derives them from the `LockupPeriod` itself.
+#[zero_copy]
+#[derive(Debug)]
+pub struct VotingMultipliers {
+    /// Factor the staked amount is scaled by during each lockup period, in the
+    /// order of `LockupPeriod::LOCKABLE`.
+    pub multipliers: [u64; 4],
+}
+const_assert!(std::mem::size_of::<VotingMultipliers>() == 4 * 8);
+
+impl Default for VotingMultipliers {
+    fn default() -> Self {
+        Self {
+            multipliers: LockupPeriod::LOCKABLE.map(|period| period.multiplier()),
+        }
+    }
+}
+
+impl VotingMultipliers {
+    fn index(period: LockupPeriod) -> Option<usize> {
+        LockupPeriod::LOCKABLE.iter().position(|p| *p == period)
+    }
+
+    /// Multiplier of the period, zero for `LockupPeriod::None`.
+    pub fn multiplier(&self, period: LockupPeriod) -> u64 {
+        Self::index(period).map_or(0, |idx| self.multipliers[idx])
+    }
+
+    /// The largest multiplier, which is the one of the longest period.
+    pub fn max_multiplier(&self) -> u64 {
+        self.multiplier(LockupPeriod::OneYear)
+    }
+
+    /// Longer periods can't have a smaller multiplier, and Flex must have a
+    /// multiplier of at least one.
+    pub fn validate(&self) -> Result<()> {
+        let multipliers = self.multipliers;
+        require!(
+            multipliers[0] > 0,
+            MplStakingError::InvalidVotingMultipliers
+        );
+        require!(
+            multipliers.windows(2).all(|w| w[0] <= w[1]),
+            MplStakingError::InvalidVotingMultipliers
+        );
+        Ok(())
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
 pub enum LockupKind {
     /// No lockup, tokens can be withdrawn as long as not engaged in a proposal.
@@ -214,6 +326,12 @@ pub enum LockupKind {
     /// Lock up permanently. The number of days specified becomes the minimum
     /// unlock period when the deposit (or a part of it) is changed to None.
     Constant,
+    /// Vests linearly every day until the end of the lockup period.
+    /// Vested tokens can be withdrawn without unlocking them first.
+    Daily,
+    /// Vests linearly every month until the end of the lockup period.
+    /// Vested tokens can be withdrawn without unlocking them first.
+    Monthly,
 }
 
 impl Default for LockupKind {
@@ -231,13 +349,22 @@ impl LockupKind {
         match self {
             LockupKind::None => 0,
             LockupKind::Constant => SECONDS_PER_DAY, // arbitrary choice
+            LockupKind::Daily => SECONDS_PER_DAY,
+            LockupKind::Monthly => SECONDS_PER_MONTH,
         }
     }
 
+    /// Whether the deposited tokens unlock gradually during the lockup.
+    pub fn is_vesting(&self) -> bool {
+        matches!(self, LockupKind::Daily | LockupKind::Monthly)
+    }
+
     /// Lockups cannot decrease in strictness
     pub fn strictness(&self) -> u8 {
         match self {
             LockupKind::None => 0,
+            LockupKind::Daily => 1,
+            LockupKind::Monthly => 1,
             LockupKind::Constant => 3,
         }
     }
@@ -273,4 +400,102 @@ mod tests {
         lockup.cooldown_requested = true;
         assert_eq!(lockup.multiplier(200), 0);
     }
+
+    #[test]
+    fn test_lockup_voting_multiplier_uses_period_table() {
+        let voting_multipliers = VotingMultipliers {
+            multipliers: [1, 3, 5, 10],
+        };
+        let period = LockupPeriod::SixMonths;
+        let lockup = Lockup::new(LockupKind::Constant, 100, period).unwrap();
+        let end_ts = 100 + period.to_secs();
+        assert_eq!({ lockup.end_ts }, end_ts);
+        assert_eq!(lockup.voting_multiplier(end_ts - 1, &voting_multipliers), 5);
+        assert_eq!(lockup.voting_multiplier(end_ts, &voting_multipliers), 1);
+        assert_eq!(voting_multipliers.max_multiplier(), 10);
+        // the weighted stake keeps the multiplier of the rewards program
+        assert_eq!(lockup.multiplier(end_ts - 1), period.multiplier());
+    }
+
+    #[test]
+    fn test_lockup_period_table_validate() {
+        assert!(VotingMultipliers::default().validate().is_ok());
+
+        let valid = VotingMultipliers {
+            multipliers: [1, 1, 5, 10],
+        };
+        assert!(valid.validate().is_ok());
+
+        for invalid in [
+            VotingMultipliers {
+                multipliers: [0, 1, 5, 10],
+            },
+            VotingMultipliers {
+                multipliers: [1, 3, 2, 10],
+            },
+        ] {
+            assert!(invalid.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_vesting_periods() {
+        let lockup = Lockup::new(LockupKind::Daily, 100, LockupPeriod::Flex).unwrap();
+        assert_eq!(lockup.periods_total().unwrap(), 5);
+        assert_eq!(lockup.period_current(99).unwrap(), 0);
+        assert_eq!(lockup.period_current(100).unwrap(), 0);
+        assert_eq!(lockup.period_current(100 + SECONDS_PER_DAY - 1).unwrap(), 0);
+        assert_eq!(lockup.period_current(100 + SECONDS_PER_DAY).unwrap(), 1);
+        assert_eq!(lockup.period_current(100 + 5 * SECONDS_PER_DAY).unwrap(), 5);
+        assert_eq!(lockup.period_current(100 + 6 * SECONDS_PER_DAY).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_vesting_periods_shorter_first_period() {
+        // 365 days are 12 months and 5 days
+        let lockup = Lockup::new(LockupKind::Monthly, 0, LockupPeriod::OneYear).unwrap();
+        assert_eq!(lockup.periods_total().unwrap(), 13);
+        assert_eq!(lockup.period_current(5 * SECONDS_PER_DAY - 1).unwrap(), 0);
+        assert_eq!(lockup.period_current(5 * SECONDS_PER_DAY).unwrap(), 1);
+        assert_eq!(
+            lockup
+                .period_current(5 * SECONDS_PER_DAY + SECONDS_PER_MONTH)
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_remove_past_periods() {
+        let mut lockup = Lockup::new(LockupKind::Monthly, 0, LockupPeriod::OneYear).unwrap();
+        let curr_ts = 5 * SECONDS_PER_DAY + SECONDS_PER_MONTH + 10;
+        lockup.remove_past_periods(curr_ts).unwrap();
+        assert_eq!({ lockup.start_ts }, 5 * SECONDS_PER_DAY + SECONDS_PER_MONTH);
+        assert_eq!(lockup.periods_total().unwrap(), 11);
+        assert_eq!(lockup.period_current(curr_ts).unwrap(), 0);
+
+        // after the end, nothing is left
+        lockup.remove_past_periods(400 * SECONDS_PER_DAY).unwrap();
+        assert_eq!({ lockup.start_ts }, { lockup.end_ts });
+        assert_eq!(lockup.periods_total().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_lockup_kind_requires_period() {
+        assert!(Lockup::new(LockupKind::Daily, 0, LockupPeriod::None).is_err());
+        assert!(Lockup::new(LockupKind::None, 0, LockupPeriod::Flex).is_err());
+        assert!(Lockup::new(LockupKind::Monthly, 0, LockupPeriod::SixMonths).is_ok());
+    }
+
+    #[test]
+    fn test_new_vesting() {
+        let lockup = Lockup::new_vesting(LockupKind::Monthly, 100, 12).unwrap();
+        assert_eq!({ lockup.end_ts }, 100 + 12 * SECONDS_PER_MONTH);
+        assert_eq!(lockup.periods_total().unwrap(), 12);
+        assert_eq!({ lockup.period }, LockupPeriod::None);
+
+        assert!(Lockup::new_vesting(LockupKind::Daily, 0, 0).is_err());
+        assert!(Lockup::new_vesting(LockupKind::Constant, 0, 3).is_err());
+        assert!(Lockup::new_vesting(LockupKind::Daily, u64::MAX, 1).is_err());
+    }
 }
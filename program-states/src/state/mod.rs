@@ -1,11 +1,17 @@
+pub use delegate_profile::*;
+pub use delegated_stake::*;
 pub use deposit_entry::*;
 pub use lockup::*;
+pub use penalty::*;
 pub use registrar::*;
 pub use voter::*;
 pub use voting_mint_config::*;
 
+pub mod delegate_profile;
+pub mod delegated_stake;
 pub mod deposit_entry;
 pub mod lockup;
+pub mod penalty;
 pub mod registrar;
 pub mod voter;
 pub mod voting_mint_config;
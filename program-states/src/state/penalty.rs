@@ -0,0 +1,50 @@
+use crate::state::SECONDS_PER_DAY;
+use anchor_lang::prelude::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// The penalties the realm authority may apply to a voter.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PenaltyKind {
+    /// Marks an empty slot of the penalty log.
+    None,
+    RestrictTokenflow,
+    AllowTokenflow,
+    RestrictBatchMinting,
+    Slash,
+    DecreaseRewards,
+}
+
+impl Default for PenaltyKind {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// A penalty applied to a voter, as kept in the voter's penalty log.
+///
+/// The log lives in the voter's former reserved bytes, so records are kept small:
+/// amounts and authorities are only part of the events emitted by the penalties.
+#[zero_copy]
+#[derive(Default, Debug)]
+pub struct PenaltyRecord {
+    pub kind: PenaltyKind,
+    /// Reason code chosen by the realm authority.
+    pub reason: u8,
+    /// Day the penalty was applied at, as days since the unix epoch.
+    pub day: u16,
+}
+const_assert!(std::mem::size_of::<PenaltyRecord>() == 1 + 1 + 2);
+
+impl PenaltyRecord {
+    pub fn new(kind: PenaltyKind, reason: u8, curr_ts: u64) -> Self {
+        Self {
+            kind,
+            reason,
+            day: u16::try_from(curr_ts / SECONDS_PER_DAY).unwrap_or(u16::MAX),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kind == PenaltyKind::None
+    }
+}
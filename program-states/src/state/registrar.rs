@@ -1,8 +1,24 @@
-use crate::{error::*, state::voting_mint_config::VotingMintConfig};
+use crate::{
+    error::*,
+    state::{
+        voting_mint_config::{VotingMint, VotingMintConfig},
+        Voter, VotingMultipliers, COOLDOWN_SECS, DELEGATE_UPDATE_DIFF_THRESHOLD,
+    },
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::{
+    cell::{Ref, RefMut},
+    mem::size_of,
+    ops::{Deref, DerefMut},
+};
 
 /// Instance of a voting rights distributor.
+///
+/// The account may have been grown by `grow_registrar`, in which case a
+/// `RegistrarExtension` and all voting mints follow the `Registrar`. Load it
+/// with `RegistrarLoader` to see all voting mints.
 #[account(zero_copy)]
 #[derive(Default)]
 pub struct Registrar {
@@ -12,44 +28,510 @@ pub struct Registrar {
     pub realm_authority: Pubkey,
     pub reward_pool: Pubkey,
     /// Storage for voting mints and their configuration.
-    /// The length should be adjusted for one's use case.
+    /// Unused once the registrar has been grown, see `RegistrarExtension`.
     pub voting_mints: [VotingMintConfig; 2],
     pub bump: u8,
-    pub padding: [u8; 7],
+    pub max_voter_weight_record_bump: u8,
+    /// How deposits are turned into voting power.
+    pub voting_power_mode: VotingPowerMode,
+    /// Whether staked deposits vote with their delegate instead of their owner,
+    /// see `DelegatedStake`.
+    pub delegate_voting: bool,
+    pub padding: [u8; 4],
+    /// Voting power multipliers of the lockup periods.
+    /// Appended to the original layout, see `migrate_registrar`.
+    pub voting_multipliers: VotingMultipliers,
+    /// Parameters that can be changed by the realm authority.
+    /// Appended to the original layout, see `migrate_registrar`.
+    pub params: RegistrarParams,
 }
-const_assert!(std::mem::size_of::<Registrar>() == 5 * 32 + 2 * 64 + 1 + 7);
+const_assert!(std::mem::size_of::<Registrar>() == 5 * 32 + 2 * 64 + 1 + 1 + 1 + 1 + 4 + 32 + 24);
 const_assert!(std::mem::size_of::<Registrar>() % 8 == 0);
 
 pub const REGISTRAR_DISCRIMINATOR: [u8; 8] = [193, 202, 205, 51, 78, 168, 150, 128];
 
+/// Header of the voting mints appended to a grown registrar account.
+#[zero_copy]
+#[derive(Default)]
+pub struct RegistrarExtension {
+    /// Layout version of the registrar account.
+    pub version: u8,
+    pub _reserved: [u8; 7],
+}
+const_assert!(std::mem::size_of::<RegistrarExtension>() == 8);
+
 impl Registrar {
+    /// Layout of registrar accounts with all voting mints after a `RegistrarExtension`.
+    pub const VERSION_EXTENDED: u8 = 1;
+    /// Number of voting mints stored in `Registrar` itself.
+    pub const INLINE_VOTING_MINTS: usize = 2;
+    /// Bounded by the number of mint accounts `max_vote_weight` can be given.
+    pub const MAX_VOTING_MINTS: usize = 16;
+    /// Size of the registrar accounts created before `voting_multipliers` and `params` were added.
+    pub const LEGACY_SPACE: usize = 8 + 5 * 32 + 2 * 64 + 1 + 7;
+
+    /// Size of a registrar account holding `voting_mints` voting mints.
+    pub fn space(voting_mints: usize) -> usize {
+        let space = 8 + size_of::<Registrar>();
+        if voting_mints <= Self::INLINE_VOTING_MINTS {
+            space
+        } else {
+            space + size_of::<RegistrarExtension>() + voting_mints * size_of::<VotingMint>()
+        }
+    }
+
+    /// The voting mints stored after the `Registrar`, given the account data following it.
+    fn extra_voting_mints(extension: &[u8]) -> &[VotingMint] {
+        let header_len = size_of::<RegistrarExtension>();
+        if extension.len() < header_len || extension[0] != Self::VERSION_EXTENDED {
+            return &[];
+        }
+        let entries = &extension[header_len..];
+        let len = entries.len() / size_of::<VotingMint>();
+        // SAFETY: VotingMint is packed, so it's aligned to a byte, and every bit
+        // pattern of its fields is valid
+        unsafe { std::slice::from_raw_parts(entries.as_ptr() as *const VotingMint, len) }
+    }
+
+    fn extra_voting_mints_mut(extension: &mut [u8]) -> &mut [VotingMint] {
+        let header_len = size_of::<RegistrarExtension>();
+        if extension.len() < header_len || extension[0] != Self::VERSION_EXTENDED {
+            return &mut [];
+        }
+        let entries = &mut extension[header_len..];
+        let len = entries.len() / size_of::<VotingMint>();
+        // SAFETY: see `extra_voting_mints`
+        unsafe { std::slice::from_raw_parts_mut(entries.as_mut_ptr() as *mut VotingMint, len) }
+    }
+}
+
+/// A registrar together with the voting mints that were appended to its account
+/// by `grow_registrar`. A grown registrar keeps all its voting mints there, so
+/// the ones in `Registrar` are only used as long as the registrar isn't grown.
+pub struct DynamicRegistrar<R, M> {
+    registrar: R,
+    extra_voting_mints: M,
+}
+
+pub type RegistrarRef<'a> = DynamicRegistrar<Ref<'a, Registrar>, Ref<'a, [VotingMint]>>;
+pub type RegistrarRefMut<'a> = DynamicRegistrar<RefMut<'a, Registrar>, RefMut<'a, [VotingMint]>>;
+
+impl<R, M> DynamicRegistrar<R, M>
+where
+    R: Deref<Target = Registrar>,
+    M: Deref<Target = [VotingMint]>,
+{
+    pub fn new(registrar: R, extra_voting_mints: M) -> Self {
+        Self {
+            registrar,
+            extra_voting_mints,
+        }
+    }
+
+    fn is_extended(&self) -> bool {
+        !self.extra_voting_mints.is_empty()
+    }
+
+    /// Number of voting mints, configured or not.
+    pub fn voting_mints_len(&self) -> usize {
+        if self.is_extended() {
+            self.extra_voting_mints.len()
+        } else {
+            self.registrar.voting_mints.len()
+        }
+    }
+
+    pub fn voting_mints(&self) -> impl Iterator<Item = &VotingMintConfig> {
+        let (inline, extra) = if self.is_extended() {
+            (&[][..], &self.extra_voting_mints[..])
+        } else {
+            (&self.registrar.voting_mints[..], &[][..])
+        };
+        inline.iter().chain(extra.iter().map(|m| &m.config))
+    }
+
+    /// The voting mint at `idx`, whether it's configured or not.
+    pub fn voting_mint(&self, idx: usize) -> Result<&VotingMintConfig> {
+        self.voting_mints()
+            .nth(idx)
+            .ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))
+    }
+
+    /// Converts an amount of native tokens of the voting mint at `idx` into vote weight.
+    ///
+    /// The voting mints stored in `Registrar` count 1:1, the ones of a grown
+    /// registrar use their exchange rate, see `VotingMint::vote_weight`.
+    pub fn vote_weight(&self, idx: usize, amount_native: u64) -> Result<u64> {
+        if self.is_extended() {
+            self.extra_voting_mints
+                .get(idx)
+                .ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))?
+                .vote_weight(amount_native)
+        } else {
+            require_gt!(
+                self.registrar.voting_mints.len(),
+                idx,
+                MplStakingError::OutOfBoundsVotingMintConfigIndex
+            );
+            Ok(amount_native)
+        }
+    }
+
+    /// Whether the deposit entries of the voting mint at `idx` are counted, which
+    /// is the case for the voting mints of a grown registrar, unless they were
+    /// configured before it was grown and haven't been recounted yet.
+    pub fn counts_deposit_entries(&self, idx: usize) -> bool {
+        self.extra_voting_mints
+            .get(idx)
+            .is_some_and(|voting_mint| !voting_mint.deposit_entries_untracked)
+    }
+
     pub fn voting_mint_config_index(&self, mint: Pubkey) -> Result<usize> {
-        self.voting_mints
-            .iter()
+        self.voting_mints()
             .position(|r| r.mint == mint)
             .ok_or_else(|| error!(MplStakingError::VotingMintNotFound))
     }
 
+    /// The max vote weight, given the accounts of all configured voting mints.
+    ///
+    /// See `max_vote_weight_from_supplies` for the definition.
     pub fn max_vote_weight(&self, mint_accounts: &[AccountInfo]) -> Result<u64> {
-        self.voting_mints
-            .iter()
-            .try_fold(0_u64, |mut sum, voting_mint_config| -> Result<u64> {
-                if !voting_mint_config.in_use() {
-                    return Ok(sum);
-                }
+        let mint_supplies = self
+            .voting_mints()
+            .filter(|voting_mint_config| voting_mint_config.in_use())
+            .map(|voting_mint_config| {
                 let mint_account = mint_accounts
                     .iter()
                     .find(|a| a.key() == voting_mint_config.mint)
                     .ok_or_else(|| error!(MplStakingError::VotingMintNotFound))?;
                 let mint = Account::<Mint>::try_from(mint_account)?;
-                sum = sum
-                    .checked_add(mint.supply)
-                    .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))?;
-                sum = sum
-                    .checked_add(mint.supply)
-                    .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))?;
-                Ok(sum)
+                Ok((voting_mint_config.mint, mint.supply))
             })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.max_vote_weight_from_supplies(&mint_supplies)
+    }
+
+    /// The max vote weight is the vote weight a single voter would have
+    /// if they had staked the whole supply of every voting mint for the
+    /// period with the largest multiplier.
+    ///
+    /// That's the sum over all configured voting mints of their supply, scaled
+    /// by the max multiplier of the voting power mode and then converted into
+    /// vote weight with the exchange rate of the mint. Converting last rounds
+    /// like the voter weight does, so no voter can have more than the max vote
+    /// weight, however the supply is spread over deposits.
+    pub fn max_vote_weight_from_supplies(&self, mint_supplies: &[(Pubkey, u64)]) -> Result<u64> {
+        let max_multiplier = self
+            .registrar
+            .voting_power_mode
+            .max_multiplier(&self.registrar.voting_multipliers);
+        self.voting_mints().enumerate().try_fold(
+            0_u64,
+            |sum, (idx, voting_mint_config)| -> Result<u64> {
+                if !voting_mint_config.in_use() {
+                    return Ok(sum);
+                }
+                let (_, supply) = mint_supplies
+                    .iter()
+                    .find(|(mint, _)| *mint == voting_mint_config.mint)
+                    .ok_or_else(|| error!(MplStakingError::VotingMintNotFound))?;
+                let max_voting_power = supply
+                    .checked_mul(max_multiplier)
+                    .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))?;
+                sum.checked_add(self.vote_weight(idx, max_voting_power)?)
+                    .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))
+            },
+        )
+    }
+}
+
+impl<R, M> DynamicRegistrar<R, M>
+where
+    R: DerefMut<Target = Registrar>,
+    M: DerefMut<Target = [VotingMint]>,
+{
+    pub fn voting_mint_mut(&mut self, idx: usize) -> Result<&mut VotingMintConfig> {
+        let voting_mint = if self.is_extended() {
+            self.extra_voting_mints.get_mut(idx).map(|m| &mut m.config)
+        } else {
+            self.registrar.voting_mints.get_mut(idx)
+        };
+        voting_mint.ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))
+    }
+
+    /// Sets the exchange rate of the voting mint at `idx`, see `VotingMint`.
+    ///
+    /// The voting mints stored in `Registrar` can only have a rate of 1.
+    pub fn set_vote_weight_rate(
+        &mut self,
+        idx: usize,
+        digit_shift: i8,
+        baseline_vote_weight_scaled_factor: u64,
+    ) -> Result<()> {
+        if self.is_extended() {
+            let voting_mint = self
+                .extra_voting_mints
+                .get_mut(idx)
+                .ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))?;
+            voting_mint.digit_shift = digit_shift;
+            voting_mint.baseline_vote_weight_scaled_factor = baseline_vote_weight_scaled_factor;
+        } else {
+            require!(
+                digit_shift == 0
+                    && baseline_vote_weight_scaled_factor == VotingMint::SCALED_FACTOR_BASE,
+                MplStakingError::RegistrarNotGrown
+            );
+        }
+        Ok(())
+    }
+
+    /// Moves the voting mints stored in `Registrar` to the start of the extension,
+    /// which is where a grown registrar keeps all its voting mints. They keep
+    /// counting 1:1. Their existing deposit entries are unknown, so they can't be removed
+    /// until the count is backfilled by `recount_deposit_entries`.
+    pub fn move_inline_voting_mints(&mut self) {
+        let registrar = &mut *self.registrar;
+        for (config, voting_mint) in registrar
+            .voting_mints
+            .iter_mut()
+            .zip(self.extra_voting_mints.iter_mut())
+        {
+            *voting_mint = VotingMint {
+                deposit_entries_untracked: config.in_use(),
+                ..VotingMint::new(std::mem::take(config))
+            };
+        }
+    }
+
+    /// Counts a new deposit entry of the voting mint at `idx`, if its deposit
+    /// entries are counted, see `counts_deposit_entries`.
+    pub fn open_deposit_entry(&mut self, idx: usize) -> Result<()> {
+        if self.counts_deposit_entries(idx) {
+            let voting_mint = &mut self.extra_voting_mints[idx];
+            voting_mint.open_deposit_entries = voting_mint
+                .open_deposit_entries
+                .checked_add(1)
+                .ok_or_else(|| error!(MplStakingError::ArithmeticOverflow))?;
+        }
+        Ok(())
+    }
+
+    /// Stops counting a closed deposit entry of the voting mint at `idx`.
+    pub fn close_deposit_entry(&mut self, idx: usize) -> Result<()> {
+        if self.counts_deposit_entries(idx) {
+            let voting_mint = &mut self.extra_voting_mints[idx];
+            voting_mint.open_deposit_entries = voting_mint
+                .open_deposit_entries
+                .checked_sub(1)
+                .ok_or_else(|| error!(MplStakingError::ArithmeticOverflow))?;
+        }
+        Ok(())
+    }
+
+    /// Starts counting the deposit entries of the voting mint at `idx` from
+    /// `open_deposit_entries`, the number of used deposit entries of the mint
+    /// across all voters. This backfills the count of voting mints configured
+    /// before the registrar was grown.
+    ///
+    /// The entries opened since the registrar was grown are counted already, so
+    /// the count can only be raised. Otherwise a wrong count could let a mint be
+    /// removed while deposits still refer to it.
+    pub fn recount_deposit_entries(&mut self, idx: usize, open_deposit_entries: u64) -> Result<()> {
+        require!(self.is_extended(), MplStakingError::RegistrarNotGrown);
+        let voting_mint = self
+            .extra_voting_mints
+            .get_mut(idx)
+            .ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))?;
+        require!(
+            voting_mint.config.in_use(),
+            MplStakingError::VotingMintNotFound
+        );
+        require_gte!(
+            open_deposit_entries,
+            voting_mint.open_deposit_entries,
+            MplStakingError::DepositEntriesUndercounted
+        );
+        voting_mint.deposit_entries_untracked = false;
+        voting_mint.open_deposit_entries = open_deposit_entries;
+        Ok(())
+    }
+
+    /// Removes the voting mint at `idx`, which must not have any deposit entries
+    /// left. Its index may be used for another voting mint afterwards.
+    pub fn remove_voting_mint(&mut self, idx: usize) -> Result<()> {
+        require!(self.is_extended(), MplStakingError::RegistrarNotGrown);
+        let voting_mint = self
+            .extra_voting_mints
+            .get_mut(idx)
+            .ok_or_else(|| error!(MplStakingError::OutOfBoundsVotingMintConfigIndex))?;
+        require!(
+            voting_mint.config.in_use(),
+            MplStakingError::VotingMintNotFound
+        );
+        require!(
+            !voting_mint.deposit_entries_untracked && voting_mint.open_deposit_entries == 0,
+            MplStakingError::VotingMintHasDeposits
+        );
+        *voting_mint = VotingMint::default();
+        Ok(())
+    }
+}
+
+impl<R: Deref<Target = Registrar>, M> Deref for DynamicRegistrar<R, M> {
+    type Target = Registrar;
+
+    fn deref(&self) -> &Registrar {
+        &self.registrar
+    }
+}
+
+impl<R: DerefMut<Target = Registrar>, M> DerefMut for DynamicRegistrar<R, M> {
+    fn deref_mut(&mut self) -> &mut Registrar {
+        &mut self.registrar
+    }
+}
+
+/// Loads a registrar account with all its voting mints.
+pub trait RegistrarLoader {
+    fn load_registrar(&self) -> Result<RegistrarRef<'_>>;
+    fn load_registrar_mut(&self) -> Result<RegistrarRefMut<'_>>;
+
+    /// Counts a new deposit entry of the voting mint at `idx`, see
+    /// `DynamicRegistrar::open_deposit_entry`. The registrar only has to be writable
+    /// if the deposit entries of the mint are counted, so instructions on a registrar
+    /// that hasn't been grown don't write-lock it.
+    fn open_deposit_entry(&self, idx: usize) -> Result<()> {
+        let counted = self.load_registrar()?.counts_deposit_entries(idx);
+        if counted {
+            self.load_registrar_mut()?.open_deposit_entry(idx)?;
+        }
+        Ok(())
+    }
+
+    /// Stops counting a closed deposit entry of the voting mint at `idx`, see
+    /// `open_deposit_entry`.
+    fn close_deposit_entry(&self, idx: usize) -> Result<()> {
+        let counted = self.load_registrar()?.counts_deposit_entries(idx);
+        if counted {
+            self.load_registrar_mut()?.close_deposit_entry(idx)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'info> RegistrarLoader for AccountLoader<'info, Registrar> {
+    fn load_registrar(&self) -> Result<RegistrarRef<'_>> {
+        // checks the discriminator
+        drop(self.load()?);
+        let account: &AccountInfo<'info> = self.as_ref();
+        let data = account.try_borrow_data()?;
+        let (registrar, extra_voting_mints) = Ref::map_split(data, |data| {
+            let (registrar, extension) = data[8..].split_at(size_of::<Registrar>());
+            (
+                bytemuck::from_bytes(registrar),
+                Registrar::extra_voting_mints(extension),
+            )
+        });
+        Ok(DynamicRegistrar::new(registrar, extra_voting_mints))
+    }
+
+    fn load_registrar_mut(&self) -> Result<RegistrarRefMut<'_>> {
+        // checks the discriminator and that the account is writable
+        drop(self.load_mut()?);
+        let account: &AccountInfo<'info> = self.as_ref();
+        let data = account.try_borrow_mut_data()?;
+        let (registrar, extra_voting_mints) = RefMut::map_split(data, |data| {
+            let (registrar, extension) = data[8..].split_at_mut(size_of::<Registrar>());
+            (
+                bytemuck::from_bytes_mut(registrar),
+                Registrar::extra_voting_mints_mut(extension),
+            )
+        });
+        Ok(DynamicRegistrar::new(registrar, extra_voting_mints))
+    }
+}
+
+/// Parameters of the registrar that can be changed by the realm authority.
+///
+/// A zero value stands for the default of the parameter, read them with the
+/// accessor methods.
+#[zero_copy]
+#[derive(Debug)]
+pub struct RegistrarParams {
+    /// Seconds between requesting an unlock and being able to withdraw.
+    pub cooldown_secs: u64,
+    /// Min seconds between two delegate changes of a deposit.
+    pub delegate_update_diff_threshold: u64,
+    /// Min weighted stake a voter must own to be chosen as a delegate.
+    pub min_own_weighted_stake: u64,
+}
+const_assert!(std::mem::size_of::<RegistrarParams>() == 3 * 8);
+
+impl Default for RegistrarParams {
+    fn default() -> Self {
+        Self {
+            cooldown_secs: COOLDOWN_SECS,
+            delegate_update_diff_threshold: DELEGATE_UPDATE_DIFF_THRESHOLD,
+            min_own_weighted_stake: Voter::MIN_OWN_WEIGHTED_STAKE,
+        }
+    }
+}
+
+impl RegistrarParams {
+    /// Seconds between requesting an unlock and being able to withdraw.
+    pub fn cooldown_secs(&self) -> u64 {
+        match self.cooldown_secs {
+            0 => COOLDOWN_SECS,
+            cooldown_secs => cooldown_secs,
+        }
+    }
+
+    /// Min seconds between two delegate changes of a deposit.
+    pub fn delegate_update_diff_threshold(&self) -> u64 {
+        match self.delegate_update_diff_threshold {
+            0 => DELEGATE_UPDATE_DIFF_THRESHOLD,
+            threshold => threshold,
+        }
+    }
+
+    /// Min weighted stake a voter must own to be chosen as a delegate.
+    pub fn min_own_weighted_stake(&self) -> u64 {
+        match self.min_own_weighted_stake {
+            0 => Voter::MIN_OWN_WEIGHTED_STAKE,
+            min_own_weighted_stake => min_own_weighted_stake,
+        }
+    }
+}
+
+/// Defines how the deposited tokens are turned into voting power.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VotingPowerMode {
+    /// Every deposited token gives exactly one vote, regardless of the lockup.
+    Flat,
+    /// Staked tokens are scaled by the multiplier of their lockup period,
+    /// the same way as the weighted stake for rewards is.
+    PeriodMultiplier,
+    /// Like `PeriodMultiplier`, but the bonus on top of the deposited amount
+    /// decreases linearly to zero until the end of the lockup.
+    Decaying,
+}
+
+impl Default for VotingPowerMode {
+    fn default() -> Self {
+        Self::Flat
+    }
+}
+
+impl VotingPowerMode {
+    /// The largest factor a deposited amount may be scaled by in this mode.
+    pub fn max_multiplier(&self, voting_multipliers: &VotingMultipliers) -> u64 {
+        match self {
+            VotingPowerMode::Flat => 1,
+            VotingPowerMode::PeriodMultiplier | VotingPowerMode::Decaying => {
+                voting_multipliers.max_multiplier()
+            }
+        }
     }
 }
 
@@ -66,3 +548,479 @@ macro_rules! registrar_seeds {
 }
 
 pub use registrar_seeds;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        DepositEntry, DynamicVoter, Lockup, LockupKind, LockupPeriod, Voter, SECONDS_PER_DAY,
+    };
+    use proptest::prelude::*;
+
+    fn registrar_with_mints(mints: &[Pubkey]) -> Registrar {
+        let mut registrar = Registrar::default();
+        for (idx, mint) in mints.iter().enumerate() {
+            registrar.voting_mints[idx] = VotingMintConfig {
+                mint: *mint,
+                ..Default::default()
+            };
+        }
+        registrar
+    }
+
+    fn inline(registrar: &Registrar) -> DynamicRegistrar<&Registrar, &[VotingMint]> {
+        DynamicRegistrar::new(registrar, &[][..])
+    }
+
+    #[test]
+    fn test_registrar_params_default_when_zeroed() {
+        let params = RegistrarParams {
+            cooldown_secs: 0,
+            delegate_update_diff_threshold: 0,
+            min_own_weighted_stake: 0,
+        };
+        assert_eq!(params.cooldown_secs(), COOLDOWN_SECS);
+        assert_eq!(
+            params.delegate_update_diff_threshold(),
+            DELEGATE_UPDATE_DIFF_THRESHOLD
+        );
+        assert_eq!(
+            params.min_own_weighted_stake(),
+            Voter::MIN_OWN_WEIGHTED_STAKE
+        );
+
+        let params = RegistrarParams {
+            cooldown_secs: 1,
+            delegate_update_diff_threshold: 2,
+            min_own_weighted_stake: 3,
+        };
+        assert_eq!(params.cooldown_secs(), 1);
+        assert_eq!(params.delegate_update_diff_threshold(), 2);
+        assert_eq!(params.min_own_weighted_stake(), 3);
+    }
+
+    #[test]
+    fn test_max_vote_weight_counts_each_supply_once() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let registrar = registrar_with_mints(&[mint_a, mint_b]);
+
+        let max_vote_weight = inline(&registrar)
+            .max_vote_weight_from_supplies(&[(mint_a, 1_000), (mint_b, 250)])
+            .unwrap();
+        assert_eq!(max_vote_weight, 1_250);
+    }
+
+    #[test]
+    fn test_max_vote_weight_ignores_unused_mints() {
+        let mint = Pubkey::new_unique();
+        let registrar = registrar_with_mints(&[mint]);
+
+        let max_vote_weight = inline(&registrar)
+            .max_vote_weight_from_supplies(&[(mint, 1_000)])
+            .unwrap();
+        assert_eq!(max_vote_weight, 1_000);
+    }
+
+    #[test]
+    fn test_max_vote_weight_requires_all_mints() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let registrar = registrar_with_mints(&[mint_a, mint_b]);
+
+        assert!(inline(&registrar)
+            .max_vote_weight_from_supplies(&[(mint_a, 1_000)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_max_vote_weight_overflow() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let registrar = registrar_with_mints(&[mint_a, mint_b]);
+
+        assert!(inline(&registrar)
+            .max_vote_weight_from_supplies(&[(mint_a, u64::MAX), (mint_b, 1)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_max_vote_weight_matches_voter_weight() {
+        // A voter holding the whole supply of every voting mint has the max vote weight.
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let registrar = registrar_with_mints(&[mint_a, mint_b]);
+        let (supply_a, supply_b) = (30_000, 5_000);
+
+        let mut voter = Voter::default();
+        voter.deposits[0] = DepositEntry {
+            amount_deposited_native: 10_000,
+            voting_mint_config_idx: 0,
+            is_used: true,
+            ..Default::default()
+        };
+        voter.deposits[1] = DepositEntry {
+            amount_deposited_native: supply_a - 10_000,
+            voting_mint_config_idx: 0,
+            is_used: true,
+            ..Default::default()
+        };
+        voter.deposits[2] = DepositEntry {
+            amount_deposited_native: supply_b,
+            voting_mint_config_idx: 1,
+            is_used: true,
+            ..Default::default()
+        };
+
+        let max_vote_weight = inline(&registrar)
+            .max_vote_weight_from_supplies(&[(mint_a, supply_a), (mint_b, supply_b)])
+            .unwrap();
+        let voter = DynamicVoter::new(&voter, &[][..]);
+        assert_eq!(
+            voter.weight(&inline(&registrar), 0).unwrap(),
+            max_vote_weight
+        );
+    }
+
+    #[test]
+    fn test_max_vote_weight_matches_voter_weight_with_multipliers() {
+        // A voter who staked the whole supply for the longest period has the max vote weight.
+        let mint = Pubkey::new_unique();
+        let supply = 30_000;
+        let curr_ts = 1_000;
+
+        let mut voter = Voter::default();
+        voter.deposits[0] = DepositEntry {
+            amount_deposited_native: supply,
+            lockup: Lockup::new(LockupKind::Constant, curr_ts, LockupPeriod::OneYear).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        let voter = DynamicVoter::new(&voter, &[][..]);
+
+        for voting_power_mode in [
+            VotingPowerMode::Flat,
+            VotingPowerMode::PeriodMultiplier,
+            VotingPowerMode::Decaying,
+        ] {
+            let registrar = Registrar {
+                voting_power_mode,
+                ..registrar_with_mints(&[mint])
+            };
+            let max_vote_weight = inline(&registrar)
+                .max_vote_weight_from_supplies(&[(mint, supply)])
+                .unwrap();
+            assert_eq!(
+                voter.weight(&inline(&registrar), curr_ts).unwrap(),
+                max_vote_weight
+            );
+        }
+    }
+
+    #[test]
+    fn test_extra_voting_mints() {
+        let (mint_a, mint_b, mint_c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut registrar = registrar_with_mints(&[mint_a, mint_b]);
+        let mut extra_voting_mints = [VotingMint::default(); 3];
+        let mut registrar = DynamicRegistrar::new(&mut registrar, &mut extra_voting_mints[..]);
+
+        registrar.move_inline_voting_mints();
+        assert!(registrar.voting_mints.iter().all(|m| !m.in_use()));
+        assert_eq!(registrar.voting_mints_len(), 3);
+        assert_eq!(registrar.voting_mint_config_index(mint_b).unwrap(), 1);
+        assert!(!registrar.voting_mint(2).unwrap().in_use());
+        assert!(registrar.voting_mint(3).is_err());
+
+        registrar.voting_mint_mut(2).unwrap().mint = mint_c;
+        registrar
+            .set_vote_weight_rate(2, 0, VotingMint::SCALED_FACTOR_BASE)
+            .unwrap();
+        assert_eq!(registrar.voting_mint_config_index(mint_c).unwrap(), 2);
+        let max_vote_weight = registrar
+            .max_vote_weight_from_supplies(&[(mint_a, 1_000), (mint_b, 250), (mint_c, 50)])
+            .unwrap();
+        assert_eq!(max_vote_weight, 1_300);
+    }
+
+    #[test]
+    fn test_extra_voting_mints_layout() {
+        assert_eq!(Registrar::space(2), 8 + size_of::<Registrar>());
+        assert_eq!(
+            Registrar::space(3),
+            8 + size_of::<Registrar>() + 8 + 3 * size_of::<VotingMint>()
+        );
+
+        let mut extension = vec![0_u8; Registrar::space(3) - 8 - size_of::<Registrar>()];
+        assert!(Registrar::extra_voting_mints(&extension).is_empty());
+        extension[0] = Registrar::VERSION_EXTENDED;
+        assert_eq!(Registrar::extra_voting_mints(&extension).len(), 3);
+    }
+
+    #[test]
+    fn test_max_vote_weight_matches_voter_weight_with_rates() {
+        // A voter holding the whole supply of mints with different decimals and rates
+        // has the max vote weight.
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (supply_a, supply_b) = (30_000, 5_000_000);
+        let mut registrar = Registrar {
+            voting_power_mode: VotingPowerMode::PeriodMultiplier,
+            ..registrar_with_mints(&[mint_a, mint_b])
+        };
+        let mut extra_voting_mints = [VotingMint::default(); 3];
+        let mut registrar = DynamicRegistrar::new(&mut registrar, &mut extra_voting_mints[..]);
+        registrar.move_inline_voting_mints();
+        registrar
+            .set_vote_weight_rate(1, -3, VotingMint::SCALED_FACTOR_BASE / 2)
+            .unwrap();
+
+        let curr_ts = 1_000;
+        let mut voter = Voter::default();
+        voter.deposits[0] = DepositEntry {
+            amount_deposited_native: supply_a,
+            voting_mint_config_idx: 0,
+            lockup: Lockup::new(LockupKind::Constant, curr_ts, LockupPeriod::OneYear).unwrap(),
+            is_used: true,
+            ..Default::default()
+        };
+        voter.deposits[1] = DepositEntry {
+            amount_deposited_native: supply_b,
+            voting_mint_config_idx: 1,
+            lockup: voter.deposits[0].lockup,
+            is_used: true,
+            ..Default::default()
+        };
+        let voter = DynamicVoter::new(&voter, &[][..]);
+
+        let max_vote_weight = registrar
+            .max_vote_weight_from_supplies(&[(mint_a, supply_a), (mint_b, supply_b)])
+            .unwrap();
+        let max_multiplier = VotingMultipliers::default().max_multiplier();
+        assert_eq!(max_vote_weight, (supply_a + 2_500) * max_multiplier);
+        assert_eq!(voter.weight(&registrar, curr_ts).unwrap(), max_vote_weight);
+        assert_eq!(voter.weight_baseline(&registrar).unwrap(), supply_a + 2_500);
+    }
+
+    #[test]
+    fn test_vote_weight_rate_requires_grown_registrar() {
+        let mut registrar = registrar_with_mints(&[Pubkey::new_unique()]);
+        let mut registrar = DynamicRegistrar::new(&mut registrar, &mut [][..]);
+        assert!(registrar
+            .set_vote_weight_rate(0, 0, VotingMint::SCALED_FACTOR_BASE)
+            .is_ok());
+        assert!(registrar
+            .set_vote_weight_rate(0, 2, VotingMint::SCALED_FACTOR_BASE)
+            .is_err());
+        assert_eq!(registrar.vote_weight(0, 1_000).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_remove_voting_mint() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut registrar = registrar_with_mints(&[mint_a]);
+        let mut extra_voting_mints = [VotingMint::default(); 3];
+        let mut registrar = DynamicRegistrar::new(&mut registrar, &mut extra_voting_mints[..]);
+
+        registrar.move_inline_voting_mints();
+        registrar.voting_mint_mut(2).unwrap().mint = mint_b;
+
+        // deposits of mints configured before growing were never counted
+        registrar.open_deposit_entry(0).unwrap();
+        assert_eq!({ registrar.extra_voting_mints[0].open_deposit_entries }, 0);
+        assert!(registrar.remove_voting_mint(0).is_err());
+        assert!(registrar.remove_voting_mint(1).is_err());
+
+        registrar.open_deposit_entry(2).unwrap();
+        registrar.open_deposit_entry(2).unwrap();
+        registrar.close_deposit_entry(2).unwrap();
+        assert!(registrar.remove_voting_mint(2).is_err());
+        registrar.close_deposit_entry(2).unwrap();
+        assert!(registrar.close_deposit_entry(2).is_err());
+
+        registrar.remove_voting_mint(2).unwrap();
+        assert!(!registrar.voting_mint(2).unwrap().in_use());
+        assert!(registrar.voting_mint_config_index(mint_b).is_err());
+        assert_eq!(registrar.voting_mint_config_index(mint_a).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_recount_deposit_entries() {
+        let mint_a = Pubkey::new_unique();
+        let mut registrar = registrar_with_mints(&[mint_a]);
+        let mut extra_voting_mints = [VotingMint::default(); 3];
+        let mut registrar = DynamicRegistrar::new(&mut registrar, &mut extra_voting_mints[..]);
+        registrar.move_inline_voting_mints();
+        assert!(!registrar.counts_deposit_entries(0));
+        assert!(registrar.recount_deposit_entries(1, 0).is_err());
+
+        // the backfilled count of a mint configured before growing is kept up to date
+        registrar.recount_deposit_entries(0, 1).unwrap();
+        assert!(!{ registrar.extra_voting_mints[0].deposit_entries_untracked });
+        registrar.open_deposit_entry(0).unwrap();
+        assert!(registrar.counts_deposit_entries(0));
+
+        // the count can't be lowered below the entries counted already
+        assert!(registrar.recount_deposit_entries(0, 1).is_err());
+        registrar.recount_deposit_entries(0, 2).unwrap();
+        registrar.close_deposit_entry(0).unwrap();
+        assert!(registrar.remove_voting_mint(0).is_err());
+        registrar.close_deposit_entry(0).unwrap();
+        registrar.remove_voting_mint(0).unwrap();
+        assert!(registrar.voting_mint_config_index(mint_a).is_err());
+    }
+
+    #[test]
+    fn test_remove_voting_mint_requires_grown_registrar() {
+        let mut registrar = registrar_with_mints(&[Pubkey::new_unique()]);
+        let mut registrar = DynamicRegistrar::new(&mut registrar, &mut [][..]);
+        registrar.open_deposit_entry(0).unwrap();
+        registrar.close_deposit_entry(0).unwrap();
+        assert!(registrar.remove_voting_mint(0).is_err());
+        assert!(registrar.recount_deposit_entries(0, 0).is_err());
+    }
+
+    const LOCKUP_PERIODS: [LockupPeriod; 5] = [
+        LockupPeriod::None,
+        LockupPeriod::Flex,
+        LockupPeriod::ThreeMonths,
+        LockupPeriod::SixMonths,
+        LockupPeriod::OneYear,
+    ];
+    const VOTING_POWER_MODES: [VotingPowerMode; 3] = [
+        VotingPowerMode::Flat,
+        VotingPowerMode::PeriodMultiplier,
+        VotingPowerMode::Decaying,
+    ];
+
+    /// A grown registrar with a voting mint for every exchange rate.
+    fn registrar_with_rates(
+        voting_power_mode: VotingPowerMode,
+        rates: &[(i8, u64)],
+    ) -> (Registrar, Vec<VotingMint>) {
+        let registrar = Registrar {
+            voting_power_mode,
+            ..Default::default()
+        };
+        let voting_mints = rates
+            .iter()
+            .map(
+                |&(digit_shift, baseline_vote_weight_scaled_factor)| VotingMint {
+                    config: VotingMintConfig {
+                        mint: Pubkey::new_unique(),
+                        ..Default::default()
+                    },
+                    baseline_vote_weight_scaled_factor,
+                    digit_shift,
+                    ..Default::default()
+                },
+            )
+            .collect();
+        (registrar, voting_mints)
+    }
+
+    fn deposit(voting_mint_config_idx: usize, amount: u64, lockup: Lockup) -> DepositEntry {
+        DepositEntry {
+            amount_deposited_native: amount,
+            voting_mint_config_idx: voting_mint_config_idx as u8,
+            lockup,
+            is_used: true,
+            ..Default::default()
+        }
+    }
+
+    fn rates() -> impl Strategy<Value = Vec<(i8, u64)>> {
+        prop::collection::vec((-4_i8..=4, 0..=4 * VotingMint::SCALED_FACTOR_BASE), 1..=4)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_max_vote_weight_bounds_voter_weight(
+            mode in 0..VOTING_POWER_MODES.len(),
+            rates in rates(),
+            deposits in prop::collection::vec(
+                (0..4_usize, 0..1_000_000_000_u64, 0..LOCKUP_PERIODS.len(), 0..400 * SECONDS_PER_DAY),
+                0..16,
+            ),
+            other_holders in prop::collection::vec(0..1_000_000_000_u64, 4),
+            curr_ts in 0..800 * SECONDS_PER_DAY,
+        ) {
+            let (registrar, voting_mints) = registrar_with_rates(VOTING_POWER_MODES[mode], &rates);
+            let registrar = DynamicRegistrar::new(&registrar, &voting_mints[..]);
+
+            let mut voter = Voter::default();
+            let mut supplies = other_holders[..rates.len()].to_vec();
+            for (d, &(idx, amount, period, start_ts)) in voter.deposits.iter_mut().zip(&deposits) {
+                let idx = idx % rates.len();
+                let period = LOCKUP_PERIODS[period];
+                let kind = if period == LockupPeriod::None {
+                    LockupKind::None
+                } else {
+                    LockupKind::Constant
+                };
+                let lockup = Lockup::new(kind, start_ts, period).unwrap();
+                *d = deposit(idx, amount, lockup);
+                supplies[idx] += amount;
+            }
+            let voter = DynamicVoter::new(&voter, &[][..]);
+
+            let mint_supplies: Vec<_> = voting_mints
+                .iter()
+                .zip(supplies)
+                .map(|(voting_mint, supply)| (voting_mint.config.mint, supply))
+                .collect();
+            let max_vote_weight = registrar.max_vote_weight_from_supplies(&mint_supplies).unwrap();
+            prop_assert!(voter.weight(&registrar, curr_ts).unwrap() <= max_vote_weight);
+        }
+
+        #[test]
+        fn prop_max_vote_weight_is_whole_supply_staked(
+            mode in 0..VOTING_POWER_MODES.len(),
+            rates in rates(),
+            supplies in prop::collection::vec(0..1_000_000_000_000_u64, 4),
+        ) {
+            // a voter who staked the whole supply of every voting mint for the
+            // longest period has exactly the max vote weight
+            let (registrar, voting_mints) = registrar_with_rates(VOTING_POWER_MODES[mode], &rates);
+            let registrar = DynamicRegistrar::new(&registrar, &voting_mints[..]);
+            let curr_ts = 1_000;
+            let lockup = Lockup::new(
+                LockupKind::Constant,
+                curr_ts,
+                LockupPeriod::OneYear,
+            )
+            .unwrap();
+
+            let mut voter = Voter::default();
+            let mut mint_supplies = Vec::new();
+            for (idx, voting_mint) in voting_mints.iter().enumerate() {
+                voter.deposits[idx] = deposit(idx, supplies[idx], lockup);
+                mint_supplies.push((voting_mint.config.mint, supplies[idx]));
+            }
+            let voter = DynamicVoter::new(&voter, &[][..]);
+
+            let max_vote_weight = registrar.max_vote_weight_from_supplies(&mint_supplies).unwrap();
+            prop_assert_eq!(voter.weight(&registrar, curr_ts).unwrap(), max_vote_weight);
+        }
+
+        #[test]
+        fn prop_max_vote_weight_grows_with_supply(
+            mode in 0..VOTING_POWER_MODES.len(),
+            rates in rates(),
+            supplies in prop::collection::vec(0..1_000_000_000_000_u64, 4),
+            idx in 0..4_usize,
+            minted in 0..1_000_000_000_u64,
+        ) {
+            let (registrar, voting_mints) = registrar_with_rates(VOTING_POWER_MODES[mode], &rates);
+            let registrar = DynamicRegistrar::new(&registrar, &voting_mints[..]);
+            let mut mint_supplies: Vec<_> = voting_mints
+                .iter()
+                .zip(supplies)
+                .map(|(voting_mint, supply)| (voting_mint.config.mint, supply))
+                .collect();
+
+            let before = registrar.max_vote_weight_from_supplies(&mint_supplies).unwrap();
+            mint_supplies[idx % rates.len()].1 += minted;
+            let after = registrar.max_vote_weight_from_supplies(&mint_supplies).unwrap();
+            prop_assert!(after >= before);
+        }
+    }
+}
@@ -1,8 +1,24 @@
-use crate::{error::*, state::deposit_entry::DepositEntry};
+use crate::{
+    error::*,
+    state::{
+        deposit_entry::DepositEntry, DynamicRegistrar, PenaltyKind, PenaltyRecord, Registrar,
+        VotingMint,
+    },
+};
 use anchor_lang::prelude::*;
+use std::{
+    cell::{Ref, RefMut},
+    mem::size_of,
+    ops::{Deref, DerefMut},
+};
 
 /// User account for minting voting rights.
+///
+/// The account may have been grown by `grow_voter`, in which case a `VoterExtension`
+/// and more deposit entries follow the `Voter`. Load it with `VoterLoader` to see
+/// all deposit entries.
 #[account(zero_copy)]
+#[derive(Default)]
 pub struct Voter {
     pub deposits: [DepositEntry; 32],
     pub voter_authority: Pubkey,
@@ -12,65 +28,70 @@ pub struct Voter {
     pub voter_bump: u8,
     pub voter_weight_record_bump: u8,
     pub penalties: u8,
-    pub _reserved1: [u8; 13],
+    /// Ring buffer of the most recent penalties applied to the voter.
+    pub penalty_log: [PenaltyRecord; 3],
+    /// Index of the `penalty_log` slot the next penalty is written to.
+    pub penalty_log_head: u8,
 }
-const_assert!(std::mem::size_of::<Voter>() == 144 * 32 + 32 + 32 + 8 + 8 + 1 + 1 + 1 + 13);
+const_assert!(std::mem::size_of::<Voter>() == 144 * 32 + 32 + 32 + 8 + 8 + 1 + 1 + 1 + 12 + 1);
 const_assert!(std::mem::size_of::<Voter>() % 8 == 0);
 
+/// Header of the deposit entries appended to a grown voter account.
+#[zero_copy]
+#[derive(Default)]
+pub struct VoterExtension {
+    /// Layout version of the voter account.
+    pub version: u8,
+    pub _reserved: [u8; 7],
+}
+const_assert!(std::mem::size_of::<VoterExtension>() == 8);
+
 impl Voter {
+    /// Min own weighted stake of a delegate, used when a registrar is created.
     pub const MIN_OWN_WEIGHTED_STAKE: u64 = 15_000_000;
     const IS_TOKENFLOW_RESTRICTED_MASK: u8 = 1 << 0;
 
-    /// The full vote weight available to the voter
-    pub fn weight(&self) -> Result<u64> {
-        self.deposits
-            .iter()
-            .filter(|d| d.is_used)
-            .try_fold(0_u64, |sum, d| {
-                d.voting_power().map(|vp| sum.checked_add(vp).unwrap())
-            })
-    }
-
-    /// The vote weight available to the voter when ignoring any lockup effects
-    pub fn weight_baseline(&self) -> u64 {
-        self.deposits
-            .iter()
-            .filter(|d| d.is_used)
-            .fold(0, |acc, d| acc + d.amount_deposited_native)
-    }
+    /// Layout of voter accounts with deposit entries after a `VoterExtension`.
+    pub const VERSION_EXTENDED: u8 = 1;
+    /// Number of deposit entries stored in `Voter` itself.
+    pub const INLINE_DEPOSIT_ENTRIES: usize = 32;
+    /// Deposit entries are addressed by a u8 index.
+    pub const MAX_DEPOSIT_ENTRIES: usize = 256;
 
-    /// The extra lockup vote weight that the user is guaranteed to have at `at_ts`, assuming
-    /// they withdraw and unlock as much as possible starting from `curr_ts`.
-    pub fn weight_locked_guaranteed(&self, curr_ts: i64, at_ts: i64) -> Result<u64> {
-        require_gte!(at_ts, curr_ts, MplStakingError::InvalidTimestampArguments);
-        self.deposits
-            .iter()
-            .filter(|d| d.is_used)
-            .try_fold(0_u64, |sum, _d| Ok(sum))
+    /// Size of a voter account holding `deposit_entries` deposit entries.
+    pub fn space(deposit_entries: usize) -> usize {
+        let space = 8 + size_of::<Voter>();
+        if deposit_entries <= Self::INLINE_DEPOSIT_ENTRIES {
+            space
+        } else {
+            space
+                + size_of::<VoterExtension>()
+                + (deposit_entries - Self::INLINE_DEPOSIT_ENTRIES) * size_of::<DepositEntry>()
+        }
     }
 
-    pub fn active_deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
-        let index = index as usize;
-        require_gt!(
-            self.deposits.len(),
-            index,
-            MplStakingError::OutOfBoundsDepositEntryIndex
-        );
-        let d = &mut self.deposits[index];
-        require!(d.is_used, MplStakingError::UnusedDepositEntryIndex);
-        Ok(d)
+    /// The deposit entries stored after the `Voter`, given the account data following it.
+    fn extra_deposits(extension: &[u8]) -> &[DepositEntry] {
+        let header_len = size_of::<VoterExtension>();
+        if extension.len() < header_len || extension[0] != Self::VERSION_EXTENDED {
+            return &[];
+        }
+        let entries = &extension[header_len..];
+        let len = entries.len() / size_of::<DepositEntry>();
+        // SAFETY: DepositEntry is packed, so it's aligned to a byte, and the entries have
+        // the same layout as the ones in `Voter`, which anchor reads from the account as well
+        unsafe { std::slice::from_raw_parts(entries.as_ptr() as *const DepositEntry, len) }
     }
 
-    pub fn active_deposit(&self, index: u8) -> Result<&DepositEntry> {
-        let index = index as usize;
-        require_gt!(
-            self.deposits.len(),
-            index,
-            MplStakingError::OutOfBoundsDepositEntryIndex
-        );
-        let d = &self.deposits[index];
-        require!(d.is_used, MplStakingError::UnusedDepositEntryIndex);
-        Ok(d)
+    fn extra_deposits_mut(extension: &mut [u8]) -> &mut [DepositEntry] {
+        let header_len = size_of::<VoterExtension>();
+        if extension.len() < header_len || extension[0] != Self::VERSION_EXTENDED {
+            return &mut [];
+        }
+        let entries = &mut extension[header_len..];
+        let len = entries.len() / size_of::<DepositEntry>();
+        // SAFETY: see `extra_deposits`
+        unsafe { std::slice::from_raw_parts_mut(entries.as_mut_ptr() as *mut DepositEntry, len) }
     }
 
     pub fn restrict_tokenflow(&mut self) -> Result<()> {
@@ -95,6 +116,23 @@ impl Voter {
         self.penalties & Self::IS_TOKENFLOW_RESTRICTED_MASK > 0
     }
 
+    /// Records a penalty in the penalty log, overwriting the oldest one if it's full.
+    pub fn log_penalty(&mut self, kind: PenaltyKind, reason: u8, curr_ts: u64) {
+        let len = self.penalty_log.len();
+        let head = self.penalty_log_head as usize % len;
+        self.penalty_log[head] = PenaltyRecord::new(kind, reason, curr_ts);
+        self.penalty_log_head = ((head + 1) % len) as u8;
+    }
+
+    /// The logged penalties, from the oldest to the most recent one.
+    pub fn recent_penalties(&self) -> impl Iterator<Item = PenaltyRecord> + '_ {
+        let len = self.penalty_log.len();
+        let head = self.penalty_log_head as usize;
+        (0..len)
+            .map(move |i| self.penalty_log[(head + i) % len])
+            .filter(|record| !record.is_empty())
+    }
+
     pub fn is_batch_minting_restricted(&self) -> bool {
         let curr_ts = Clock::get().unwrap().unix_timestamp as u64;
 
@@ -102,6 +140,239 @@ impl Voter {
     }
 }
 
+/// A voter together with the deposit entries that were appended to its account
+/// by `grow_voter`. Their indexes continue after the ones stored in `Voter`.
+pub struct DynamicVoter<V, D> {
+    voter: V,
+    extra_deposits: D,
+}
+
+pub type VoterRef<'a> = DynamicVoter<Ref<'a, Voter>, Ref<'a, [DepositEntry]>>;
+pub type VoterRefMut<'a> = DynamicVoter<RefMut<'a, Voter>, RefMut<'a, [DepositEntry]>>;
+
+impl<V, D> DynamicVoter<V, D>
+where
+    V: Deref<Target = Voter>,
+    D: Deref<Target = [DepositEntry]>,
+{
+    pub fn new(voter: V, extra_deposits: D) -> Self {
+        Self {
+            voter,
+            extra_deposits,
+        }
+    }
+
+    /// Number of deposit entries, used or not.
+    pub fn deposits_len(&self) -> usize {
+        self.voter.deposits.len() + self.extra_deposits.len()
+    }
+
+    pub fn deposits(&self) -> impl Iterator<Item = &DepositEntry> {
+        self.voter.deposits.iter().chain(self.extra_deposits.iter())
+    }
+
+    fn deposit(&self, index: usize) -> Option<&DepositEntry> {
+        let inline = self.voter.deposits.len();
+        if index < inline {
+            Some(&self.voter.deposits[index])
+        } else {
+            self.extra_deposits.get(index - inline)
+        }
+    }
+
+    /// Sums up `voting_power` of every used deposit entry, converted into vote
+    /// weight with the exchange rate of its voting mint. Deposits that vote with
+    /// their delegate are left out, whether or not delegate voting is still enabled.
+    fn sum_weight<R, M>(
+        &self,
+        registrar: &DynamicRegistrar<R, M>,
+        voting_power: impl Fn(&DepositEntry) -> Result<u64>,
+    ) -> Result<u64>
+    where
+        R: Deref<Target = Registrar>,
+        M: Deref<Target = [VotingMint]>,
+    {
+        self.deposits()
+            .filter(|d| d.is_used && d.delegated_vote_amount == 0)
+            .try_fold(0_u64, |sum, d| {
+                let weight =
+                    registrar.vote_weight(d.voting_mint_config_idx as usize, voting_power(d)?)?;
+                sum.checked_add(weight)
+                    .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))
+            })
+    }
+
+    /// The full vote weight available to the voter at `curr_ts`
+    pub fn weight<R, M>(&self, registrar: &DynamicRegistrar<R, M>, curr_ts: u64) -> Result<u64>
+    where
+        R: Deref<Target = Registrar>,
+        M: Deref<Target = [VotingMint]>,
+    {
+        self.sum_weight(registrar, |d| d.voting_power(registrar, curr_ts))
+    }
+
+    /// The vote weight available to the voter when ignoring any lockup effects
+    pub fn weight_baseline<R, M>(&self, registrar: &DynamicRegistrar<R, M>) -> Result<u64>
+    where
+        R: Deref<Target = Registrar>,
+        M: Deref<Target = [VotingMint]>,
+    {
+        self.sum_weight(registrar, |d| Ok(d.amount_deposited_native))
+    }
+
+    /// The vote weight that the user is guaranteed to have at `at_ts`, assuming
+    /// they withdraw and unlock as much as possible starting from `curr_ts`.
+    pub fn weight_locked_guaranteed<R, M>(
+        &self,
+        registrar: &DynamicRegistrar<R, M>,
+        curr_ts: u64,
+        at_ts: u64,
+    ) -> Result<u64>
+    where
+        R: Deref<Target = Registrar>,
+        M: Deref<Target = [VotingMint]>,
+    {
+        require_gte!(at_ts, curr_ts, MplStakingError::InvalidTimestampArguments);
+        self.sum_weight(registrar, |d| {
+            d.voting_power_locked_guaranteed(registrar, curr_ts, at_ts)
+        })
+    }
+
+    pub fn active_deposit(&self, index: u8) -> Result<&DepositEntry> {
+        let d = self
+            .deposit(index as usize)
+            .ok_or(MplStakingError::OutOfBoundsDepositEntryIndex)?;
+        require!(d.is_used, MplStakingError::UnusedDepositEntryIndex);
+        Ok(d)
+    }
+
+    /// Index of the first deposit entry that isn't in use.
+    pub fn free_deposit_index(&self) -> Result<usize> {
+        self.deposits()
+            .position(|d| !d.is_used)
+            .ok_or_else(|| error!(MplStakingError::DepositEntryFull))
+    }
+}
+
+impl<V, D> DynamicVoter<V, D>
+where
+    V: DerefMut<Target = Voter>,
+    D: DerefMut<Target = [DepositEntry]>,
+{
+    pub fn deposits_mut(&mut self) -> impl Iterator<Item = &mut DepositEntry> {
+        self.voter
+            .deposits
+            .iter_mut()
+            .chain(self.extra_deposits.iter_mut())
+    }
+
+    /// The deposit entry at `index`, whether it's in use or not.
+    pub fn deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
+        let index = index as usize;
+        let inline = self.voter.deposits.len();
+        if index < inline {
+            Ok(&mut self.voter.deposits[index])
+        } else {
+            self.extra_deposits
+                .get_mut(index - inline)
+                .ok_or_else(|| error!(MplStakingError::OutOfBoundsDepositEntryIndex))
+        }
+    }
+
+    pub fn active_deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
+        let d = self.deposit_mut(index)?;
+        require!(d.is_used, MplStakingError::UnusedDepositEntryIndex);
+        Ok(d)
+    }
+
+    /// Moves `amount` tokens of a deposit into the first free deposit entry, which
+    /// keeps the lockup, delegate and mint of the original. The slashing penalty
+    /// stays with the original. Returns the index of the new deposit entry.
+    pub fn split_deposit(&mut self, index: u8, amount: u64) -> Result<u8> {
+        let new_index = self.free_deposit_index()? as u8;
+        let original = self.active_deposit_mut(index)?;
+        require!(
+            amount > 0 && amount < original.amount_deposited_native,
+            MplStakingError::InvalidAmount
+        );
+        original.amount_deposited_native -= amount;
+        // the delegate keeps counting both parts
+        let delegated = original.delegated_vote_amount > 0;
+        if delegated {
+            original.delegated_vote_amount = original.amount_deposited_native;
+        }
+        let accepted = original.accepted_delegate_amount > 0;
+        if accepted {
+            original.accepted_delegate_amount = original.amount_deposited_native;
+        }
+
+        let mut split = *original;
+        split.amount_deposited_native = amount;
+        split.slashing_penalty = 0;
+        if delegated {
+            split.delegated_vote_amount = amount;
+        }
+        if accepted {
+            split.accepted_delegate_amount = amount;
+        }
+        *self.deposit_mut(new_index)? = split;
+
+        Ok(new_index)
+    }
+}
+
+impl<V: Deref<Target = Voter>, D> Deref for DynamicVoter<V, D> {
+    type Target = Voter;
+
+    fn deref(&self) -> &Voter {
+        &self.voter
+    }
+}
+
+impl<V: DerefMut<Target = Voter>, D> DerefMut for DynamicVoter<V, D> {
+    fn deref_mut(&mut self) -> &mut Voter {
+        &mut self.voter
+    }
+}
+
+/// Loads a voter account with all its deposit entries.
+pub trait VoterLoader {
+    fn load_voter(&self) -> Result<VoterRef<'_>>;
+    fn load_voter_mut(&self) -> Result<VoterRefMut<'_>>;
+}
+
+impl<'info> VoterLoader for AccountLoader<'info, Voter> {
+    fn load_voter(&self) -> Result<VoterRef<'_>> {
+        // checks the discriminator
+        drop(self.load()?);
+        let account: &AccountInfo<'info> = self.as_ref();
+        let data = account.try_borrow_data()?;
+        let (voter, extra_deposits) = Ref::map_split(data, |data| {
+            let (voter, extension) = data[8..].split_at(size_of::<Voter>());
+            (
+                bytemuck::from_bytes(voter),
+                Voter::extra_deposits(extension),
+            )
+        });
+        Ok(DynamicVoter::new(voter, extra_deposits))
+    }
+
+    fn load_voter_mut(&self) -> Result<VoterRefMut<'_>> {
+        // checks the discriminator and that the account is writable
+        drop(self.load_mut()?);
+        let account: &AccountInfo<'info> = self.as_ref();
+        let data = account.try_borrow_mut_data()?;
+        let (voter, extra_deposits) = RefMut::map_split(data, |data| {
+            let (voter, extension) = data[8..].split_at_mut(size_of::<Voter>());
+            (
+                bytemuck::from_bytes_mut(voter),
+                Voter::extra_deposits_mut(extension),
+            )
+        });
+        Ok(DynamicVoter::new(voter, extra_deposits))
+    }
+}
+
 #[macro_export]
 macro_rules! voter_seeds {
     ( $voter:expr ) => {
@@ -115,3 +386,128 @@ macro_rules! voter_seeds {
 }
 
 pub use voter_seeds;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SECONDS_PER_DAY;
+
+    #[test]
+    fn test_split_deposit() {
+        let mut voter = Voter::default();
+        voter.deposits[0].is_used = true;
+        voter.deposits[0].amount_deposited_native = 100;
+        voter.deposits[0].slashing_penalty = 5;
+        voter.deposits[1].is_used = true;
+        let mut voter = DynamicVoter::new(&mut voter, &mut [][..]);
+
+        assert!(voter.split_deposit(0, 0).is_err());
+        assert!(voter.split_deposit(0, 100).is_err());
+
+        assert_eq!(voter.split_deposit(0, 40).unwrap(), 2);
+        assert_eq!({ voter.deposits[0].amount_deposited_native }, 60);
+        assert_eq!({ voter.deposits[0].slashing_penalty }, 5);
+        assert!(voter.deposits[2].is_used);
+        assert_eq!({ voter.deposits[2].amount_deposited_native }, 40);
+        assert_eq!({ voter.deposits[2].slashing_penalty }, 0);
+    }
+
+    #[test]
+    fn test_delegated_deposits_vote_with_delegate() {
+        let mut voter = Voter::default();
+        for d in voter.deposits[..2].iter_mut() {
+            d.is_used = true;
+            d.amount_deposited_native = 100;
+        }
+        voter.deposits[1].delegated_vote_amount = 100;
+        voter.deposits[1].accepted_delegate_amount = 100;
+        let mut voter = DynamicVoter::new(&mut voter, &mut [][..]);
+
+        // the delegated deposit votes with its delegate, even once delegate voting is off
+        let mut registrar = Registrar::default();
+        assert_eq!(
+            voter
+                .weight_baseline(&DynamicRegistrar::new(&registrar, &[][..]))
+                .unwrap(),
+            100
+        );
+        registrar.delegate_voting = true;
+        let registrar = DynamicRegistrar::new(&registrar, &[][..]);
+        assert_eq!(voter.weight_baseline(&registrar).unwrap(), 100);
+
+        // both parts of a split deposit stay with the delegate
+        assert_eq!(voter.split_deposit(1, 40).unwrap(), 2);
+        assert_eq!({ voter.deposits[1].delegated_vote_amount }, 60);
+        assert_eq!({ voter.deposits[2].delegated_vote_amount }, 40);
+        assert_eq!({ voter.deposits[1].accepted_delegate_amount }, 60);
+        assert_eq!({ voter.deposits[2].accepted_delegate_amount }, 40);
+        assert_eq!(voter.weight_baseline(&registrar).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_extra_deposits() {
+        let mut voter = Voter::default();
+        for d in voter.deposits.iter_mut() {
+            d.is_used = true;
+            d.amount_deposited_native = 1;
+        }
+        let mut extra_deposits = [DepositEntry::default(); 2];
+        let mut voter = DynamicVoter::new(&mut voter, &mut extra_deposits[..]);
+        assert_eq!(voter.deposits_len(), 34);
+        assert_eq!(voter.free_deposit_index().unwrap(), 32);
+        assert!(voter.active_deposit(33).is_err());
+        assert!(voter.deposit_mut(34).is_err());
+
+        assert!(voter.split_deposit(31, 1).is_err());
+        voter.deposit_mut(33).unwrap().is_used = true;
+        voter.deposit_mut(33).unwrap().amount_deposited_native = 10;
+        assert_eq!(voter.split_deposit(33, 4).unwrap(), 32);
+        assert_eq!(
+            { voter.active_deposit(32).unwrap().amount_deposited_native },
+            4
+        );
+        let registrar = Registrar::default();
+        let registrar = DynamicRegistrar::new(&registrar, &[][..]);
+        assert_eq!(voter.weight_baseline(&registrar).unwrap(), 32 + 10);
+    }
+
+    #[test]
+    fn test_extra_deposits_layout() {
+        assert_eq!(Voter::space(32), 8 + size_of::<Voter>());
+        assert_eq!(
+            Voter::space(34),
+            8 + size_of::<Voter>() + 8 + 2 * size_of::<DepositEntry>()
+        );
+
+        let mut extension = vec![0_u8; Voter::space(34) - 8 - size_of::<Voter>()];
+        assert!(Voter::extra_deposits(&extension).is_empty());
+        extension[0] = Voter::VERSION_EXTENDED;
+        assert_eq!(Voter::extra_deposits(&extension).len(), 2);
+    }
+
+    #[test]
+    fn test_penalty_log_keeps_most_recent() {
+        let mut voter = Voter::default();
+        assert_eq!(voter.recent_penalties().count(), 0);
+
+        voter.log_penalty(PenaltyKind::RestrictTokenflow, 1, SECONDS_PER_DAY);
+        voter.log_penalty(PenaltyKind::Slash, 2, 2 * SECONDS_PER_DAY);
+        let kinds: Vec<_> = voter.recent_penalties().map(|r| r.kind).collect();
+        assert_eq!(kinds, [PenaltyKind::RestrictTokenflow, PenaltyKind::Slash]);
+
+        voter.log_penalty(PenaltyKind::AllowTokenflow, 3, 3 * SECONDS_PER_DAY);
+        voter.log_penalty(PenaltyKind::DecreaseRewards, 4, 4 * SECONDS_PER_DAY);
+        let records: Vec<_> = voter
+            .recent_penalties()
+            .map(|r| (r.kind, r.reason, { r.day }))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                (PenaltyKind::Slash, 2, 2),
+                (PenaltyKind::AllowTokenflow, 3, 3),
+                (PenaltyKind::DecreaseRewards, 4, 4),
+            ]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::*;
 use anchor_lang::prelude::*;
 
 /// Exchange rate for an asset that can be used to mint voting rights.
@@ -22,3 +23,97 @@ impl VotingMintConfig {
         self.mint != Pubkey::default()
     }
 }
+
+/// A voting mint of a grown registrar, see `DynamicRegistrar`.
+///
+/// Unlike the voting mints stored in `Registrar`, which count 1:1, these have
+/// an exchange rate from native token amounts to vote weight.
+#[zero_copy]
+#[derive(Default)]
+pub struct VotingMint {
+    pub config: VotingMintConfig,
+    /// Vote weight of one native token, in units of `SCALED_FACTOR_BASE`,
+    /// applied after `digit_shift`.
+    pub baseline_vote_weight_scaled_factor: u64,
+    /// Number of digits native token amounts are shifted by to normalise
+    /// the decimals of the mint, e.g. 3 for a mint with three decimals less
+    /// than the governance token.
+    pub digit_shift: i8,
+    /// Set for voting mints that were configured before the registrar was grown,
+    /// whose deposit entries were never counted in `open_deposit_entries`,
+    /// until the realm authority backfills the count.
+    pub deposit_entries_untracked: bool,
+    /// Number of used deposit entries of this voting mint, across all voters.
+    pub open_deposit_entries: u64,
+    pub _reserved: [u8; 14],
+}
+const_assert!(std::mem::size_of::<VotingMint>() == 2 * 32 + 8 + 1 + 1 + 8 + 14);
+const_assert!(std::mem::size_of::<VotingMint>() % 8 == 0);
+
+impl VotingMint {
+    /// `baseline_vote_weight_scaled_factor` of a rate of 1.
+    pub const SCALED_FACTOR_BASE: u64 = 1_000_000_000;
+
+    pub fn new(config: VotingMintConfig) -> Self {
+        Self {
+            config,
+            baseline_vote_weight_scaled_factor: Self::SCALED_FACTOR_BASE,
+            ..Default::default()
+        }
+    }
+
+    /// Converts an amount of native tokens of the mint into vote weight.
+    pub fn vote_weight(&self, amount_native: u64) -> Result<u64> {
+        let shift = 10_u64
+            .checked_pow(self.digit_shift.unsigned_abs() as u32)
+            .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))?;
+        let shifted = if self.digit_shift < 0 {
+            amount_native / shift
+        } else {
+            amount_native
+                .checked_mul(shift)
+                .ok_or_else(|| error!(MplStakingError::VoterWeightOverflow))?
+        };
+        let weight = shifted as u128 * self.baseline_vote_weight_scaled_factor as u128
+            / Self::SCALED_FACTOR_BASE as u128;
+        u64::try_from(weight).map_err(|_| error!(MplStakingError::VoterWeightOverflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voting_mint(digit_shift: i8, baseline_vote_weight_scaled_factor: u64) -> VotingMint {
+        VotingMint {
+            digit_shift,
+            baseline_vote_weight_scaled_factor,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_vote_weight() {
+        let base = VotingMint::SCALED_FACTOR_BASE;
+        assert_eq!(
+            VotingMint::new(Default::default())
+                .vote_weight(1_234)
+                .unwrap(),
+            1_234
+        );
+        assert_eq!(voting_mint(0, base / 2).vote_weight(1_234).unwrap(), 617);
+        assert_eq!(voting_mint(3, base).vote_weight(1_234).unwrap(), 1_234_000);
+        assert_eq!(voting_mint(-3, base).vote_weight(1_234).unwrap(), 1);
+        assert_eq!(voting_mint(-2, 3 * base).vote_weight(1_234).unwrap(), 36);
+        assert_eq!(voting_mint(0, 0).vote_weight(1_234).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_vote_weight_overflow() {
+        let base = VotingMint::SCALED_FACTOR_BASE;
+        assert!(voting_mint(1, base).vote_weight(u64::MAX).is_err());
+        assert!(voting_mint(0, 2 * base).vote_weight(u64::MAX).is_err());
+        assert!(voting_mint(20, base).vote_weight(1).is_err());
+        assert!(voting_mint(-20, base).vote_weight(u64::MAX).is_err());
+    }
+}
@@ -152,7 +152,7 @@ pub enum RewardsInstruction {
     /// [W] Reward pool account
     /// [W] Mining
     /// [RS] Deposit authority
-    /// [RS] Mining owner
+    /// [RS] Mining owner, not a signer when a stale delegation is revoked
     /// [W] Old delegate mining
     /// [W] New delegate mining
     ChangeDelegate {
@@ -507,13 +507,14 @@ pub fn change_delegate<'a>(
     new_delegate_mining: AccountInfo<'a>,
     new_delegate: Pubkey,
     staked_amount: u64,
+    mining_owner_signed: bool,
     signers_seeds: &[&[u8]],
 ) -> ProgramResult {
     let accounts = vec![
         AccountMeta::new(reward_pool.key(), false),
         AccountMeta::new(mining.key(), false),
         AccountMeta::new_readonly(deposit_authority.key(), true),
-        AccountMeta::new_readonly(mining_owner.key(), true),
+        AccountMeta::new_readonly(mining_owner.key(), mining_owner_signed),
         AccountMeta::new(old_delegate_mining.key(), false),
         AccountMeta::new(new_delegate_mining.key(), false),
     ];
@@ -0,0 +1,174 @@
+use crate::voter::load_token_owner_record;
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{DelegateProfile, DelegatedStake, DepositEntry, Registrar},
+};
+use spl_governance::state::token_owner_record::get_token_owner_record_address;
+
+// Instructions that change a delegated deposit keep the `DelegateProfile` and the
+// `DelegatedStake` of its delegate up to date. The accounts of the delegates
+// involved are passed in the remaining accounts. The `DelegateProfile` is needed
+// whenever a deposit is staked with a delegate, the `DelegatedStake` only while
+// the registrar has delegate voting enabled or the deposit is still counted for
+// its delegate. Moving vote weight between a delegate and the owner also needs the
+// token owner record of the one losing it, see `require_no_outstanding_votes`.
+
+fn delegate_account_address(registrar: &Pubkey, seed: &[u8], delegate: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[registrar.as_ref(), seed, delegate.as_ref()], &crate::id()).0
+}
+
+/// The `DelegatedStake` of `delegate`, if it was passed in `accounts`.
+pub fn find_delegated_stake<'info>(
+    registrar: &Pubkey,
+    delegate: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+) -> Result<Option<AccountLoader<'info, DelegatedStake>>> {
+    let address = delegate_account_address(registrar, b"delegated-stake", delegate);
+    accounts
+        .iter()
+        .find(|account| account.key() == address)
+        .map(AccountLoader::try_from)
+        .transpose()
+}
+
+/// The `DelegateProfile` of `delegate`, which must have been passed in `accounts`.
+pub fn load_delegate_profile<'info>(
+    registrar: &Pubkey,
+    delegate: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+) -> Result<AccountLoader<'info, DelegateProfile>> {
+    let address = delegate_account_address(registrar, b"delegate-profile", delegate);
+    let account = accounts
+        .iter()
+        .find(|account| account.key() == address)
+        .ok_or_else(|| error!(MplStakingError::DelegateProfileNotFound))?;
+    AccountLoader::try_from(account)
+}
+
+/// Requires `delegate` to have opted in to accept stake from other voters.
+pub fn require_active_delegate(
+    registrar: &Pubkey,
+    delegate: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    let delegate_profile = load_delegate_profile(registrar, delegate, accounts)?;
+    require!(
+        delegate_profile.load()?.active,
+        MplStakingError::DelegateNotActive
+    );
+    Ok(())
+}
+
+/// Requires `voter` not to have any outstanding votes, the same way governance
+/// requires it before tokens can be withdrawn, so the vote weight it voted with
+/// can't be moved elsewhere and be voted with again. The token owner record of the
+/// voter must have been passed in `accounts`, a voter without one hasn't voted.
+pub fn require_no_outstanding_votes(
+    registrar: &Registrar,
+    voter: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    let address = get_token_owner_record_address(
+        &registrar.governance_program_id,
+        &registrar.realm,
+        &registrar.realm_governing_token_mint,
+        voter,
+    );
+    let account = accounts
+        .iter()
+        .find(|account| account.key() == address)
+        .ok_or_else(|| error!(MplStakingError::TokenOwnerRecordNotFound))?;
+    if account.data_is_empty() {
+        return Ok(());
+    }
+    load_token_owner_record(voter, account, registrar)?.assert_can_withdraw_governing_tokens()?;
+    Ok(())
+}
+
+/// Stops counting the deposit for its delegate. The delegate must not have any
+/// outstanding votes while the deposit votes with it.
+pub fn release_delegated_stake(
+    registrar_key: &Pubkey,
+    registrar: &Registrar,
+    deposit_entry: &mut DepositEntry,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    if deposit_entry.delegated_vote_amount > 0 {
+        require_no_outstanding_votes(registrar, &deposit_entry.delegate, accounts)?;
+    }
+    release_delegated_stake_unchecked(registrar_key, deposit_entry, accounts)
+}
+
+fn release_delegated_stake_unchecked(
+    registrar: &Pubkey,
+    deposit_entry: &mut DepositEntry,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    let accepted_amount = deposit_entry.accepted_delegate_amount;
+    if accepted_amount > 0 {
+        load_delegate_profile(registrar, &deposit_entry.delegate, accounts)?
+            .load_mut()?
+            .release(accepted_amount)?;
+        deposit_entry.accepted_delegate_amount = 0;
+    }
+
+    let amount = deposit_entry.delegated_vote_amount;
+    if amount > 0 {
+        find_delegated_stake(registrar, &deposit_entry.delegate, accounts)?
+            .ok_or_else(|| error!(MplStakingError::DelegatedStakeNotFound))?
+            .load_mut()?
+            .sub(deposit_entry.voting_mint_config_idx as usize, amount)?;
+        deposit_entry.delegated_vote_amount = 0;
+    }
+    Ok(())
+}
+
+/// Counts the deposit for its current delegate with its current amount, or not at
+/// all if it isn't staked with a delegate (anymore). Additional stake must be
+/// accepted by the `DelegateProfile` of the delegate. The delegate must not have
+/// any outstanding votes if the deposit votes with it for less than before, and the
+/// owner mustn't have any if it votes with the delegate for more, since that weight
+/// is taken from the owner.
+pub fn sync_delegated_stake(
+    registrar_key: &Pubkey,
+    registrar: &Registrar,
+    owner: &Pubkey,
+    deposit_entry: &mut DepositEntry,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    let staked_with_delegate = deposit_entry.is_staked_with_delegate(owner);
+    let amount = deposit_entry.amount_deposited_native;
+    let vote_amount = if staked_with_delegate && registrar.delegate_voting {
+        amount
+    } else {
+        0
+    };
+    if deposit_entry.delegated_vote_amount > vote_amount {
+        require_no_outstanding_votes(registrar, &deposit_entry.delegate, accounts)?;
+    }
+    if vote_amount > deposit_entry.delegated_vote_amount {
+        require_no_outstanding_votes(registrar, owner, accounts)?;
+    }
+
+    let previous_amount = deposit_entry.accepted_delegate_amount;
+    release_delegated_stake_unchecked(registrar_key, deposit_entry, accounts)?;
+    if !staked_with_delegate {
+        return Ok(());
+    }
+
+    load_delegate_profile(registrar_key, &deposit_entry.delegate, accounts)?
+        .load_mut()?
+        .accept(amount, previous_amount)?;
+    deposit_entry.accepted_delegate_amount = amount;
+
+    if !registrar.delegate_voting {
+        return Ok(());
+    }
+    find_delegated_stake(registrar_key, &deposit_entry.delegate, accounts)?
+        .ok_or_else(|| error!(MplStakingError::DelegatedStakeNotFound))?
+        .load_mut()?
+        .add(deposit_entry.voting_mint_config_idx as usize, amount)?;
+    deposit_entry.delegated_vote_amount = amount;
+    Ok(())
+}
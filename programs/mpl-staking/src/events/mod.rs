@@ -1,4 +1,9 @@
 use anchor_lang::prelude::*;
+use mplx_staking_states::state::{LockupKind, LockupPeriod, PenaltyKind};
+
+// Indexers decode the events of `log_voter_info` from the logs, so new fields of
+// `VoterInfo`, `DepositEntryInfo` and `LockingInfo` are only ever appended. Older
+// decoders read the fields they know and ignore the rest.
 
 #[event]
 #[derive(Debug)]
@@ -7,6 +12,19 @@ pub struct VoterInfo {
     pub voting_power: u64,
     /// Voter's total voting power, when ignoring any effects from lockup
     pub voting_power_baseline: u64,
+    /// Voting power the voter is guaranteed to still have at the requested timestamp, if any
+    pub voting_power_locked_guaranteed: Option<u64>,
+    /// The most recent penalties applied to the voter, oldest first
+    pub recent_penalties: Vec<PenaltyInfo>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct PenaltyInfo {
+    pub kind: PenaltyKind,
+    /// Reason code given by the realm authority
+    pub reason: u8,
+    /// Day the penalty was applied at, as days since the unix epoch
+    pub day: u16,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
@@ -25,6 +43,8 @@ pub struct LockingInfo {
     pub end_timestamp: Option<u64>,
     /// Information about vesting, if any
     pub vesting: Option<VestingInfo>,
+    /// Whether the lockup is renewed once it expires
+    pub auto_renew: bool,
 }
 
 #[event]
@@ -40,4 +60,328 @@ pub struct DepositEntryInfo {
     pub voting_power_baseline: u64,
     /// Information about locking, if any
     pub locking: Option<LockingInfo>,
+    /// Voting power this deposit is guaranteed to still have at the requested timestamp, if any
+    pub voting_power_locked_guaranteed: Option<u64>,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct RegistrarParamsChanged {
+    pub registrar: Pubkey,
+    /// Seconds between requesting an unlock and being able to withdraw
+    pub cooldown_secs: u64,
+    /// Min seconds between two delegate changes of a deposit
+    pub delegate_update_diff_threshold: u64,
+    /// Min weighted stake a voter must own to be chosen as a delegate
+    pub min_own_weighted_stake: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct TokenflowRestricted {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub authority: Pubkey,
+    /// Reason code given by the realm authority
+    pub reason: u8,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct TokenflowAllowed {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub authority: Pubkey,
+    /// Reason code given by the realm authority
+    pub reason: u8,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct BatchMintingRestricted {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub authority: Pubkey,
+    /// Batch minting is restricted until this timestamp
+    pub until_ts: u64,
+    /// Reason code given by the realm authority
+    pub reason: u8,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DepositSlashed {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub authority: Pubkey,
+    pub deposit_entry_index: u8,
+    /// Amount of native tokens taken from the deposit
+    pub amount: u64,
+    /// Reason code given by the realm authority
+    pub reason: u8,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct RewardsDecreased {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub authority: Pubkey,
+    /// Weighted stake the voter's mining account was decreased by
+    pub decreased_weighted_stake_number: u64,
+    /// Reason code given by the realm authority
+    pub reason: u8,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct Deposited {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens added to the deposit
+    pub amount: u64,
+    /// Amount of native tokens in the deposit afterwards
+    pub amount_deposited_native: u64,
+    pub lockup_kind: LockupKind,
+    pub lockup_period: LockupPeriod,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct Withdrawn {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens sent to the destination
+    pub amount: u64,
+    /// Slashed amount of native tokens sent to the realm treasury along with the withdraw
+    pub slashing_penalty: u64,
+    /// Amount of native tokens in the deposit afterwards
+    pub amount_deposited_native: u64,
+    pub lockup_kind: LockupKind,
+    pub lockup_period: LockupPeriod,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct Staked {
+    pub voter: Pubkey,
+    pub source_deposit_entry_index: u8,
+    pub target_deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens moved from the source to the target deposit
+    pub amount: u64,
+    pub lockup_period: LockupPeriod,
+    pub lockup_start_ts: u64,
+    pub lockup_end_ts: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct StakeExtended {
+    pub voter: Pubkey,
+    pub source_deposit_entry_index: u8,
+    pub target_deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens moved from the source to the target deposit
+    pub additional_amount: u64,
+    /// Amount of native tokens in the target deposit afterwards
+    pub amount_deposited_native: u64,
+    /// The lockup period the stake had before, Flex if it had expired
+    pub old_lockup_period: LockupPeriod,
+    pub new_lockup_period: LockupPeriod,
+    pub lockup_start_ts: u64,
+    pub lockup_end_ts: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct TokensUnlocked {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    /// The deposit entry holding the tokens in cooldown, different from
+    /// `deposit_entry_index` if only a part of the deposit was unlocked
+    pub cooldown_deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens that are cooling down
+    pub amount: u64,
+    pub lockup_period: LockupPeriod,
+    /// Time at which the tokens can be withdrawn
+    pub cooldown_ends_at: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct UnlockCancelled {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens staked again
+    pub amount: u64,
+    pub lockup_period: LockupPeriod,
+    pub lockup_start_ts: u64,
+    pub lockup_end_ts: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct AutoRenewChanged {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    pub auto_renew: bool,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct StakeRenewed {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub lockup_period: LockupPeriod,
+    pub lockup_start_ts: u64,
+    pub lockup_end_ts: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DepositsMerged {
+    pub voter: Pubkey,
+    /// The deposit entry that was merged and closed
+    pub source_deposit_entry_index: u8,
+    pub target_deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens in the merged deposit
+    pub amount_deposited_native: u64,
+    pub lockup_period: LockupPeriod,
+    pub lockup_start_ts: u64,
+    pub lockup_end_ts: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DepositSplit {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    /// The deposit entry the tokens were moved to
+    pub new_deposit_entry_index: u8,
+    /// Amount of native tokens moved
+    pub amount: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DelegateChanged {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens staked through the delegate
+    pub amount: u64,
+    pub lockup_period: LockupPeriod,
+    pub old_delegate: Pubkey,
+    pub new_delegate: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct Granted {
+    pub voter: Pubkey,
+    pub voter_authority: Pubkey,
+    pub grant_authority: Pubkey,
+    pub deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of native tokens granted
+    pub amount: u64,
+    pub lockup_kind: LockupKind,
+    /// None for vesting grants
+    pub lockup_period: LockupPeriod,
+    pub lockup_start_ts: u64,
+    pub lockup_end_ts: u64,
+    pub allow_clawback: bool,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct ClawedBack {
+    pub voter: Pubkey,
+    pub grant_authority: Pubkey,
+    pub deposit_entry_index: u8,
+    pub mint: Pubkey,
+    /// Amount of still locked native tokens sent to the destination
+    pub amount: u64,
+    /// Amount of vested native tokens left in the deposit for the voter
+    pub amount_deposited_native: u64,
+    pub destination: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DepositEntryClosed {
+    pub voter: Pubkey,
+    pub deposit_entry_index: u8,
+    /// The registrar isn't passed to close_deposit_entry, so it's the index of the mint
+    pub voting_mint_config_index: u8,
+    pub lockup_kind: LockupKind,
+    pub lockup_period: LockupPeriod,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct VoterClosed {
+    pub voter: Pubkey,
+    pub voter_authority: Pubkey,
+    pub registrar: Pubkey,
+    /// Receiver of the rent of the voter account and its vaults
+    pub sol_destination: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct VoterGrown {
+    pub voter: Pubkey,
+    /// Number of deposit entries the voter can hold now
+    pub deposit_entries: u16,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct RegistrarGrown {
+    pub registrar: Pubkey,
+    /// Number of voting mints the registrar can hold now
+    pub voting_mints: u8,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct VotingMintRemoved {
+    pub registrar: Pubkey,
+    pub voting_mint_config_index: u16,
+    pub mint: Pubkey,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct VotingMintDepositsRecounted {
+    pub registrar: Pubkey,
+    pub voting_mint_config_index: u16,
+    pub mint: Pubkey,
+    pub open_deposit_entries: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct DelegateProfileUpdated {
+    pub registrar: Pubkey,
+    pub delegate: Pubkey,
+    /// Whether the delegate accepts new stake
+    pub active: bool,
+    pub max_accepted_stake: u64,
+    pub metadata_uri: String,
 }
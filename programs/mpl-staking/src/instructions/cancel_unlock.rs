@@ -0,0 +1,100 @@
+use crate::{
+    clock_unix_timestamp, cpi_instructions, delegated_stake::sync_delegated_stake,
+    events::UnlockCancelled, Stake,
+};
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    registrar_seeds,
+    state::{LockupKind, LockupPeriod, RegistrarLoader, VoterLoader},
+};
+
+/// Cancels a pending unlock request and stakes the deposit again.
+///
+/// The cooldown is cleared and the deposit is locked up anew for `new_lockup_period`
+/// starting now, so the tokens earn rewards again right away.
+pub fn cancel_unlock(
+    ctx: Context<Stake>,
+    deposit_entry_index: u8,
+    new_lockup_period: LockupPeriod,
+) -> Result<()> {
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+
+    require!(
+        registrar.reward_pool == ctx.accounts.reward_pool.key(),
+        MplStakingError::InvalidRewardPool
+    );
+    require!(
+        new_lockup_period != LockupPeriod::None,
+        MplStakingError::InvalidLockupPeriod
+    );
+
+    let curr_ts = clock_unix_timestamp();
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    require!(
+        !voter.is_batch_minting_restricted(),
+        MplStakingError::BatchMintingRestricted
+    );
+
+    let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
+    require!(
+        deposit_entry.lockup.kind == LockupKind::Constant,
+        MplStakingError::InvalidLockupKind
+    );
+    require!(
+        deposit_entry.lockup.cooldown_requested,
+        MplStakingError::UnlockNotRequested
+    );
+    ctx.accounts.verify_delegate(deposit_entry)?;
+
+    deposit_entry.lockup.cooldown_requested = false;
+    deposit_entry.lockup.cooldown_ends_at = 0;
+    deposit_entry.lockup.start_ts = curr_ts;
+    deposit_entry.lockup.end_ts = curr_ts
+        .checked_add(new_lockup_period.to_secs())
+        .ok_or(MplStakingError::InvalidTimestampArguments)?;
+    deposit_entry.lockup.period = new_lockup_period;
+    sync_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &registrar,
+        &ctx.accounts.voter_authority.key(),
+        deposit_entry,
+        ctx.remaining_accounts,
+    )?;
+
+    let reward_pool = ctx.accounts.reward_pool.to_account_info();
+    let mining = ctx.accounts.deposit_mining.to_account_info();
+    let deposit_authority = ctx.accounts.registrar.to_account_info();
+    let delegate_mining = ctx.accounts.delegate_mining.to_account_info();
+    let signers_seeds = registrar_seeds!(&registrar);
+    let owner = &ctx.accounts.voter_authority.key();
+    let delegate_wallet_addr = &ctx.accounts.delegate.key();
+
+    cpi_instructions::deposit_mining(
+        ctx.accounts.rewards_program.to_account_info(),
+        reward_pool,
+        mining,
+        deposit_authority,
+        delegate_mining,
+        deposit_entry.amount_deposited_native,
+        new_lockup_period,
+        owner,
+        signers_seeds,
+        delegate_wallet_addr,
+    )?;
+
+    emit!(UnlockCancelled {
+        voter: ctx.accounts.voter.key(),
+        deposit_entry_index,
+        mint: registrar
+            .voting_mint(deposit_entry.voting_mint_config_idx as usize)?
+            .mint,
+        amount: deposit_entry.amount_deposited_native,
+        lockup_period: new_lockup_period,
+        lockup_start_ts: deposit_entry.lockup.start_ts,
+        lockup_end_ts: deposit_entry.lockup.end_ts,
+        delegate: deposit_entry.delegate,
+    });
+
+    Ok(())
+}
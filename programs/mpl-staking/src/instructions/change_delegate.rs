@@ -1,12 +1,14 @@
-use crate::{clock_unix_timestamp, cpi_instructions};
+use crate::{
+    clock_unix_timestamp, cpi_instructions,
+    delegated_stake::{release_delegated_stake, require_active_delegate, sync_delegated_stake},
+    events::DelegateChanged,
+};
 use anchor_lang::prelude::*;
 use mplx_staking_states::{
     error::MplStakingError,
     registrar_seeds,
-    state::{Registrar, Voter},
+    state::{Registrar, RegistrarLoader, Voter, VoterLoader},
 };
-use solana_program::clock::SECONDS_PER_DAY;
-pub const DELEGATE_UPDATE_DIFF_THRESHOLD: u64 = 5 * SECONDS_PER_DAY;
 
 #[derive(Accounts)]
 pub struct ChangeDelegate<'info> {
@@ -62,53 +64,143 @@ pub struct ChangeDelegate<'info> {
 /// Rewards will be recalculated, and the new delegate will start receiving rewards.
 /// The old delegate will stop receiving rewards.
 /// It might be done once per five days.
+///
+/// The new delegate must have an active `DelegateProfile` that accepts the staked
+/// tokens. The profiles of the old and the new delegate are passed as remaining
+/// accounts.
 pub fn change_delegate(ctx: Context<ChangeDelegate>, deposit_entry_index: u8) -> Result<()> {
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    change_delegates(ctx, &[deposit_entry_index])
+}
+
+/// Changes delegate for several stakes at once, like `change_delegate` does for one.
+///
+/// All deposits must have the same current delegate, and each of them must not
+/// have changed its delegate within the last five days. Every index may be given
+/// only once. The rewards program moves their summed staked amount in a single CPI.
+pub fn change_delegate_batch(
+    ctx: Context<ChangeDelegate>,
+    deposit_entry_indices: Vec<u8>,
+) -> Result<()> {
+    require_gte!(
+        Voter::MAX_DEPOSIT_ENTRIES,
+        deposit_entry_indices.len(),
+        MplStakingError::TooManyDepositEntryIndices
+    );
+    let mut seen = [false; Voter::MAX_DEPOSIT_ENTRIES];
+    for &deposit_entry_index in &deposit_entry_indices {
+        require!(
+            !std::mem::replace(&mut seen[deposit_entry_index as usize], true),
+            MplStakingError::DuplicateDepositEntryIndex
+        );
+    }
+    change_delegates(ctx, &deposit_entry_indices)
+}
+
+fn change_delegates(ctx: Context<ChangeDelegate>, deposit_entry_indices: &[u8]) -> Result<()> {
+    let registrar = &ctx.accounts.registrar.load_registrar()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
     let voter_authority = voter.voter_authority;
-    let target = voter.active_deposit_mut(deposit_entry_index)?;
+    let batch_minting_restricted = voter.is_batch_minting_restricted();
     let curr_ts = clock_unix_timestamp();
 
     require!(
         registrar.reward_pool == ctx.accounts.reward_pool.key(),
         MplStakingError::InvalidRewardPool
     );
+    let first_index = *deposit_entry_indices
+        .first()
+        .ok_or_else(|| error!(MplStakingError::NoDepositEntryIndices))?;
+    let old_delegate = voter.active_deposit_mut(first_index)?.delegate;
 
-    let delegate_last_update_diff = curr_ts
-        .checked_sub(target.delegate_last_update_ts)
-        .ok_or(MplStakingError::ArithmeticOverflow)?;
-
-    require!(
-        delegate_last_update_diff > DELEGATE_UPDATE_DIFF_THRESHOLD,
-        MplStakingError::DelegateUpdateIsTooSoon
-    );
-
-    if ctx.accounts.voter.key() == ctx.accounts.delegate_voter.key() {
+    let new_delegate = if ctx.accounts.voter.key() == ctx.accounts.delegate_voter.key() {
         require!(
-            target.delegate != voter_authority,
+            old_delegate != voter_authority,
             MplStakingError::SameDelegate
         );
-        target.delegate = voter_authority;
+        voter_authority
     } else {
-        let delegate_voter = &ctx.accounts.delegate_voter.load()?;
         require!(
-            ctx.accounts.voter.key() != ctx.accounts.delegate_voter.key()
-                && delegate_voter.voter_authority != target.delegate,
+            !batch_minting_restricted,
+            MplStakingError::BatchMintingRestricted
+        );
+
+        let delegate_voter = &ctx.accounts.delegate_voter.load_voter()?;
+        require!(
+            delegate_voter.voter_authority != old_delegate,
             MplStakingError::SameDelegate
         );
 
         let delegate_voter_weighted_stake = delegate_voter
-            .deposits
-            .iter()
+            .deposits()
             .fold(0, |acc, d| acc + d.weighted_stake(curr_ts));
         require!(
-            delegate_voter_weighted_stake >= Voter::MIN_OWN_WEIGHTED_STAKE,
+            delegate_voter_weighted_stake >= registrar.params.min_own_weighted_stake(),
             MplStakingError::InsufficientWeightedStake
         );
+        require_active_delegate(
+            &ctx.accounts.registrar.key(),
+            &delegate_voter.voter_authority,
+            ctx.remaining_accounts,
+        )?;
+
+        delegate_voter.voter_authority
+    };
+
+    let mut staked_amount: u64 = 0;
+    for &deposit_entry_index in deposit_entry_indices {
+        let target = voter.active_deposit_mut(deposit_entry_index)?;
+        // vesting deposits aren't staked, so they can't earn rewards for a delegate
+        require!(
+            !target.lockup.kind.is_vesting(),
+            MplStakingError::InvalidLockupKind
+        );
+        // the rewards program moves all of them away from a single old delegate
+        require!(
+            target.delegate == old_delegate,
+            MplStakingError::InvalidDelegate
+        );
+
+        let delegate_last_update_diff = curr_ts
+            .checked_sub(target.delegate_last_update_ts)
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+
+        require!(
+            delegate_last_update_diff > registrar.params.delegate_update_diff_threshold(),
+            MplStakingError::DelegateUpdateIsTooSoon
+        );
 
-        target.delegate = delegate_voter.voter_authority;
+        release_delegated_stake(
+            &ctx.accounts.registrar.key(),
+            registrar,
+            target,
+            ctx.remaining_accounts,
+        )?;
+        target.delegate = new_delegate;
+        target.delegate_last_update_ts = curr_ts;
+        sync_delegated_stake(
+            &ctx.accounts.registrar.key(),
+            registrar,
+            &voter_authority,
+            target,
+            ctx.remaining_accounts,
+        )?;
+
+        staked_amount = staked_amount
+            .checked_add(target.amount_deposited_native)
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+
+        emit!(DelegateChanged {
+            voter: ctx.accounts.voter.key(),
+            deposit_entry_index,
+            mint: registrar
+                .voting_mint(target.voting_mint_config_idx as usize)?
+                .mint,
+            amount: target.amount_deposited_native,
+            lockup_period: target.lockup.period,
+            old_delegate,
+            new_delegate,
+        });
     }
-    target.delegate_last_update_ts = curr_ts;
 
     let reward_pool = ctx.accounts.reward_pool.to_account_info();
     let mining = ctx.accounts.deposit_mining.to_account_info();
@@ -116,9 +208,7 @@ pub fn change_delegate(ctx: Context<ChangeDelegate>, deposit_entry_index: u8) ->
     let old_delegate_mining = ctx.accounts.old_delegate_mining.to_account_info();
     let new_delegate_mining = ctx.accounts.new_delegate_mining.to_account_info();
     let signers_seeds = registrar_seeds!(registrar);
-    let staked_amount = target.amount_deposited_native;
     let mining_owner = ctx.accounts.voter_authority.to_account_info();
-    let new_delegate = target.delegate;
 
     cpi_instructions::change_delegate(
         ctx.accounts.rewards_program.to_account_info(),
@@ -130,6 +220,7 @@ pub fn change_delegate(ctx: Context<ChangeDelegate>, deposit_entry_index: u8) ->
         new_delegate_mining,
         new_delegate,
         staked_amount,
+        true,
         signers_seeds,
     )?;
 
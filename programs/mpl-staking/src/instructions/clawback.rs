@@ -0,0 +1,246 @@
+use crate::{
+    clock_unix_timestamp, cpi_instructions, delegated_stake::release_delegated_stake,
+    events::ClawedBack, voter::VoterWeightRecord,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+use mplx_staking_states::{
+    error::MplStakingError,
+    registrar_seeds,
+    state::{DepositEntry, LockupKind, Registrar, RegistrarLoader, Voter, VoterLoader},
+    voter_seeds,
+};
+
+#[derive(Accounts)]
+pub struct Clawback<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    /// Must be the grant authority of the voting mint of the deposit.
+    pub grant_authority: Signer<'info>,
+
+    /// Clawbacks must update the voter weight record, to prevent a stale
+    /// record being used to vote after the clawback.
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), voter.load()?.voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.voter_authority,
+        constraint = voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    #[account(
+        mut,
+        associated_token::authority = voter,
+        associated_token::mint = destination.mint,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK:
+    /// Ownership of the account will be checked in the rewards contract
+    /// It's the core account for the rewards contract, which will
+    /// keep track of all rewards and staking logic.
+    #[account(mut)]
+    pub reward_pool: UncheckedAccount<'info>,
+
+    /// CHECK: mining PDA will be checked in the rewards contract
+    /// PDA(["mining", mining owner <aka voter_authority in our case>, reward_pool],
+    /// reward_program)
+    #[account(mut)]
+    pub deposit_mining: UncheckedAccount<'info>,
+
+    /// CHECK: Mining Account that belongs to Rewards Program and the delegate of the deposit
+    /// The address of the mining account on the rewards program,
+    /// derived from PDA(["mining", delegate wallet addr, reward_pool], rewards_program)
+    #[account(mut)]
+    pub delegate_mining: UncheckedAccount<'info>,
+
+    /// CHECK: Rewards Program account
+    #[account(executable)]
+    pub rewards_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Clawback<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.vault.to_account_info(),
+            to: self.destination.to_account_info(),
+            authority: self.voter.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+/// Returns the still locked tokens of a granted deposit to the grant authority.
+///
+/// Only possible for deposits created by `grant` with `allow_clawback`, and only
+/// as long as their lockup hasn't expired. The tokens that are already vested
+/// belong to the voter: they stay in the deposit and can be withdrawn.
+///
+/// `deposit_entry_index`: The deposit entry to claw back.
+pub fn clawback(ctx: Context<Clawback>, deposit_entry_index: u8) -> Result<()> {
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+    require!(
+        registrar.reward_pool == ctx.accounts.reward_pool.key(),
+        MplStakingError::InvalidRewardPool
+    );
+
+    let curr_ts = clock_unix_timestamp();
+    let (amount, mining_owner, delegate, staked_lockup, closed_voting_mint_idx) = {
+        let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+        let mining_owner = voter.voter_authority;
+        let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
+
+        let voting_mint_config =
+            registrar.voting_mint(deposit_entry.voting_mint_config_idx as usize)?;
+        require_keys_eq!(
+            voting_mint_config.grant_authority,
+            ctx.accounts.grant_authority.key(),
+            MplStakingError::InvalidAuthority
+        );
+        require_keys_eq!(
+            voting_mint_config.mint,
+            ctx.accounts.destination.mint,
+            MplStakingError::InvalidMint
+        );
+        require!(
+            deposit_entry.allow_clawback && !deposit_entry.lockup.expired(curr_ts),
+            MplStakingError::ClawbackNotAllowedOnDeposit
+        );
+
+        release_delegated_stake(
+            &ctx.accounts.registrar.key(),
+            &registrar,
+            deposit_entry,
+            ctx.remaining_accounts,
+        )?;
+        let amount = deposit_entry
+            .amount_deposited_native
+            .checked_sub(deposit_entry.amount_unlocked(curr_ts)?)
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+        let delegate = deposit_entry.delegate;
+        // vesting grants aren't staked, so there's nothing to take from the rewards pool
+        let staked_lockup = (deposit_entry.lockup.kind == LockupKind::Constant)
+            .then_some((deposit_entry.lockup.period, deposit_entry.lockup.end_ts));
+        deposit_entry.amount_deposited_native = deposit_entry
+            .amount_deposited_native
+            .checked_sub(amount)
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+
+        // slashed tokens still have to go to the treasury on withdraw
+        let closed_voting_mint_idx =
+            if deposit_entry.amount_deposited_native == 0 && deposit_entry.slashing_penalty == 0 {
+                let voting_mint_config_idx = deposit_entry.voting_mint_config_idx as usize;
+                *deposit_entry = DepositEntry::default();
+                deposit_entry.is_used = false;
+                Some(voting_mint_config_idx)
+            } else {
+                // the vested tokens that are left are no longer subject to the grant
+                deposit_entry.amount_initially_locked_native = 0;
+                deposit_entry.lockup.end_ts = curr_ts;
+                deposit_entry.allow_clawback = false;
+                None
+            };
+
+        emit!(ClawedBack {
+            voter: ctx.accounts.voter.key(),
+            grant_authority: ctx.accounts.grant_authority.key(),
+            deposit_entry_index,
+            mint: ctx.accounts.destination.mint,
+            amount,
+            amount_deposited_native: deposit_entry.amount_deposited_native,
+            destination: ctx.accounts.destination.key(),
+        });
+
+        // Update the voter weight record
+        let record = &mut ctx.accounts.voter_weight_record;
+        record.voter_weight = voter.weight(&registrar, curr_ts)?;
+        record.voter_weight_expiry = Some(Clock::get()?.slot);
+
+        (
+            amount,
+            mining_owner,
+            delegate,
+            staked_lockup,
+            closed_voting_mint_idx,
+        )
+    };
+
+    {
+        let voter = ctx.accounts.voter.load_voter()?;
+        let voter_seeds = voter_seeds!(voter);
+        token::transfer(
+            ctx.accounts.transfer_ctx().with_signer(&[voter_seeds]),
+            amount,
+        )?;
+    }
+
+    if let Some((lockup_period, stake_expiration_date)) = staked_lockup {
+        // Same as for slashing, the still locked stake is taken away from the rewards
+        // pool, weighted by the default multiplier of the lockup period.
+        let amount_multiplied_by_period = amount
+            .checked_mul(lockup_period.multiplier())
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+        let rewards_program = ctx.accounts.rewards_program.to_account_info();
+        let reward_pool = ctx.accounts.reward_pool.to_account_info();
+        let mining = ctx.accounts.deposit_mining.to_account_info();
+        let deposit_authority = ctx.accounts.registrar.to_account_info();
+        let signers_seeds = registrar_seeds!(&registrar);
+
+        let (slash_amount_in_native, slash_amount_multiplied_by_period) =
+            if delegate != mining_owner {
+                // A slash doesn't touch the mining of the delegate, so the stake the delegate
+                // got from the deposit is withdrawn first, together with the baseline of the
+                // stake of the owner. Only the bonus of the lockup period is left to slash.
+                cpi_instructions::withdraw_mining(
+                    rewards_program.clone(),
+                    reward_pool.clone(),
+                    mining.clone(),
+                    deposit_authority.clone(),
+                    ctx.accounts.delegate_mining.to_account_info(),
+                    amount,
+                    &mining_owner,
+                    signers_seeds,
+                    &delegate,
+                )?;
+                (0, amount_multiplied_by_period - amount)
+            } else {
+                (amount, amount_multiplied_by_period)
+            };
+
+        if slash_amount_multiplied_by_period > 0 {
+            cpi_instructions::slash(
+                rewards_program,
+                deposit_authority,
+                reward_pool,
+                mining,
+                &mining_owner,
+                slash_amount_in_native,
+                slash_amount_multiplied_by_period,
+                Some(stake_expiration_date),
+                signers_seeds,
+            )?;
+        }
+    }
+
+    if let Some(voting_mint_config_idx) = closed_voting_mint_idx {
+        // the registrar can only be borrowed mutably once it's no longer passed to CPIs
+        drop(registrar);
+        ctx.accounts
+            .registrar
+            .close_deposit_entry(voting_mint_config_idx)?;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
+use crate::events::DepositEntryClosed;
 use anchor_lang::prelude::*;
 use mplx_staking_states::{
     error::MplStakingError,
-    state::{DepositEntry, Voter},
+    state::{DepositEntry, Registrar, RegistrarLoader, Voter, VoterLoader},
 };
 
 #[derive(Accounts)]
@@ -10,18 +11,23 @@ pub struct CloseDepositEntry<'info> {
     // the other constraints must be exhaustive
     #[account(
         mut,
-        seeds = [voter.load()?.registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
         bump = voter.load()?.voter_bump,
+        has_one = registrar,
         has_one = voter_authority)]
     pub voter: AccountLoader<'info, Voter>,
     pub voter_authority: Signer<'info>,
+
+    /// Only needs to be writable if the deposit entries of the mint are counted,
+    /// see `RegistrarLoader::close_deposit_entry`.
+    pub registrar: AccountLoader<'info, Registrar>,
 }
 
 /// Close an empty deposit entry, allowing it to be reused in the future.
 ///
 /// Deposit entries can only be closed when they don't hold any tokens.
 pub fn close_deposit_entry(ctx: Context<CloseDepositEntry>, deposit_entry_index: u8) -> Result<()> {
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
     let d = voter.active_deposit_mut(deposit_entry_index)?;
     require_eq!(
         d.amount_deposited_native,
@@ -29,6 +35,18 @@ pub fn close_deposit_entry(ctx: Context<CloseDepositEntry>, deposit_entry_index:
         MplStakingError::VotingTokenNonZero
     );
 
+    emit!(DepositEntryClosed {
+        voter: ctx.accounts.voter.key(),
+        deposit_entry_index,
+        voting_mint_config_index: d.voting_mint_config_idx,
+        lockup_kind: d.lockup.kind,
+        lockup_period: d.lockup.period,
+    });
+
+    ctx.accounts
+        .registrar
+        .close_deposit_entry(d.voting_mint_config_idx as usize)?;
+
     *d = DepositEntry::default();
     d.is_used = false;
 
@@ -1,20 +1,20 @@
-use crate::cpi_instructions;
+use crate::{clock_unix_timestamp, cpi_instructions, events::VoterClosed};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
-use bytemuck::bytes_of_mut;
 use mplx_staking_states::{
     error::MplStakingError,
     registrar_seeds,
-    state::{Registrar, Voter},
+    state::{Registrar, RegistrarLoader, Voter, VoterLoader},
     voter_seeds,
 };
-use std::ops::DerefMut;
 
 // Remaining accounts must be all the token token accounts owned by voter, he wants to close,
 // they should be writable so that they can be closed and sol required for rent
 // can then be sent back to the sol_destination
 #[derive(Accounts)]
 pub struct CloseVoter<'info> {
+    /// Only needs to be writable if the deposit entries of a closed deposit entry's
+    /// mint are counted, see `RegistrarLoader::close_deposit_entry`.
     pub registrar: AccountLoader<'info, Registrar>,
 
     // checking the PDA address it just an extra precaution,
@@ -58,32 +58,38 @@ pub struct CloseVoter<'info> {
 /// Only accounts with no remaining lockups can be closed.
 /// remaining_accounts: All voter vaults followed by target token accounts, in order.
 pub fn close_voter<'info>(ctx: Context<'_, '_, '_, 'info, CloseVoter<'info>>) -> Result<()> {
-    let registrar = ctx.accounts.registrar.load()?;
+    let registrar = ctx.accounts.registrar.load_registrar()?;
 
     require!(
         registrar.reward_pool == ctx.accounts.reward_pool.key(),
         MplStakingError::InvalidRewardPool
     );
 
-    {
-        let voter = ctx.accounts.voter.load()?;
+    let closed_voting_mint_idxs: Vec<u8> = {
+        let voter = ctx.accounts.voter.load_voter()?;
 
-        let any_locked = voter.deposits.iter().any(|d| d.amount_locked() > 0);
+        let curr_ts = clock_unix_timestamp();
+        let mut any_locked = false;
+        for d in voter.deposits() {
+            any_locked |= d.amount_locked(curr_ts)? > 0;
+        }
         require!(!any_locked, MplStakingError::DepositStillLocked);
 
-        let active_deposit_entries = voter.deposits.iter().filter(|d| d.is_used).count();
+        let active_deposit_entries = voter.deposits().filter(|d| d.is_used).count();
         require_eq!(ctx.remaining_accounts.len(), active_deposit_entries);
 
         let voter_seeds = voter_seeds!(voter);
 
-        let active_deposits = voter.deposits.iter().filter(|d| d.is_used);
+        let active_deposits = voter.deposits().filter(|d| d.is_used);
         let deposit_vaults = &ctx.remaining_accounts[..active_deposit_entries];
         let target_accounts = &ctx.remaining_accounts[active_deposit_entries..];
 
         for ((deposit, deposit_vault), target_account) in
             active_deposits.zip(deposit_vaults).zip(target_accounts)
         {
-            let mint = &registrar.voting_mints[deposit.voting_mint_config_idx as usize].mint;
+            let mint = &registrar
+                .voting_mint(deposit.voting_mint_config_idx as usize)?
+                .mint;
 
             let token = Account::<TokenAccount>::try_from(&deposit_vault.clone()).unwrap();
             require_keys_eq!(
@@ -123,14 +129,19 @@ pub fn close_voter<'info>(ctx: Context<'_, '_, '_, 'info, CloseVoter<'info>>) ->
 
             deposit_vault.exit(ctx.program_id)?;
         }
-    }
+
+        voter
+            .deposits()
+            .filter(|d| d.is_used)
+            .map(|d| d.voting_mint_config_idx)
+            .collect()
+    };
 
     {
-        // zero out voter account to prevent reinit attacks
-        let mut voter = ctx.accounts.voter.load_mut()?;
-        let voter_dereffed = voter.deref_mut();
-        let voter_bytes = bytes_of_mut(voter_dereffed);
-        voter_bytes.fill(0);
+        // zero out voter account, including any extra deposit entries,
+        // to prevent reinit attacks
+        let voter: &AccountInfo = ctx.accounts.voter.as_ref();
+        voter.try_borrow_mut_data()?[8..].fill(0);
     }
 
     let reward_pool = &ctx.accounts.reward_pool;
@@ -150,5 +161,20 @@ pub fn close_voter<'info>(ctx: Context<'_, '_, '_, 'info, CloseVoter<'info>>) ->
         signers_seeds,
     )?;
 
+    emit!(VoterClosed {
+        voter: ctx.accounts.voter.key(),
+        voter_authority: ctx.accounts.voter_authority.key(),
+        registrar: ctx.accounts.registrar.key(),
+        sol_destination: ctx.accounts.sol_destination.key(),
+    });
+
+    // the registrar can only be borrowed mutably once it's no longer passed to CPIs
+    drop(registrar);
+    for voting_mint_config_idx in closed_voting_mint_idxs {
+        ctx.accounts
+            .registrar
+            .close_deposit_entry(voting_mint_config_idx as usize)?;
+    }
+
     Ok(())
 }
@@ -1,8 +1,9 @@
+use crate::instructions::realloc_registrar;
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
 use mplx_staking_states::{
     error::MplStakingError,
-    state::{Registrar, VotingMintConfig},
+    state::{Registrar, RegistrarLoader, VotingMint, VotingMintConfig},
 };
 
 // Remaining accounts must be all the token mints that have registered
@@ -15,6 +16,12 @@ pub struct ConfigureVotingMint<'info> {
 
     /// Tokens of this mint will produce vote weight
     pub mint: Account<'info, Mint>,
+
+    /// Pays the rent if the registrar has to be grown for the exchange rate.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
     // This instruction expects that all voting mint addresses, including a
     // newly registered one, are passed in ctx.remainingAccounts.
 }
@@ -23,25 +30,55 @@ pub struct ConfigureVotingMint<'info> {
 /// deposit the mint in exchange for vote weight. There can only be a single
 /// exchange rate per mint.
 ///
-/// * `idx`: index of the rate to be set
+/// * `idx`: index of the rate to be set, the registrar must have been grown by `grow_registrar` to
+///   use indexes beyond the first two
 /// * `grant_authority`: The keypair that might be an authority for Grant/Clawback
+/// * `digit_shift`: Number of digits native token amounts are shifted by, to normalise mints with
+///   different decimals. Negative values divide.
+/// * `baseline_vote_weight_scaled_factor`: Vote weight of one shifted token, in units of
+///   `VotingMint::SCALED_FACTOR_BASE` (1e9), to discount e.g. LP tokens.
 ///
 /// This instruction can be called several times for the same mint and index to
 /// change the voting mint configuration.
 ///
-/// The vote weight for `amount` of native tokens will be 1:1. Therefore, all active
-/// deposited tokens (locked or not) will be sumed up.
-
+/// The vote weight for `amount` of native tokens is
+/// `amount * 10^digit_shift * baseline_vote_weight_scaled_factor / 1e9`,
+/// and all active deposited tokens (locked or not) are summed up. The factor can't be 0.
+/// The voting mints stored in `Registrar` only support a rate of 1, i.e. a `digit_shift`
+/// of 0 and a factor of 1e9, so a registrar that hasn't been grown by `grow_registrar`
+/// is grown to hold one more voting mint when another rate is set, paid by `payer`.
+/// Growing moves the voting mints that are already configured, so this also sets a rate
+/// for a mint that was configured before.
 pub fn configure_voting_mint(
     ctx: Context<ConfigureVotingMint>,
     idx: u16,
     grant_authority: Option<Pubkey>,
+    digit_shift: i8,
+    baseline_vote_weight_scaled_factor: u64,
 ) -> Result<()> {
-    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    require_gt!(
+        baseline_vote_weight_scaled_factor,
+        0,
+        MplStakingError::InvalidVoteWeightScaledFactor
+    );
+    let voting_mints_len = ctx.accounts.registrar.load_registrar()?.voting_mints_len();
+    if voting_mints_len <= Registrar::INLINE_VOTING_MINTS
+        && (digit_shift != 0
+            || baseline_vote_weight_scaled_factor != VotingMint::SCALED_FACTOR_BASE)
+    {
+        realloc_registrar(
+            &ctx.accounts.registrar,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Registrar::INLINE_VOTING_MINTS + 1,
+        )?;
+    }
+
+    let registrar = &mut ctx.accounts.registrar.load_registrar_mut()?;
     let mint = ctx.accounts.mint.key();
     let idx = idx as usize;
     require_gt!(
-        registrar.voting_mints.len(),
+        registrar.voting_mints_len(),
         idx,
         MplStakingError::OutOfBoundsVotingMintConfigIndex
     );
@@ -55,15 +92,16 @@ pub fn configure_voting_mint(
             MplStakingError::VotingMintConfiguredWithDifferentIndex
         ),
         Err(_) => require!(
-            !registrar.voting_mints[idx].in_use(),
+            !registrar.voting_mint(idx)?.in_use(),
             MplStakingError::VotingMintConfigIndexAlreadyInUse
         ),
     };
 
-    registrar.voting_mints[idx] = VotingMintConfig {
+    *registrar.voting_mint_mut(idx)? = VotingMintConfig {
         mint,
         grant_authority: grant_authority.unwrap_or_default(),
     };
+    registrar.set_vote_weight_rate(idx, digit_shift, baseline_vote_weight_scaled_factor)?;
 
     // Check for overflow in vote weight
     registrar.max_vote_weight(ctx.remaining_accounts)?;
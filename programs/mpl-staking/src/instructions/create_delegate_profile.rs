@@ -0,0 +1,59 @@
+use crate::events::DelegateProfileUpdated;
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{DelegateProfile, Registrar, Voter};
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct CreateDelegateProfile<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), b"delegate-profile".as_ref(), voter_authority.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<DelegateProfile>(),
+    )]
+    pub delegate_profile: AccountLoader<'info, DelegateProfile>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Opts the voter in to be chosen as a delegate by other voters.
+///
+/// The profile starts out active and accepts up to `max_accepted_stake` staked
+/// tokens from other voters. See `update_delegate_profile` to change it.
+pub fn create_delegate_profile(
+    ctx: Context<CreateDelegateProfile>,
+    max_accepted_stake: u64,
+    metadata_uri: String,
+) -> Result<()> {
+    let delegate_profile = &mut ctx.accounts.delegate_profile.load_init()?;
+    delegate_profile.registrar = ctx.accounts.registrar.key();
+    delegate_profile.delegate = ctx.accounts.voter_authority.key();
+    delegate_profile.max_accepted_stake = max_accepted_stake;
+    delegate_profile.set_metadata_uri(&metadata_uri)?;
+    delegate_profile.active = true;
+    delegate_profile.bump = *ctx.bumps.get("delegate_profile").unwrap();
+
+    emit!(DelegateProfileUpdated {
+        registrar: delegate_profile.registrar,
+        delegate: delegate_profile.delegate,
+        active: true,
+        max_accepted_stake,
+        metadata_uri,
+    });
+
+    Ok(())
+}
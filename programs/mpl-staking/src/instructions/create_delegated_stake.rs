@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{DelegatedStake, Registrar, Voter};
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct CreateDelegatedStake<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar)]
+    pub delegate_voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), b"delegated-stake".as_ref(), delegate_voter.load()?.voter_authority.as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<DelegatedStake>(),
+    )]
+    pub delegated_stake: AccountLoader<'info, DelegatedStake>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the account that sums up the staked tokens delegated to a voter, so
+/// they can vote with them once the registrar has delegate voting enabled.
+///
+/// There can only be a single account per delegate, so anyone may create it.
+pub fn create_delegated_stake(ctx: Context<CreateDelegatedStake>) -> Result<()> {
+    let delegated_stake = &mut ctx.accounts.delegated_stake.load_init()?;
+    delegated_stake.registrar = ctx.accounts.registrar.key();
+    delegated_stake.delegate = ctx.accounts.delegate_voter.load()?.voter_authority;
+    delegated_stake.bump = *ctx.bumps.get("delegated_stake").unwrap();
+
+    Ok(())
+}
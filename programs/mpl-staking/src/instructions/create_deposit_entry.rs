@@ -1,4 +1,4 @@
-use crate::clock_unix_timestamp;
+use crate::{clock_unix_timestamp, delegated_stake::require_active_delegate};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -6,11 +6,16 @@ use anchor_spl::{
 };
 use mplx_staking_states::{
     error::MplStakingError,
-    state::{DepositEntry, Lockup, LockupKind, LockupPeriod, Registrar, Voter},
+    state::{
+        DepositEntry, Lockup, LockupKind, LockupPeriod, Registrar, RegistrarLoader, Voter,
+        VoterLoader,
+    },
 };
 
 #[derive(Accounts)]
 pub struct CreateDepositEntry<'info> {
+    /// Only needs to be writable if the deposit entries of the mint are counted,
+    /// see `RegistrarLoader::open_deposit_entry`.
     pub registrar: AccountLoader<'info, Registrar>,
 
     // checking the PDA address it just an extra precaution,
@@ -47,6 +52,9 @@ pub struct CreateDepositEntry<'info> {
 /// Initializes a deposit entry with the requested settings.
 /// Will error if the deposit entry is already in use.
 ///
+/// A delegate other than the voter itself must have an active `DelegateProfile`,
+/// passed as a remaining account.
+///
 /// - `deposit_entry_index`: deposit entry to use
 /// - `kind`: Type of lockup to use.
 /// - `period`: An enum that represents possible options for locking up.
@@ -57,21 +65,30 @@ pub fn create_deposit_entry(
     period: LockupPeriod,
 ) -> Result<()> {
     // Load accounts.
-    let registrar = &ctx.accounts.registrar.load()?;
-    let mut voter = ctx.accounts.voter.load_mut()?;
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+    let mut voter = ctx.accounts.voter.load_voter_mut()?;
 
     let delegate = if ctx.accounts.delegate_voter.key() != ctx.accounts.voter.key() {
+        require!(
+            !voter.is_batch_minting_restricted(),
+            MplStakingError::BatchMintingRestricted
+        );
+
         let curr_ts = clock_unix_timestamp();
-        let delegate_voter = ctx.accounts.delegate_voter.load()?;
+        let delegate_voter = ctx.accounts.delegate_voter.load_voter()?;
 
         let delegate_voter_weighted_stake = delegate_voter
-            .deposits
-            .iter()
+            .deposits()
             .fold(0, |acc, d| acc + d.weighted_stake(curr_ts));
         require!(
-            delegate_voter_weighted_stake >= Voter::MIN_OWN_WEIGHTED_STAKE,
+            delegate_voter_weighted_stake >= registrar.params.min_own_weighted_stake(),
             MplStakingError::InsufficientUnlockedTokens
         );
+        require_active_delegate(
+            &ctx.accounts.registrar.key(),
+            &delegate_voter.voter_authority,
+            ctx.remaining_accounts,
+        )?;
 
         delegate_voter.voter_authority.key()
     } else {
@@ -80,8 +97,8 @@ pub fn create_deposit_entry(
 
     // if both period and lockup are None, that means the deposit entry is not lockable
     // in that case delegate field doesn't make sense and should be the same as mining account
-    // derived from voter
-    if period == LockupPeriod::None && kind == LockupKind::None {
+    // derived from voter. The same goes for vesting deposits, they aren't staked.
+    if (period == LockupPeriod::None && kind == LockupKind::None) || kind.is_vesting() {
         require!(
             delegate == voter.voter_authority.key(),
             MplStakingError::InvalidDelegate
@@ -90,14 +107,11 @@ pub fn create_deposit_entry(
 
     // Get the exchange rate entry associated with this deposit.
     let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+    drop(registrar);
+    ctx.accounts.registrar.open_deposit_entry(mint_idx)?;
 
     // Get and set up the deposit entry.
-    require_gt!(
-        voter.deposits.len(),
-        deposit_entry_index as usize,
-        MplStakingError::OutOfBoundsDepositEntryIndex
-    );
-    let d_entry = &mut voter.deposits[deposit_entry_index as usize];
+    let d_entry = voter.deposit_mut(deposit_entry_index)?;
     require!(!d_entry.is_used, MplStakingError::UnusedDepositEntryIndex);
 
     let start_ts = clock_unix_timestamp();
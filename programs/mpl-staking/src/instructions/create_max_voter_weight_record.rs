@@ -0,0 +1,55 @@
+use crate::voter::MaxVoterWeightRecord;
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{Registrar, RegistrarLoader};
+use std::mem::size_of;
+
+// Remaining accounts must be all the token mints that have registered
+// as voting mints.
+#[derive(Accounts)]
+pub struct CreateMaxVoterWeightRecord<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The max voter weight record is the account that will be shown to spl-governance
+    /// as the max vote weight of the realm. See update_max_voter_weight_record.
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), b"max-voter-weight-record".as_ref()],
+        bump,
+        payer = payer,
+        space = size_of::<MaxVoterWeightRecord>(),
+    )]
+    pub max_voter_weight_record: Box<Account<'info, MaxVoterWeightRecord>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the max voter weight record of the registrar. There can only be a single
+/// record per registrar, so anyone may create it.
+///
+/// The record is filled in right away, see update_max_voter_weight_record.
+pub fn create_max_voter_weight_record(
+    ctx: Context<CreateMaxVoterWeightRecord>,
+    max_voter_weight_record_bump: u8,
+) -> Result<()> {
+    require_eq!(
+        max_voter_weight_record_bump,
+        *ctx.bumps.get("max_voter_weight_record").unwrap()
+    );
+
+    let registrar = &mut ctx.accounts.registrar.load_registrar_mut()?;
+    registrar.max_voter_weight_record_bump = max_voter_weight_record_bump;
+
+    let record = &mut ctx.accounts.max_voter_weight_record;
+    record.account_discriminator =
+        spl_governance_addin_api::max_voter_weight::MaxVoterWeightRecord::ACCOUNT_DISCRIMINATOR;
+    record.realm = registrar.realm;
+    record.governing_token_mint = registrar.realm_governing_token_mint;
+    record.max_voter_weight = registrar.max_vote_weight(ctx.remaining_accounts)?;
+    record.max_voter_weight_expiry = Some(Clock::get()?.slot);
+
+    Ok(())
+}
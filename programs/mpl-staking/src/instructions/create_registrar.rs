@@ -1,7 +1,10 @@
 use crate::cpi_instructions;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token};
-use mplx_staking_states::{error::MplStakingError, state::Registrar};
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{Registrar, RegistrarParams, VotingMultipliers},
+};
 use spl_governance::state::realm;
 use std::mem::size_of;
 
@@ -80,6 +83,8 @@ pub fn create_registrar(
         registrar.realm_governing_token_mint = ctx.accounts.realm_governing_token_mint.key();
         registrar.realm_authority = ctx.accounts.realm_authority.key();
         registrar.reward_pool = ctx.accounts.reward_pool.key();
+        registrar.voting_multipliers = VotingMultipliers::default();
+        registrar.params = RegistrarParams::default();
 
         // Verify that "realm_authority" is the expected authority on "realm"
         // and that the mint matches one of the realm mints too.
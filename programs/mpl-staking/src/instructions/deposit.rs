@@ -1,9 +1,9 @@
-use crate::clock_unix_timestamp;
+use crate::{clock_unix_timestamp, events::Deposited};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
 use mplx_staking_states::{
     error::MplStakingError,
-    state::{LockupKind, LockupPeriod, Registrar, Voter},
+    state::{LockupKind, LockupPeriod, Registrar, RegistrarLoader, Voter, VoterLoader},
 };
 
 #[derive(Accounts)]
@@ -53,6 +53,9 @@ impl<'info> Deposit<'info> {
 /// Tokens will be transfered from deposit_token to vault using the deposit_authority.
 ///
 /// The deposit entry must have been initialized with create_deposit_entry.
+/// Only deposit entries without a lockup or with a vesting lockup accept tokens,
+/// unless they were granted with `allow_clawback`.
+/// Tokens added to a vesting deposit vest over its remaining periods.
 ///
 /// `deposit_entry_index`: Index of the deposit entry.
 /// `amount`: Number of native tokens to transfer.
@@ -61,15 +64,18 @@ pub fn deposit(ctx: Context<Deposit>, deposit_entry_index: u8, amount: u64) -> R
         return Ok(());
     }
 
-    let registrar = &ctx.accounts.registrar.load()?;
+    let registrar = &ctx.accounts.registrar.load_registrar()?;
     let curr_ts = clock_unix_timestamp();
 
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
     let d_entry = voter.active_deposit_mut(deposit_entry_index)?;
+    // granted deposits that can be clawed back only hold the grant, clawback
+    // would take the voter's own tokens as well
     require!(
-        d_entry.lockup.kind == LockupKind::None
-            && d_entry.lockup.period == LockupPeriod::None
-            && d_entry.is_used,
+        ((d_entry.lockup.kind == LockupKind::None && d_entry.lockup.period == LockupPeriod::None)
+            || d_entry.lockup.kind.is_vesting())
+            && d_entry.is_used
+            && !d_entry.allow_clawback,
         MplStakingError::DepositingIsForbidded,
     );
 
@@ -83,17 +89,22 @@ pub fn deposit(ctx: Context<Deposit>, deposit_entry_index: u8, amount: u64) -> R
 
     // Deposit tokens into the vault and increase the lockup amount too.
     token::transfer(ctx.accounts.transfer_ctx(), amount)?;
-    d_entry.amount_deposited_native = d_entry.amount_deposited_native.checked_add(amount).unwrap();
+    if d_entry.lockup.kind.is_vesting() {
+        d_entry.add_to_vesting(curr_ts, amount)?;
+    } else {
+        d_entry.amount_deposited_native =
+            d_entry.amount_deposited_native.checked_add(amount).unwrap();
+    }
 
-    msg!(
-        "Deposited amount {} at deposit index {} with lockup kind {:?} with lockup period {:?} and {} seconds left. It's used now: {:?}",
-        amount,
+    emit!(Deposited {
+        voter: ctx.accounts.voter.key(),
         deposit_entry_index,
-        d_entry.lockup.kind,
-        d_entry.lockup.period,
-        d_entry.lockup.seconds_left(curr_ts),
-        d_entry.is_used,
-    );
+        mint: ctx.accounts.deposit_token.mint,
+        amount,
+        amount_deposited_native: d_entry.amount_deposited_native,
+        lockup_kind: d_entry.lockup.kind,
+        lockup_period: d_entry.lockup.period,
+    });
 
     Ok(())
 }
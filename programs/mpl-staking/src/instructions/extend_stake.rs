@@ -1,9 +1,12 @@
-use crate::{clock_unix_timestamp, cpi_instructions, Stake};
+use crate::{
+    clock_unix_timestamp, cpi_instructions, delegated_stake::sync_delegated_stake,
+    events::StakeExtended, Stake,
+};
 use anchor_lang::prelude::*;
 use mplx_staking_states::{
     error::MplStakingError,
     registrar_seeds,
-    state::{LockupKind, LockupPeriod},
+    state::{LockupKind, LockupPeriod, RegistrarLoader, VoterLoader},
 };
 
 /// Prolongs the deposit
@@ -20,7 +23,7 @@ pub fn extend_stake(
     new_lockup_period: LockupPeriod,
     additional_amount: u64,
 ) -> Result<()> {
-    let registrar = ctx.accounts.registrar.load()?;
+    let registrar = ctx.accounts.registrar.load_registrar()?;
 
     require!(
         registrar.reward_pool == ctx.accounts.reward_pool.key(),
@@ -28,10 +31,14 @@ pub fn extend_stake(
     );
 
     let curr_ts = clock_unix_timestamp();
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    require!(
+        !voter.is_batch_minting_restricted(),
+        MplStakingError::BatchMintingRestricted
+    );
     let source = voter.active_deposit_mut(source_deposit_entry_index)?;
     let source_mint_idx = source.voting_mint_config_idx;
-    let source_available_tokens = source.amount_unlocked();
+    let source_available_tokens = source.amount_unlocked(curr_ts)?;
     require!(
         source.lockup.kind == LockupKind::None,
         MplStakingError::LockingIsForbidded
@@ -48,10 +55,18 @@ pub fn extend_stake(
         MplStakingError::InsufficientUnlockedTokens
     );
     require!(
-        target.lockup.period != LockupPeriod::None && target.lockup.kind != LockupKind::None,
+        target.lockup.period != LockupPeriod::None && target.lockup.kind == LockupKind::Constant,
         MplStakingError::ExtendDepositIsNotAllowed
     );
 
+    // granted tokens that might still be clawed back can't be restaked,
+    // once vested they belong to the voter
+    require!(
+        !target.allow_clawback || target.lockup.expired(curr_ts),
+        MplStakingError::ExtendDepositIsNotAllowed
+    );
+    target.allow_clawback = false;
+
     let start_ts = target.lockup.start_ts;
     let target_basic_amount = target.amount_deposited_native;
     let current_lockup_period = if target.lockup.expired(curr_ts) {
@@ -84,6 +99,13 @@ pub fn extend_stake(
         .checked_add(new_lockup_period.to_secs())
         .ok_or(MplStakingError::InvalidTimestampArguments)?;
     target.lockup.period = new_lockup_period;
+    sync_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &registrar,
+        &ctx.accounts.voter_authority.key(),
+        target,
+        ctx.remaining_accounts,
+    )?;
 
     let reward_pool = ctx.accounts.reward_pool.to_account_info();
     let mining = ctx.accounts.deposit_mining.to_account_info();
@@ -109,5 +131,19 @@ pub fn extend_stake(
         delegate_wallet_addr,
     )?;
 
+    emit!(StakeExtended {
+        voter: ctx.accounts.voter.key(),
+        source_deposit_entry_index,
+        target_deposit_entry_index,
+        mint: registrar.voting_mint(source_mint_idx as usize)?.mint,
+        additional_amount,
+        amount_deposited_native: target.amount_deposited_native,
+        old_lockup_period: current_lockup_period,
+        new_lockup_period,
+        lockup_start_ts: target.lockup.start_ts,
+        lockup_end_ts: target.lockup.end_ts,
+        delegate: target.delegate,
+    });
+
     Ok(())
 }
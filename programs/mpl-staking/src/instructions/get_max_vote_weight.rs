@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{Registrar, RegistrarLoader};
+
+// Remaining accounts must be all the token mints that have registered
+// as voting mints.
+#[derive(Accounts)]
+pub struct GetMaxVoteWeight<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+}
+
+/// Returns the max vote weight of the registrar as return data, for clients and
+/// other programs that need it without a `MaxVoterWeightRecord`.
+///
+/// See `DynamicRegistrar::max_vote_weight_from_supplies` for the definition.
+pub fn get_max_vote_weight(ctx: Context<GetMaxVoteWeight>) -> Result<u64> {
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+    registrar.max_vote_weight(ctx.remaining_accounts)
+}
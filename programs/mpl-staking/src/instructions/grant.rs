@@ -0,0 +1,260 @@
+use crate::{clock_unix_timestamp, cpi_instructions, events::Granted, voter::VoterWeightRecord};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount},
+};
+use mplx_staking_states::{
+    error::MplStakingError,
+    registrar_seeds,
+    state::{
+        DepositEntry, DynamicVoter, Lockup, LockupKind, LockupPeriod, Registrar, RegistrarLoader,
+        Voter, VoterLoader,
+    },
+};
+use std::{cell::RefMut, mem::size_of};
+
+#[derive(Accounts)]
+pub struct Grant<'info> {
+    /// Also, Registrar plays the role of deposit_authority on the Rewards Contract,
+    /// therefore their PDA that should sign the CPI call
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        init_if_needed,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<Voter>(),
+    )]
+    pub voter: AccountLoader<'info, Voter>,
+
+    /// CHECK: The grant is made on behalf of this address, it doesn't need to sign.
+    pub voter_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), voter_authority.key().as_ref()],
+        bump,
+        payer = payer,
+        space = size_of::<VoterWeightRecord>(),
+    )]
+    pub voter_weight_record: Box<Account<'info, VoterWeightRecord>>,
+
+    #[account(
+        init_if_needed,
+        associated_token::authority = voter,
+        associated_token::mint = deposit_mint,
+        payer = payer
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = deposit_token.owner == grant_authority.key(),
+        constraint = deposit_token.mint == deposit_mint.key(),
+    )]
+    pub deposit_token: Box<Account<'info, TokenAccount>>,
+
+    /// Must be the grant authority of the voting mint.
+    pub grant_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub deposit_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK:
+    /// Ownership of the account will be checked in the rewards contract
+    /// It's the core account for the rewards contract, which will
+    /// keep track of all rewards and staking logic.
+    #[account(mut)]
+    pub reward_pool: UncheckedAccount<'info>,
+
+    /// CHECK: mining PDA will be checked in the rewards contract
+    /// PDA(["mining", mining owner <aka voter_authority in our case>, reward_pool],
+    /// reward_program)
+    #[account(mut)]
+    pub deposit_mining: UncheckedAccount<'info>,
+
+    /// CHECK: Rewards Program account
+    #[account(executable)]
+    pub rewards_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> Grant<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.deposit_token.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.grant_authority.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+/// Stakes tokens of the grant authority on behalf of a voter.
+///
+/// Creates the voter (and its mining account) if it doesn't exist yet and puts the
+/// tokens into the first free deposit entry, locked up starting now. A `Constant`
+/// grant is staked for `period` and delegated to the voter itself. A `Daily` or
+/// `Monthly` grant vests over `periods` periods instead and isn't staked.
+///
+/// - `voter_bump`, `voter_weight_record_bump`: bumps of the voter PDAs.
+/// - `kind`: lockup kind of the grant, `Constant`, `Daily` or `Monthly`.
+/// - `period`: lockup period of a `Constant` grant, `None` for vesting grants.
+/// - `periods`: number of vesting periods of a vesting grant, zero for `Constant` grants.
+/// - `allow_clawback`: whether the grant authority may claw the tokens back while they are locked
+///   up, see `clawback`.
+/// - `amount`: number of native tokens to grant.
+#[allow(clippy::too_many_arguments)]
+pub fn grant(
+    ctx: Context<Grant>,
+    voter_bump: u8,
+    voter_weight_record_bump: u8,
+    kind: LockupKind,
+    period: LockupPeriod,
+    periods: u32,
+    allow_clawback: bool,
+    amount: u64,
+) -> Result<()> {
+    require_eq!(voter_bump, *ctx.bumps.get("voter").unwrap());
+    require_eq!(
+        voter_weight_record_bump,
+        *ctx.bumps.get("voter_weight_record").unwrap()
+    );
+
+    require!(amount > 0, MplStakingError::InvalidAmount);
+
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+    require!(
+        registrar.reward_pool == ctx.accounts.reward_pool.key(),
+        MplStakingError::InvalidRewardPool
+    );
+
+    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+    require_keys_eq!(
+        registrar.voting_mint(mint_idx)?.grant_authority,
+        ctx.accounts.grant_authority.key(),
+        MplStakingError::InvalidAuthority
+    );
+
+    let voter_authority = ctx.accounts.voter_authority.key();
+    let curr_ts = clock_unix_timestamp();
+    let lockup = if kind.is_vesting() {
+        require!(
+            period == LockupPeriod::None,
+            MplStakingError::InvalidLockupKind
+        );
+        Lockup::new_vesting(kind, curr_ts, periods)?
+    } else {
+        require!(
+            kind == LockupKind::Constant && periods == 0,
+            MplStakingError::InvalidLockupKind
+        );
+        Lockup::new(kind, curr_ts, period)?
+    };
+    let is_new_voter = {
+        let mut voter = match ctx.accounts.voter.load_init() {
+            // a new voter doesn't have any extra deposit entries
+            Ok(voter) => {
+                let (voter, extra_deposits) =
+                    RefMut::map_split(voter, |voter| (voter, &mut [][..]));
+                DynamicVoter::new(voter, extra_deposits)
+            }
+            Err(_) => ctx.accounts.voter.load_voter_mut()?,
+        };
+
+        let is_new_voter = voter.voter_authority == Pubkey::default();
+        if is_new_voter {
+            voter.voter_bump = voter_bump;
+            voter.voter_weight_record_bump = voter_weight_record_bump;
+            voter.voter_authority = voter_authority;
+            voter.registrar = ctx.accounts.registrar.key();
+        }
+
+        let deposit_entry_index = voter.free_deposit_index()?;
+        *voter.deposit_mut(deposit_entry_index as u8)? = DepositEntry {
+            lockup,
+            delegate: voter_authority,
+            amount_deposited_native: amount,
+            // the vesting schedule starts with all the granted tokens
+            amount_initially_locked_native: if kind.is_vesting() { amount } else { 0 },
+            delegate_last_update_ts: curr_ts,
+            voting_mint_config_idx: mint_idx as u8,
+            is_used: true,
+            allow_clawback,
+            ..Default::default()
+        };
+
+        emit!(Granted {
+            voter: ctx.accounts.voter.key(),
+            voter_authority,
+            grant_authority: ctx.accounts.grant_authority.key(),
+            deposit_entry_index: deposit_entry_index as u8,
+            mint: ctx.accounts.deposit_mint.key(),
+            amount,
+            lockup_kind: kind,
+            lockup_period: period,
+            lockup_start_ts: lockup.start_ts,
+            lockup_end_ts: lockup.end_ts,
+            allow_clawback,
+        });
+
+        is_new_voter
+    };
+
+    let voter_weight_record = &mut ctx.accounts.voter_weight_record;
+    voter_weight_record.account_discriminator =
+        spl_governance_addin_api::voter_weight::VoterWeightRecord::ACCOUNT_DISCRIMINATOR;
+    voter_weight_record.realm = registrar.realm;
+    voter_weight_record.governing_token_mint = registrar.realm_governing_token_mint;
+    voter_weight_record.governing_token_owner = voter_authority;
+
+    token::transfer(ctx.accounts.transfer_ctx(), amount)?;
+
+    let rewards_program = ctx.accounts.rewards_program.to_account_info();
+    let reward_pool = ctx.accounts.reward_pool.to_account_info();
+    let mining = ctx.accounts.deposit_mining.to_account_info();
+    // a re-created voter may still have its mining account
+    if is_new_voter && ctx.accounts.deposit_mining.data_is_empty() {
+        cpi_instructions::initialize_mining(
+            rewards_program.clone(),
+            reward_pool.clone(),
+            mining.clone(),
+            &voter_authority,
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+
+    // vesting grants aren't staked
+    if !kind.is_vesting() {
+        let signers_seeds = registrar_seeds!(&registrar);
+        cpi_instructions::deposit_mining(
+            rewards_program,
+            reward_pool,
+            mining.clone(),
+            ctx.accounts.registrar.to_account_info(),
+            mining,
+            amount,
+            period,
+            &voter_authority,
+            signers_seeds,
+            &voter_authority,
+        )?;
+    }
+
+    // the registrar can only be borrowed mutably once it's no longer passed to CPIs
+    drop(registrar);
+    ctx.accounts.registrar.open_deposit_entry(mint_idx)?;
+
+    Ok(())
+}
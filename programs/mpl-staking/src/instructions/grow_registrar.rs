@@ -0,0 +1,90 @@
+use crate::events::RegistrarGrown;
+use anchor_lang::{prelude::*, system_program};
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{Registrar, RegistrarLoader},
+};
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct GrowRegistrar<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Reallocates the registrar account to hold `additional_voting_mints` more voting mints,
+/// which can then be set up with `configure_voting_mint`.
+///
+/// Registrar accounts start out with the voting mints stored in `Registrar`. The first
+/// time a registrar is grown, its account is migrated to the extended layout, where a
+/// `RegistrarExtension` and all voting mints follow the `Registrar`, and the configured
+/// voting mints keep their indexes. The payer covers the additional rent.
+pub fn grow_registrar(ctx: Context<GrowRegistrar>, additional_voting_mints: u8) -> Result<()> {
+    let voting_mints_len = ctx.accounts.registrar.load_registrar()?.voting_mints_len();
+    let new_voting_mints_len = voting_mints_len + additional_voting_mints as usize;
+    realloc_registrar(
+        &ctx.accounts.registrar,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        new_voting_mints_len,
+    )
+}
+
+/// Reallocates the registrar account to hold `new_voting_mints_len` voting mints,
+/// migrating it to the extended layout the first time it's grown. The payer covers
+/// the additional rent.
+pub(crate) fn realloc_registrar<'info>(
+    registrar_loader: &AccountLoader<'info, Registrar>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_voting_mints_len: usize,
+) -> Result<()> {
+    let voting_mints_len = registrar_loader.load_registrar()?.voting_mints_len();
+    require_gte!(
+        Registrar::MAX_VOTING_MINTS,
+        new_voting_mints_len,
+        MplStakingError::TooManyVotingMints
+    );
+
+    let registrar = registrar_loader.to_account_info();
+    let new_space = Registrar::space(new_voting_mints_len);
+    let rent = Rent::get()?
+        .minimum_balance(new_space)
+        .saturating_sub(registrar.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: registrar.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    registrar.realloc(new_space, true)?;
+
+    // the version tag marks the account as extended
+    if voting_mints_len <= Registrar::INLINE_VOTING_MINTS
+        && new_voting_mints_len > Registrar::INLINE_VOTING_MINTS
+    {
+        registrar.try_borrow_mut_data()?[8 + size_of::<Registrar>()] = Registrar::VERSION_EXTENDED;
+        registrar_loader
+            .load_registrar_mut()?
+            .move_inline_voting_mints();
+    }
+
+    emit!(RegistrarGrown {
+        registrar: registrar.key(),
+        voting_mints: new_voting_mints_len as u8,
+    });
+
+    Ok(())
+}
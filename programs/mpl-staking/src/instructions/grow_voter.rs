@@ -0,0 +1,73 @@
+use crate::events::VoterGrown;
+use anchor_lang::{prelude::*, system_program};
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{Voter, VoterLoader},
+};
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct GrowVoter<'info> {
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [voter.load()?.registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Reallocates the voter account to hold `additional_deposit_entries` more deposit entries.
+///
+/// Voter accounts start out with the deposit entries stored in `Voter`. The first time
+/// a voter is grown, its account is migrated to the extended layout, where a
+/// `VoterExtension` and the additional deposit entries follow the `Voter`.
+/// The payer covers the additional rent. An account can grow by at most 10 KiB,
+/// i.e. 71 deposit entries, per instruction.
+pub fn grow_voter(ctx: Context<GrowVoter>, additional_deposit_entries: u8) -> Result<()> {
+    let deposits_len = ctx.accounts.voter.load_voter()?.deposits_len();
+    let new_deposits_len = deposits_len + additional_deposit_entries as usize;
+    require_gte!(
+        Voter::MAX_DEPOSIT_ENTRIES,
+        new_deposits_len,
+        MplStakingError::TooManyDepositEntries
+    );
+
+    let voter = ctx.accounts.voter.to_account_info();
+    let new_space = Voter::space(new_deposits_len);
+    let rent = Rent::get()?
+        .minimum_balance(new_space)
+        .saturating_sub(voter.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: voter.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    voter.realloc(new_space, true)?;
+
+    // the version tag marks the account as extended
+    if new_deposits_len > Voter::INLINE_DEPOSIT_ENTRIES {
+        voter.try_borrow_mut_data()?[8 + size_of::<Voter>()] = Voter::VERSION_EXTENDED;
+    }
+
+    emit!(VoterGrown {
+        voter: voter.key(),
+        deposit_entries: new_deposits_len as u16,
+    });
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
-use crate::events::*;
+use crate::{clock_unix_timestamp, events::*};
 use anchor_lang::prelude::*;
-use mplx_staking_states::state::{Registrar, Voter};
+use mplx_staking_states::state::{Registrar, RegistrarLoader, Voter, VoterLoader};
 
 #[derive(Accounts)]
 pub struct LogVoterInfo<'info> {
@@ -15,47 +15,96 @@ pub struct LogVoterInfo<'info> {
 /// Logs deposit information about deposits with an index between `deposit_entry_begin`
 /// and `deposit_entry_begin + deposit_entry_count`.
 ///
-/// With the current setup, all information about deposits can be logged by calling
-/// this with deposit_entry_begin=0, =8, =16, =24 and deposit_entry_count=8.
+/// A voter has 32 deposit entries, or up to `Voter::MAX_DEPOSIT_ENTRIES` (256) once
+/// grown, so all information about deposits can be logged by calling this with
+/// deposit_entry_begin=0, =8, =16, ... up to the number of entries of the voter and
+/// deposit_entry_count=8.
+///
+/// `log_voter_info_at` takes a `guaranteed_at_ts`, in which case the events also contain
+/// the voting power that is guaranteed to remain at that time, e.g. at the end of a
+/// proposal's voting period.
 pub fn log_voter_info(
     ctx: Context<LogVoterInfo>,
     deposit_entry_begin: u8,
     deposit_entry_count: u8,
+    guaranteed_at_ts: Option<u64>,
 ) -> Result<()> {
-    let voter = ctx.accounts.voter.load()?;
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+    let voter = ctx.accounts.voter.load_voter()?;
+    let curr_ts = clock_unix_timestamp();
     let deposit_entry_begin = deposit_entry_begin as usize;
     let deposit_entry_count = deposit_entry_count as usize;
 
     msg!("voter");
     emit!(VoterInfo {
-        voting_power: voter.weight()?,
-        voting_power_baseline: voter.weight_baseline(),
+        voting_power: voter.weight(&registrar, curr_ts)?,
+        voting_power_baseline: voter.weight_baseline(&registrar)?,
+        voting_power_locked_guaranteed: guaranteed_at_ts
+            .map(|at_ts| voter.weight_locked_guaranteed(&registrar, curr_ts, at_ts))
+            .transpose()?,
+        recent_penalties: voter
+            .recent_penalties()
+            .map(|record| PenaltyInfo {
+                kind: record.kind,
+                reason: record.reason,
+                day: record.day,
+            })
+            .collect(),
     });
 
     msg!("deposit_entries");
-    for (deposit_index, deposit) in voter.deposits.iter().enumerate() {
+    for (deposit_index, deposit) in voter.deposits().enumerate() {
         if !deposit.is_used
             || deposit_index < deposit_entry_begin
             || deposit_index >= deposit_entry_begin + deposit_entry_count
         {
             continue;
         }
-        let locking_info = if !deposit.lockup.cooldown_requested {
+        let lockup = &deposit.lockup;
+        let locking_info = if !lockup.cooldown_requested {
+            let vesting = if lockup.kind.is_vesting() && !lockup.expired(curr_ts) {
+                let periods_total = lockup.periods_total()?;
+                // vesting steps are counted back from the end of the lockup
+                let next_timestamp = lockup.end_ts
+                    - (periods_total - lockup.period_current(curr_ts)? - 1)
+                        * lockup.kind.period_secs();
+                Some(VestingInfo {
+                    rate: deposit
+                        .amount_initially_locked_native
+                        .checked_div(periods_total)
+                        .unwrap(),
+                    next_timestamp,
+                })
+            } else {
+                None
+            };
             Some(LockingInfo {
-                amount: deposit.amount_locked(),
-                end_timestamp: Some(deposit.lockup.end_ts),
-                vesting: None,
+                amount: deposit.amount_locked(curr_ts)?,
+                end_timestamp: Some(lockup.end_ts),
+                vesting,
+                auto_renew: deposit.auto_renew,
             })
         } else {
             None
         };
 
+        let mint_idx = deposit.voting_mint_config_idx as usize;
         emit!(DepositEntryInfo {
             deposit_entry_index: deposit_index as u8,
             voting_mint_config_index: deposit.voting_mint_config_idx,
-            unlocked: deposit.amount_unlocked(),
-            voting_power: deposit.voting_power()?,
-            voting_power_baseline: deposit.amount_deposited_native,
+            unlocked: deposit.amount_unlocked(curr_ts)?,
+            voting_power: registrar
+                .vote_weight(mint_idx, deposit.voting_power(&registrar, curr_ts)?)?,
+            voting_power_baseline: registrar
+                .vote_weight(mint_idx, deposit.amount_deposited_native)?,
+            voting_power_locked_guaranteed: guaranteed_at_ts
+                .map(|at_ts| {
+                    registrar.vote_weight(
+                        mint_idx,
+                        deposit.voting_power_locked_guaranteed(&registrar, curr_ts, at_ts)?,
+                    )
+                })
+                .transpose()?,
             locking: locking_info,
         });
     }
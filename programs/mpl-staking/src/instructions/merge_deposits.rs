@@ -0,0 +1,190 @@
+use crate::{
+    clock_unix_timestamp, cpi_instructions,
+    delegated_stake::{release_delegated_stake, sync_delegated_stake},
+    events::DepositsMerged,
+    Stake,
+};
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    registrar_seeds,
+    state::{DepositEntry, LockupKind, LockupPeriod, RegistrarLoader, VoterLoader},
+};
+
+/// Merges the source deposit entry into the target one and frees the source entry.
+///
+/// Both must be Constant deposits of the same mint and delegate that aren't cooling down.
+/// The merged deposit is locked up for the stricter of the two periods, restarted at the
+/// current time like `extend_stake` does. Expired lockups count as Flex.
+///
+/// Only the parts whose stake in the rewards mining doesn't already match the merged
+/// lockup are restaked.
+pub fn merge_deposits(
+    ctx: Context<Stake>,
+    source_deposit_entry_index: u8,
+    target_deposit_entry_index: u8,
+) -> Result<()> {
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+
+    require!(
+        registrar.reward_pool == ctx.accounts.reward_pool.key(),
+        MplStakingError::InvalidRewardPool
+    );
+    require_neq!(
+        source_deposit_entry_index,
+        target_deposit_entry_index,
+        MplStakingError::DepositsNotMergeable
+    );
+
+    let curr_ts = clock_unix_timestamp();
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    require!(
+        !voter.is_batch_minting_restricted(),
+        MplStakingError::BatchMintingRestricted
+    );
+
+    let source = *voter.active_deposit(source_deposit_entry_index)?;
+    let target = voter.active_deposit_mut(target_deposit_entry_index)?;
+    for deposit_entry in [&source, &*target] {
+        require!(
+            deposit_entry.lockup.kind == LockupKind::Constant,
+            MplStakingError::InvalidLockupKind
+        );
+        require!(
+            !deposit_entry.lockup.cooldown_requested,
+            MplStakingError::UnlockAlreadyRequested
+        );
+        // granted tokens that might still be clawed back must stay in their own entry
+        require!(
+            !deposit_entry.allow_clawback || deposit_entry.lockup.expired(curr_ts),
+            MplStakingError::DepositsNotMergeable
+        );
+    }
+    require_eq!(
+        target.voting_mint_config_idx,
+        source.voting_mint_config_idx,
+        MplStakingError::InvalidMint
+    );
+    require_eq!(
+        target.delegate,
+        source.delegate,
+        MplStakingError::InvalidDelegate
+    );
+    ctx.accounts.verify_delegate(target)?;
+
+    let effective_period = |deposit_entry: &DepositEntry| {
+        if deposit_entry.lockup.expired(curr_ts) {
+            LockupPeriod::Flex
+        } else {
+            deposit_entry.lockup.period
+        }
+    };
+    let source_lockup_period = effective_period(&source);
+    let target_lockup_period = effective_period(target);
+    let new_lockup_period = source_lockup_period.max(target_lockup_period);
+    let new_start_ts = curr_ts;
+    let new_end_ts = curr_ts
+        .checked_add(new_lockup_period.to_secs())
+        .ok_or(MplStakingError::InvalidTimestampArguments)?;
+    let target_start_ts = target.lockup.start_ts;
+    let target_end_ts = target.lockup.end_ts;
+    let target_amount = target.amount_deposited_native;
+
+    target.amount_deposited_native = target
+        .amount_deposited_native
+        .checked_add(source.amount_deposited_native)
+        .ok_or(MplStakingError::ArithmeticOverflow)?;
+    target.slashing_penalty = target
+        .slashing_penalty
+        .checked_add(source.slashing_penalty)
+        .ok_or(MplStakingError::ArithmeticOverflow)?;
+    target.delegate_last_update_ts = target
+        .delegate_last_update_ts
+        .max(source.delegate_last_update_ts);
+    target.allow_clawback = false;
+    target.lockup.start_ts = new_start_ts;
+    target.lockup.end_ts = new_end_ts;
+    target.lockup.period = new_lockup_period;
+    sync_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &registrar,
+        &ctx.accounts.voter_authority.key(),
+        target,
+        ctx.remaining_accounts,
+    )?;
+    let target = *target;
+
+    let source_entry = voter.active_deposit_mut(source_deposit_entry_index)?;
+    release_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &registrar,
+        source_entry,
+        ctx.remaining_accounts,
+    )?;
+    *source_entry = DepositEntry::default();
+    source_entry.is_used = false;
+
+    let signers_seeds = registrar_seeds!(&registrar);
+    let mining_owner = &ctx.accounts.voter_authority.key();
+    let delegate_wallet_addr = &ctx.accounts.delegate.key();
+
+    for (old_lockup_period, deposit_start_ts, deposit_end_ts, base_amount) in [
+        (
+            source_lockup_period,
+            source.lockup.start_ts,
+            source.lockup.end_ts,
+            source.amount_deposited_native,
+        ),
+        (
+            target_lockup_period,
+            target_start_ts,
+            target_end_ts,
+            target_amount,
+        ),
+    ] {
+        // the part that defines the merged lockup is already staked accordingly,
+        // and all Flex stakes have the same weight
+        if old_lockup_period == new_lockup_period
+            && (deposit_end_ts == new_end_ts || new_lockup_period == LockupPeriod::Flex)
+        {
+            continue;
+        }
+        cpi_instructions::extend_stake(
+            ctx.accounts.rewards_program.to_account_info(),
+            ctx.accounts.reward_pool.to_account_info(),
+            ctx.accounts.deposit_mining.to_account_info(),
+            ctx.accounts.registrar.to_account_info(),
+            ctx.accounts.delegate_mining.to_account_info(),
+            old_lockup_period,
+            new_lockup_period,
+            deposit_start_ts,
+            base_amount,
+            0,
+            mining_owner,
+            signers_seeds,
+            delegate_wallet_addr,
+        )?;
+    }
+
+    emit!(DepositsMerged {
+        voter: ctx.accounts.voter.key(),
+        source_deposit_entry_index,
+        target_deposit_entry_index,
+        mint: registrar
+            .voting_mint(target.voting_mint_config_idx as usize)?
+            .mint,
+        amount_deposited_native: target.amount_deposited_native,
+        lockup_period: new_lockup_period,
+        lockup_start_ts: target.lockup.start_ts,
+        lockup_end_ts: target.lockup.end_ts,
+        delegate: target.delegate,
+    });
+
+    // the registrar can only be borrowed mutably once it's no longer passed to CPIs
+    drop(registrar);
+    ctx.accounts
+        .registrar
+        .close_deposit_entry(source.voting_mint_config_idx as usize)?;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anchor_lang::{prelude::*, system_program, Discriminator};
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{Registrar, RegistrarParams, VotingMultipliers},
+};
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct MigrateRegistrar<'info> {
+    /// CHECK: a registrar with the original layout is too small to be loaded,
+    /// the discriminator and the size are checked in the instruction
+    #[account(mut, owner = crate::id())]
+    pub registrar: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Reallocates a registrar account created before the voting multipliers and the
+/// parameters were added to `Registrar`, and sets them to their defaults.
+///
+/// The original fields keep their offsets, so the registrar works as before. Anyone
+/// may migrate a registrar, the payer covers the additional rent.
+pub fn migrate_registrar(ctx: Context<MigrateRegistrar>) -> Result<()> {
+    let registrar = ctx.accounts.registrar.to_account_info();
+    require!(
+        registrar.try_borrow_data()?[..8] == Registrar::discriminator(),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    require_eq!(
+        registrar.data_len(),
+        Registrar::LEGACY_SPACE,
+        MplStakingError::RegistrarAlreadyMigrated
+    );
+
+    let new_space = 8 + size_of::<Registrar>();
+    let rent = Rent::get()?
+        .minimum_balance(new_space)
+        .saturating_sub(registrar.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: registrar.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    registrar.realloc(new_space, true)?;
+
+    let registrar_loader = AccountLoader::<Registrar>::try_from(&registrar)?;
+    let registrar = &mut registrar_loader.load_mut()?;
+    registrar.voting_multipliers = VotingMultipliers::default();
+    registrar.params = RegistrarParams::default();
+
+    Ok(())
+}
@@ -1,36 +1,82 @@
 use anchor_lang::prelude::SolanaSysvar;
+pub use cancel_unlock::*;
 pub use change_delegate::*;
 pub use claim::*;
+pub use clawback::*;
 pub use close_deposit_entry::*;
 pub use close_voter::*;
 pub use configure_voting_mint::*;
+pub use create_delegate_profile::*;
+pub use create_delegated_stake::*;
 pub use create_deposit_entry::*;
+pub use create_max_voter_weight_record::*;
 pub use create_registrar::*;
 pub use create_voter::*;
 pub use deposit::*;
 pub use extend_stake::*;
+pub use get_max_vote_weight::*;
+pub use grant::*;
+pub use grow_registrar::*;
+pub use grow_voter::*;
 pub use log_voter_info::*;
+pub use merge_deposits::*;
+pub use migrate_registrar::*;
 pub use penalties::*;
+pub use recount_voting_mint_deposits::*;
+pub use remove_voting_mint::*;
+pub use renew_stake::*;
+pub use revoke_stale_delegation::*;
+pub use set_auto_renew::*;
+pub use set_delegate_voting::*;
+pub use set_registrar_params::*;
+pub use set_voting_multipliers::*;
+pub use set_voting_power_mode::*;
 pub use solana_program::sysvar::clock::Clock;
+pub use split_deposit::*;
 pub use stake::*;
 pub use unlock_tokens::*;
+pub use update_delegate_profile::*;
+pub use update_max_voter_weight_record::*;
 pub use update_voter_weight_record::*;
 pub use withdraw::*;
 
+mod cancel_unlock;
 mod change_delegate;
 mod claim;
+mod clawback;
 mod close_deposit_entry;
 mod close_voter;
 mod configure_voting_mint;
+mod create_delegate_profile;
+mod create_delegated_stake;
 mod create_deposit_entry;
+mod create_max_voter_weight_record;
 mod create_registrar;
 mod create_voter;
 mod deposit;
 mod extend_stake;
+mod get_max_vote_weight;
+mod grant;
+mod grow_registrar;
+mod grow_voter;
 mod log_voter_info;
+mod merge_deposits;
+mod migrate_registrar;
 mod penalties;
+mod recount_voting_mint_deposits;
+mod remove_voting_mint;
+mod renew_stake;
+mod revoke_stale_delegation;
+mod set_auto_renew;
+mod set_delegate_voting;
+mod set_registrar_params;
+mod set_voting_multipliers;
+mod set_voting_power_mode;
+mod split_deposit;
 mod stake;
 mod unlock_tokens;
+mod update_delegate_profile;
+mod update_max_voter_weight_record;
 mod update_voter_weight_record;
 mod withdraw;
 
@@ -1,9 +1,12 @@
 use super::Penalty;
+use crate::{clock_unix_timestamp, events::TokenflowAllowed};
 use anchor_lang::prelude::*;
-use mplx_staking_states::error::MplStakingError;
+use mplx_staking_states::{error::MplStakingError, state::PenaltyKind};
 
 /// Restricts claiming rewards from the specified mining account.
-pub fn allow_tokenflow(ctx: Context<Penalty>) -> Result<()> {
+///
+/// `reason`: A reason code that's recorded in the voter's penalty log.
+pub fn allow_tokenflow(ctx: Context<Penalty>, reason: u8) -> Result<()> {
     let registrar = ctx.accounts.registrar.load()?;
 
     require_keys_eq!(
@@ -14,6 +17,14 @@ pub fn allow_tokenflow(ctx: Context<Penalty>) -> Result<()> {
 
     let mut voter = ctx.accounts.voter.load_mut()?;
     voter.allow_tokenflow()?;
+    voter.log_penalty(PenaltyKind::AllowTokenflow, reason, clock_unix_timestamp());
+
+    emit!(TokenflowAllowed {
+        registrar: ctx.accounts.registrar.key(),
+        voter: ctx.accounts.voter.key(),
+        authority: ctx.accounts.realm_authority.key(),
+        reason,
+    });
 
     Ok(())
 }
@@ -1,9 +1,9 @@
-use crate::cpi_instructions;
+use crate::{clock_unix_timestamp, cpi_instructions, events::RewardsDecreased};
 use anchor_lang::prelude::*;
 use mplx_staking_states::{
     error::MplStakingError,
     registrar_seeds,
-    state::{Registrar, Voter},
+    state::{PenaltyKind, Registrar, Voter},
 };
 
 #[derive(Accounts)]
@@ -43,10 +43,11 @@ pub struct DecreaseRewards<'info> {
 /// Reduces the weighted stake of the mining account wich leads to a decrease in rewards.
 ///
 /// - `decreased_weighted_stake_number`: weighted number to decrease by.
-/// - `mining_owner`: The owner of the mining account.
+/// - `reason`: A reason code that's recorded in the voter's penalty log.
 pub fn decrease_rewards(
     ctx: Context<DecreaseRewards>,
     decreased_weighted_stake_number: u64,
+    reason: u8,
 ) -> Result<()> {
     let registrar = ctx.accounts.registrar.load()?;
 
@@ -61,6 +62,15 @@ pub fn decrease_rewards(
         .decreased_weighted_stake_by
         .checked_add(decreased_weighted_stake_number)
         .ok_or(MplStakingError::ArithmeticOverflow)?;
+    voter.log_penalty(PenaltyKind::DecreaseRewards, reason, clock_unix_timestamp());
+
+    emit!(RewardsDecreased {
+        registrar: ctx.accounts.registrar.key(),
+        voter: ctx.accounts.voter.key(),
+        authority: ctx.accounts.realm_authority.key(),
+        decreased_weighted_stake_number,
+        reason,
+    });
 
     let signers_seeds = registrar_seeds!(&registrar);
     let mining_owner = ctx.accounts.voter_authority.to_account_info().key;
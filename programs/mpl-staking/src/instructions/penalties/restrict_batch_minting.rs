@@ -1,9 +1,15 @@
 use super::Penalty;
+use crate::{clock_unix_timestamp, events::BatchMintingRestricted};
 use anchor_lang::prelude::*;
-use mplx_staking_states::error::MplStakingError;
+use mplx_staking_states::{error::MplStakingError, state::PenaltyKind};
 
 /// Restricts batch minting operation for the account until the specified timestamp.
-pub fn restrict_batch_minting(ctx: Context<Penalty>, until_ts: u64) -> Result<()> {
+///
+/// Batch minting relies on staked tokens and delegations, so until then the voter
+/// can't stake, extend stakes, or delegate deposits to another voter.
+///
+/// `reason`: A reason code that's recorded in the voter's penalty log.
+pub fn restrict_batch_minting(ctx: Context<Penalty>, until_ts: u64, reason: u8) -> Result<()> {
     let registrar = ctx.accounts.registrar.load()?;
 
     require_keys_eq!(
@@ -14,6 +20,19 @@ pub fn restrict_batch_minting(ctx: Context<Penalty>, until_ts: u64) -> Result<()
 
     let mut voter = ctx.accounts.voter.load_mut()?;
     voter.batch_minting_restricted_until = until_ts;
+    voter.log_penalty(
+        PenaltyKind::RestrictBatchMinting,
+        reason,
+        clock_unix_timestamp(),
+    );
+
+    emit!(BatchMintingRestricted {
+        registrar: ctx.accounts.registrar.key(),
+        voter: ctx.accounts.voter.key(),
+        authority: ctx.accounts.realm_authority.key(),
+        until_ts,
+        reason,
+    });
 
     Ok(())
 }
@@ -1,9 +1,12 @@
-use crate::{clock_unix_timestamp, cpi_instructions, voter::VoterWeightRecord};
+use crate::{
+    clock_unix_timestamp, cpi_instructions, delegated_stake::sync_delegated_stake,
+    events::DepositSlashed, voter::VoterWeightRecord,
+};
 use anchor_lang::prelude::*;
 use mplx_staking_states::{
     error::MplStakingError,
     registrar_seeds,
-    state::{Registrar, Voter},
+    state::{PenaltyKind, Registrar, RegistrarLoader, Voter, VoterLoader},
 };
 
 #[derive(Accounts)]
@@ -52,13 +55,15 @@ pub struct Slashing<'info> {
 /// `deposit_entry_index`: The deposit entry to slash.
 /// `amount`: is in units of the native currency being slashed.
 /// `mining_owner`: The owner of the mining account.
+/// `reason`: A reason code that's recorded in the voter's penalty log.
 pub fn slash(
     ctx: Context<Slashing>,
     deposit_entry_index: u8,
     amount: u64,
     mining_owner: Pubkey,
+    reason: u8,
 ) -> Result<()> {
-    let registrar = ctx.accounts.registrar.load()?;
+    let registrar = ctx.accounts.registrar.load_registrar()?;
 
     require_keys_eq!(
         registrar.realm_authority,
@@ -66,7 +71,8 @@ pub fn slash(
         MplStakingError::InvalidRealmAuthority
     );
 
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    let voter_authority = voter.voter_authority;
     let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
 
     // Bookkeeping for slashed funds.
@@ -86,6 +92,13 @@ pub fn slash(
     // NB: accounts won't be closed automatically, even in case
     // of slashing. The user will have to withdraw the remaining
     // funds manually even if they're equal to zero.
+    sync_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &registrar,
+        &voter_authority,
+        deposit_entry,
+        ctx.remaining_accounts,
+    )?;
 
     msg!(
         "Slashed amount {} at deposit index {}",
@@ -93,32 +106,48 @@ pub fn slash(
         deposit_entry_index,
     );
 
-    let slash_amount_multiplied_by_period = amount
-        .checked_mul(deposit_entry.lockup.period.multiplier())
-        .ok_or(MplStakingError::ArithmeticOverflow)?;
     let curr_ts = clock_unix_timestamp();
-    let stake_expiration_date = if curr_ts > deposit_entry.lockup.end_ts {
-        None
-    } else {
-        Some(deposit_entry.lockup.end_ts)
-    };
-    let signers_seeds = registrar_seeds!(&registrar);
-
-    cpi_instructions::slash(
-        ctx.accounts.rewards_program.to_account_info(),
-        ctx.accounts.registrar.to_account_info(),
-        ctx.accounts.reward_pool.to_account_info(),
-        ctx.accounts.deposit_mining.to_account_info(),
-        &mining_owner,
+
+    // Vesting deposits aren't staked, the rewards program doesn't know about them.
+    if !deposit_entry.lockup.kind.is_vesting() {
+        // The rewards program weights the stake by the default multiplier of the
+        // lockup period, so that's what has to be taken away from it.
+        let slash_amount_multiplied_by_period = amount
+            .checked_mul(deposit_entry.lockup.period.multiplier())
+            .ok_or(MplStakingError::ArithmeticOverflow)?;
+        let stake_expiration_date = if curr_ts > deposit_entry.lockup.end_ts {
+            None
+        } else {
+            Some(deposit_entry.lockup.end_ts)
+        };
+        let signers_seeds = registrar_seeds!(&registrar);
+
+        cpi_instructions::slash(
+            ctx.accounts.rewards_program.to_account_info(),
+            ctx.accounts.registrar.to_account_info(),
+            ctx.accounts.reward_pool.to_account_info(),
+            ctx.accounts.deposit_mining.to_account_info(),
+            &mining_owner,
+            amount,
+            slash_amount_multiplied_by_period,
+            stake_expiration_date,
+            signers_seeds,
+        )?;
+    }
+
+    voter.log_penalty(PenaltyKind::Slash, reason, curr_ts);
+    emit!(DepositSlashed {
+        registrar: ctx.accounts.registrar.key(),
+        voter: ctx.accounts.voter.key(),
+        authority: ctx.accounts.realm_authority.key(),
+        deposit_entry_index,
         amount,
-        slash_amount_multiplied_by_period,
-        stake_expiration_date,
-        signers_seeds,
-    )?;
+        reason,
+    });
 
     // Update the voter weight record
     let record = &mut ctx.accounts.voter_weight_record;
-    record.voter_weight = voter.weight()?;
+    record.voter_weight = voter.weight(&registrar, curr_ts)?;
     record.voter_weight_expiry = Some(Clock::get()?.slot);
 
     Ok(())
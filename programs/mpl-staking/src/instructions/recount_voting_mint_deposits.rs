@@ -0,0 +1,38 @@
+use crate::events::VotingMintDepositsRecounted;
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{Registrar, RegistrarLoader};
+
+#[derive(Accounts)]
+pub struct RecountVotingMintDeposits<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Sets the number of open deposit entries of the voting mint at `idx` of a grown
+/// registrar to `open_deposit_entries`, which the realm authority counts off-chain
+/// across all voters of the registrar.
+///
+/// The deposit entries of voting mints configured before the registrar was grown
+/// aren't counted, so they can't be removed by `remove_voting_mint` until their
+/// count has been backfilled this way. From then on the count is kept up to date.
+/// The count can't be lowered below the deposit entries counted already.
+/// A registrar that hasn't been grown has to be grown by `grow_registrar` first.
+pub fn recount_voting_mint_deposits(
+    ctx: Context<RecountVotingMintDeposits>,
+    idx: u16,
+    open_deposit_entries: u64,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_registrar_mut()?;
+    let idx = idx as usize;
+    registrar.recount_deposit_entries(idx, open_deposit_entries)?;
+
+    emit!(VotingMintDepositsRecounted {
+        registrar: ctx.accounts.registrar.key(),
+        voting_mint_config_index: idx as u16,
+        mint: registrar.voting_mint(idx)?.mint,
+        open_deposit_entries,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::events::VotingMintRemoved;
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{Registrar, RegistrarLoader};
+
+#[derive(Accounts)]
+pub struct RemoveVotingMint<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Removes the voting mint at `idx` from the registrar, so its index can be used
+/// for another mint with `configure_voting_mint`.
+///
+/// Only voting mints of a registrar that has been grown by `grow_registrar` can be
+/// removed, and only once all their deposit entries have been closed. The deposit
+/// entries of voting mints configured before the registrar was grown aren't counted,
+/// so those mints can only be removed once their count has been backfilled by
+/// `recount_voting_mint_deposits`.
+pub fn remove_voting_mint(ctx: Context<RemoveVotingMint>, idx: u16) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_registrar_mut()?;
+    let idx = idx as usize;
+    let mint = registrar.voting_mint(idx)?.mint;
+    registrar.remove_voting_mint(idx)?;
+
+    emit!(VotingMintRemoved {
+        registrar: ctx.accounts.registrar.key(),
+        voting_mint_config_index: idx as u16,
+        mint,
+    });
+
+    Ok(())
+}
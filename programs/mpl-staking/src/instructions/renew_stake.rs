@@ -0,0 +1,145 @@
+use crate::{clock_unix_timestamp, cpi_instructions, events::StakeRenewed};
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    registrar_seeds,
+    state::{LockupKind, LockupPeriod, Registrar, RegistrarLoader, Voter, VoterLoader},
+};
+
+#[derive(Accounts)]
+pub struct RenewStake<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority,
+        has_one = registrar)
+    ]
+    pub voter: AccountLoader<'info, Voter>,
+
+    /// CHECK: the owner of the voter, doesn't need to sign since renewing is permissionless
+    pub voter_authority: UncheckedAccount<'info>,
+
+    /// CHECK: delegate might be any arbitrary address
+    pub delegate: UncheckedAccount<'info>,
+
+    /// CHECK: Mining Account that belongs to Rewards Program and some delegate
+    /// The address of the mining account on the rewards progra,
+    /// derived from PDA(["mining", delegate wallet addr, reward_pool], rewards_program)
+    #[account(mut)]
+    pub delegate_mining: UncheckedAccount<'info>,
+
+    /// CHECK:
+    /// Ownership of the account will be checked in the rewards contract
+    /// It's the core account for the rewards contract, which will
+    /// keep track of all rewards and staking logic.
+    #[account(mut)]
+    pub reward_pool: UncheckedAccount<'info>,
+
+    /// CHECK: mining PDA will be checked in the rewards contract
+    /// PDA(["mining", mining owner <aka voter_authority in our case>, reward_pool],
+    /// reward_program)
+    #[account(mut)]
+    pub deposit_mining: UncheckedAccount<'info>,
+
+    /// CHECK: Rewards Program account
+    #[account(executable)]
+    pub rewards_program: UncheckedAccount<'info>,
+}
+
+/// Locks an expired auto-renewing deposit up again for the same period.
+///
+/// Permissionless crank: anyone may call it once the lockup of a deposit that has
+/// `auto_renew` set has expired. The new lockup starts now.
+pub fn renew_stake(ctx: Context<RenewStake>, deposit_entry_index: u8) -> Result<()> {
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+
+    require!(
+        registrar.reward_pool == ctx.accounts.reward_pool.key(),
+        MplStakingError::InvalidRewardPool
+    );
+
+    let curr_ts = clock_unix_timestamp();
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    require!(
+        !voter.is_batch_minting_restricted(),
+        MplStakingError::BatchMintingRestricted
+    );
+
+    let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
+    require!(
+        deposit_entry.auto_renew,
+        MplStakingError::AutoRenewNotEnabled
+    );
+    require!(
+        deposit_entry.lockup.kind == LockupKind::Constant,
+        MplStakingError::InvalidLockupKind
+    );
+    require!(
+        !deposit_entry.lockup.cooldown_requested,
+        MplStakingError::UnlockAlreadyRequested
+    );
+    require!(
+        deposit_entry.lockup.expired(curr_ts),
+        MplStakingError::DepositStillLocked
+    );
+    require_eq!(
+        deposit_entry.delegate,
+        ctx.accounts.delegate.key(),
+        MplStakingError::InvalidDelegate
+    );
+
+    // the lockup has expired, so granted tokens belong to the voter now
+    deposit_entry.allow_clawback = false;
+
+    let start_ts = deposit_entry.lockup.start_ts;
+    let lockup_period = deposit_entry.lockup.period;
+    deposit_entry.lockup.start_ts = curr_ts;
+    deposit_entry.lockup.end_ts = curr_ts
+        .checked_add(lockup_period.to_secs())
+        .ok_or(MplStakingError::InvalidTimestampArguments)?;
+
+    let reward_pool = ctx.accounts.reward_pool.to_account_info();
+    let mining = ctx.accounts.deposit_mining.to_account_info();
+    let deposit_authority = ctx.accounts.registrar.to_account_info();
+    let delegate_mining = ctx.accounts.delegate_mining.to_account_info();
+    let signers_seeds = registrar_seeds!(&registrar);
+    let mining_owner = &ctx.accounts.voter_authority.key();
+    let delegate_wallet_addr = &ctx.accounts.delegate.key();
+
+    // an expired lockup is accounted as Flex by the rewards program
+    cpi_instructions::extend_stake(
+        ctx.accounts.rewards_program.to_account_info(),
+        reward_pool,
+        mining,
+        deposit_authority,
+        delegate_mining,
+        LockupPeriod::Flex,
+        lockup_period,
+        start_ts,
+        deposit_entry.amount_deposited_native,
+        0,
+        mining_owner,
+        signers_seeds,
+        delegate_wallet_addr,
+    )?;
+
+    emit!(StakeRenewed {
+        voter: ctx.accounts.voter.key(),
+        deposit_entry_index,
+        mint: registrar
+            .voting_mint(deposit_entry.voting_mint_config_idx as usize)?
+            .mint,
+        amount: deposit_entry.amount_deposited_native,
+        lockup_period,
+        lockup_start_ts: deposit_entry.lockup.start_ts,
+        lockup_end_ts: deposit_entry.lockup.end_ts,
+        delegate: deposit_entry.delegate,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::{
+    clock_unix_timestamp, cpi_instructions, delegated_stake::release_delegated_stake,
+    events::DelegateChanged,
+};
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    registrar_seeds,
+    state::{Registrar, RegistrarLoader, Voter, VoterLoader},
+};
+
+#[derive(Accounts)]
+pub struct RevokeStaleDelegation<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+    mut,
+    seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+    bump = voter.load()?.voter_bump,
+    has_one = voter_authority,
+    has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+    /// CHECK: owner of the deposit mining, doesn't need to sign
+    pub voter_authority: UncheckedAccount<'info>,
+
+    #[account(has_one = registrar)]
+    pub delegate_voter: AccountLoader<'info, Voter>,
+
+    /// CHECK: Mining Account that belongs to Rewards Program and the current delegate
+    /// The address of the mining account on the rewards program
+    /// derived from PDA(["mining", delegate wallet addr, reward_pool], rewards_program)
+    /// Seeds derivation will be checked on the rewards contract
+    #[account(mut)]
+    pub old_delegate_mining: UncheckedAccount<'info>,
+
+    /// CHECK:
+    /// Ownership of the account will be checked in the rewards contract
+    /// It's the core account for the rewards contract, which will
+    /// keep track of all rewards and staking logic.
+    #[account(mut)]
+    pub reward_pool: UncheckedAccount<'info>,
+
+    /// CHECK: mining PDA will be checked in the rewards contract
+    /// PDA(["mining", mining owner <aka voter_authority in our case>, reward_pool],
+    /// reward_program)
+    #[account(mut)]
+    pub deposit_mining: UncheckedAccount<'info>,
+
+    /// CHECK: Rewards Program account
+    #[account(executable)]
+    pub rewards_program: UncheckedAccount<'info>,
+}
+
+/// Moves a deposit back to its owner once its delegate no longer has the min own
+/// weighted stake required by the registrar, e.g. after unlocking or being slashed.
+///
+/// Anyone may call it, and it doesn't wait for the delegate update threshold. The
+/// owner may choose another delegate right away. The `DelegateProfile`, the
+/// `DelegatedStake` and the token owner record of the delegate are passed as
+/// remaining accounts, see `release_delegated_stake`.
+pub fn revoke_stale_delegation(
+    ctx: Context<RevokeStaleDelegation>,
+    deposit_entry_index: u8,
+) -> Result<()> {
+    let registrar = &ctx.accounts.registrar.load_registrar()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    let voter_authority = voter.voter_authority;
+    let target = voter.active_deposit_mut(deposit_entry_index)?;
+    let curr_ts = clock_unix_timestamp();
+
+    require!(
+        registrar.reward_pool == ctx.accounts.reward_pool.key(),
+        MplStakingError::InvalidRewardPool
+    );
+    require!(
+        !target.lockup.kind.is_vesting(),
+        MplStakingError::InvalidLockupKind
+    );
+    require!(
+        target.delegate != voter_authority,
+        MplStakingError::SameDelegate
+    );
+
+    let delegate_voter = &ctx.accounts.delegate_voter.load_voter()?;
+    require!(
+        delegate_voter.voter_authority == target.delegate,
+        MplStakingError::InvalidDelegate
+    );
+    let delegate_voter_weighted_stake = delegate_voter
+        .deposits()
+        .fold(0, |acc, d| acc + d.weighted_stake(curr_ts));
+    require!(
+        delegate_voter_weighted_stake < registrar.params.min_own_weighted_stake(),
+        MplStakingError::DelegateStillQualified
+    );
+
+    let old_delegate = target.delegate;
+    release_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        registrar,
+        target,
+        ctx.remaining_accounts,
+    )?;
+    target.delegate = voter_authority;
+
+    let reward_pool = ctx.accounts.reward_pool.to_account_info();
+    let mining = ctx.accounts.deposit_mining.to_account_info();
+    let deposit_authority = ctx.accounts.registrar.to_account_info();
+    let old_delegate_mining = ctx.accounts.old_delegate_mining.to_account_info();
+    let signers_seeds = registrar_seeds!(registrar);
+    let staked_amount = target.amount_deposited_native;
+    let mining_owner = ctx.accounts.voter_authority.to_account_info();
+
+    // the deposit mining is the mining of the owner delegating to itself
+    cpi_instructions::change_delegate(
+        ctx.accounts.rewards_program.to_account_info(),
+        reward_pool,
+        mining.clone(),
+        deposit_authority,
+        mining_owner,
+        old_delegate_mining,
+        mining,
+        voter_authority,
+        staked_amount,
+        false,
+        signers_seeds,
+    )?;
+
+    emit!(DelegateChanged {
+        voter: ctx.accounts.voter.key(),
+        deposit_entry_index,
+        mint: registrar
+            .voting_mint(target.voting_mint_config_idx as usize)?
+            .mint,
+        amount: staked_amount,
+        lockup_period: target.lockup.period,
+        old_delegate,
+        new_delegate: voter_authority,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::events::AutoRenewChanged;
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, Voter, VoterLoader},
+};
+
+#[derive(Accounts)]
+pub struct SetAutoRenew<'info> {
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [voter.load()?.registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+}
+
+/// Enables or disables the auto-renewal of a Constant deposit.
+///
+/// Once the lockup of an auto-renewing deposit expires, anyone may call `renew_stake`
+/// to lock it up again for the same period.
+pub fn set_auto_renew(
+    ctx: Context<SetAutoRenew>,
+    deposit_entry_index: u8,
+    auto_renew: bool,
+) -> Result<()> {
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
+    require!(
+        deposit_entry.lockup.kind == LockupKind::Constant,
+        MplStakingError::InvalidLockupKind
+    );
+
+    deposit_entry.auto_renew = auto_renew;
+
+    emit!(AutoRenewChanged {
+        voter: ctx.accounts.voter.key(),
+        deposit_entry_index,
+        auto_renew,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{Registrar, VotingPowerMode},
+};
+
+#[derive(Accounts)]
+pub struct SetDelegateVoting<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Chooses whether staked deposits vote with their delegate instead of their owner.
+///
+/// Only applies to deposits once they're changed the next time, e.g. by
+/// `change_delegate`. Until then, deposits that vote with their delegate keep doing
+/// so even after delegate voting is turned off, and all others keep voting with
+/// their owner, so no vote weight is lost or counted twice. See `DelegatedStake`.
+///
+/// Delegated deposits vote with their deposited amount, without the multiplier of
+/// their lockup period, so delegate voting requires the `Flat` voting power mode.
+/// Otherwise the max vote weight would count them for more than they can vote with.
+pub fn set_delegate_voting(ctx: Context<SetDelegateVoting>, delegate_voting: bool) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    require!(
+        !delegate_voting || registrar.voting_power_mode == VotingPowerMode::Flat,
+        MplStakingError::DelegateVotingRequiresFlatVotingPower
+    );
+    registrar.delegate_voting = delegate_voting;
+
+    Ok(())
+}
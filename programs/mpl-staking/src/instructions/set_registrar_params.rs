@@ -0,0 +1,41 @@
+use crate::events::RegistrarParamsChanged;
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{Registrar, RegistrarParams};
+
+#[derive(Accounts)]
+pub struct SetRegistrarParams<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Changes the parameters of the registrar.
+///
+/// * `cooldown_secs`: seconds between requesting an unlock and being able to withdraw.
+/// * `delegate_update_diff_threshold`: min seconds between two delegate changes of a deposit.
+/// * `min_own_weighted_stake`: min weighted stake a voter must own to be chosen as a delegate.
+///
+/// Zero restores the default of a parameter. A new cooldown only applies to unlocks
+/// requested afterwards.
+pub fn set_registrar_params(
+    ctx: Context<SetRegistrarParams>,
+    cooldown_secs: u64,
+    delegate_update_diff_threshold: u64,
+    min_own_weighted_stake: u64,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    registrar.params = RegistrarParams {
+        cooldown_secs,
+        delegate_update_diff_threshold,
+        min_own_weighted_stake,
+    };
+
+    emit!(RegistrarParamsChanged {
+        registrar: ctx.accounts.registrar.key(),
+        cooldown_secs,
+        delegate_update_diff_threshold,
+        min_own_weighted_stake,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{Registrar, VotingMultipliers};
+
+#[derive(Accounts)]
+pub struct SetVotingMultipliers<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Changes the voting power multipliers of the lockup periods.
+///
+/// * `multipliers`: multipliers of the Flex, ThreeMonths, SixMonths and OneYear periods.
+///
+/// Longer periods can't have a smaller multiplier. The new multipliers immediately
+/// apply to the voting power of all deposits.
+///
+/// The durations of the periods and the multipliers of the weighted stake are the
+/// ones of the rewards program and can't be changed.
+pub fn set_voting_multipliers(
+    ctx: Context<SetVotingMultipliers>,
+    multipliers: [u64; 4],
+) -> Result<()> {
+    let voting_multipliers = VotingMultipliers { multipliers };
+    voting_multipliers.validate()?;
+
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    registrar.voting_multipliers = voting_multipliers;
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{Registrar, VotingPowerMode},
+};
+
+#[derive(Accounts)]
+pub struct SetVotingPowerMode<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Changes the way deposits are turned into voting power.
+///
+/// * `voting_power_mode`: see `VotingPowerMode` for the available options.
+///
+/// The new mode affects the voter weight records and the max voter weight record
+/// once they are updated the next time. While delegate voting is enabled, only the
+/// `Flat` mode is allowed, see `set_delegate_voting`.
+pub fn set_voting_power_mode(
+    ctx: Context<SetVotingPowerMode>,
+    voting_power_mode: VotingPowerMode,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    require!(
+        !registrar.delegate_voting || voting_power_mode == VotingPowerMode::Flat,
+        MplStakingError::DelegateVotingRequiresFlatVotingPower
+    );
+    registrar.voting_power_mode = voting_power_mode;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use crate::events::DepositSplit;
+use anchor_lang::prelude::*;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, Registrar, RegistrarLoader, Voter, VoterLoader},
+};
+
+#[derive(Accounts)]
+pub struct SplitDeposit<'info> {
+    /// Only needs to be writable if the deposit entries of the mint are counted,
+    /// see `RegistrarLoader::open_deposit_entry`.
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+}
+
+/// Moves `amount` tokens of a staked deposit into the first free deposit entry.
+///
+/// The new deposit keeps the lockup and delegate of the original one, so the weighted
+/// stake in the rewards mining doesn't change. Afterwards each part can be unlocked or
+/// delegated on its own.
+pub fn split_deposit(
+    ctx: Context<SplitDeposit>,
+    deposit_entry_index: u8,
+    amount: u64,
+) -> Result<()> {
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    let deposit_entry = voter.active_deposit(deposit_entry_index)?;
+    require!(
+        deposit_entry.lockup.kind == LockupKind::Constant,
+        MplStakingError::InvalidLockupKind
+    );
+    require!(
+        !deposit_entry.lockup.cooldown_requested,
+        MplStakingError::UnlockAlreadyRequested
+    );
+
+    let voting_mint_config_idx = deposit_entry.voting_mint_config_idx as usize;
+
+    let new_deposit_entry_index = voter.split_deposit(deposit_entry_index, amount)?;
+    ctx.accounts
+        .registrar
+        .open_deposit_entry(voting_mint_config_idx)?;
+
+    emit!(DepositSplit {
+        voter: ctx.accounts.voter.key(),
+        deposit_entry_index,
+        new_deposit_entry_index,
+        amount,
+    });
+
+    Ok(())
+}
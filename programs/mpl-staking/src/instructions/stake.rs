@@ -1,6 +1,13 @@
-use crate::{clock_unix_timestamp, cpi_instructions, Stake};
+use crate::{
+    clock_unix_timestamp, cpi_instructions, delegated_stake::sync_delegated_stake, events::Staked,
+    Stake,
+};
 use anchor_lang::prelude::*;
-use mplx_staking_states::{error::MplStakingError, registrar_seeds, state::LockupKind};
+use mplx_staking_states::{
+    error::MplStakingError,
+    registrar_seeds,
+    state::{LockupKind, RegistrarLoader, VoterLoader},
+};
 
 /// Transfers unlocked tokens from the source deposit entry to the target deposit entry.
 ///
@@ -13,14 +20,18 @@ pub fn stake(
     target_deposit_entry_index: u8,
     amount: u64,
 ) -> Result<()> {
-    let registrar = ctx.accounts.registrar.load()?;
+    let registrar = ctx.accounts.registrar.load_registrar()?;
 
     require!(
         registrar.reward_pool == ctx.accounts.reward_pool.key(),
         MplStakingError::InvalidRewardPool
     );
 
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
+    require!(
+        !voter.is_batch_minting_restricted(),
+        MplStakingError::BatchMintingRestricted
+    );
 
     let source = voter.active_deposit_mut(source_deposit_entry_index)?;
     let source_mint_idx = source.voting_mint_config_idx;
@@ -31,7 +42,7 @@ pub fn stake(
 
     // Reduce source amounts
     require_gte!(
-        source.amount_unlocked(),
+        source.amount_unlocked(clock_unix_timestamp())?,
         amount,
         MplStakingError::InsufficientUnlockedTokens
     );
@@ -48,6 +59,11 @@ pub fn stake(
         MplStakingError::InvalidMint
     );
 
+    require!(
+        target.lockup.kind == LockupKind::Constant,
+        MplStakingError::LockingIsForbidded
+    );
+
     // Checks that target doesn't have any stored tokens yet
     require!(
         target.amount_deposited_native == 0,
@@ -66,6 +82,13 @@ pub fn stake(
         .start_ts
         .checked_add(target.lockup.period.to_secs())
         .ok_or(MplStakingError::InvalidTimestampArguments)?;
+    sync_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &registrar,
+        &ctx.accounts.voter_authority.key(),
+        target,
+        ctx.remaining_accounts,
+    )?;
 
     let reward_pool = ctx.accounts.reward_pool.to_account_info();
     let mining = ctx.accounts.deposit_mining.to_account_info();
@@ -88,5 +111,17 @@ pub fn stake(
         delegate_wallet_addr,
     )?;
 
+    emit!(Staked {
+        voter: ctx.accounts.voter.key(),
+        source_deposit_entry_index,
+        target_deposit_entry_index,
+        mint: registrar.voting_mint(source_mint_idx as usize)?.mint,
+        amount,
+        lockup_period: target.lockup.period,
+        lockup_start_ts: target.lockup.start_ts,
+        lockup_end_ts: target.lockup.end_ts,
+        delegate: target.delegate,
+    });
+
     Ok(())
 }
@@ -1,21 +1,40 @@
-use crate::{clock_unix_timestamp, cpi_instructions::withdraw_mining, Stake};
+use crate::{
+    clock_unix_timestamp, cpi_instructions::withdraw_mining, delegated_stake::sync_delegated_stake,
+    events::TokensUnlocked, Stake,
+};
 use anchor_lang::prelude::*;
-use mplx_staking_states::{error::MplStakingError, state::COOLDOWN_SECS};
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, RegistrarLoader, VoterLoader},
+};
 
-pub fn unlock_tokens(ctx: Context<Stake>, deposit_entry_index: u8) -> Result<()> {
-    let registrar = ctx.accounts.registrar.load()?;
+/// Requests to unlock `amount` tokens of an expired Constant deposit.
+///
+/// The tokens stop being staked right away and can be withdrawn once the cooldown has passed.
+/// If `amount` is less than the deposited amount, the deposit is split: the unlocked tokens
+/// move to a free deposit entry that is in cooldown, the rest stays staked.
+pub fn unlock_tokens(ctx: Context<Stake>, deposit_entry_index: u8, amount: u64) -> Result<()> {
+    let registrar = ctx.accounts.registrar.load_registrar()?;
 
     require!(
         registrar.reward_pool == ctx.accounts.reward_pool.key(),
         MplStakingError::InvalidRewardPool
     );
 
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let voter = &mut ctx.accounts.voter.load_voter_mut()?;
     let curr_ts = clock_unix_timestamp();
+    let cooldown_ends_at = curr_ts
+        .checked_add(registrar.params.cooldown_secs())
+        .ok_or(MplStakingError::InvalidTimestampArguments)?;
 
     let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
 
-    // Check whether unlock request is allowed
+    // Check whether unlock request is allowed,
+    // vesting deposits unlock on their own
+    require!(
+        deposit_entry.lockup.kind == LockupKind::Constant,
+        MplStakingError::InvalidLockupKind
+    );
     require!(
         !deposit_entry.lockup.cooldown_requested,
         MplStakingError::UnlockAlreadyRequested
@@ -25,12 +44,33 @@ pub fn unlock_tokens(ctx: Context<Stake>, deposit_entry_index: u8) -> Result<()>
         MplStakingError::DepositStillLocked
     );
 
+    require!(
+        amount > 0 && amount <= deposit_entry.amount_deposited_native,
+        MplStakingError::InvalidAmount
+    );
+
     ctx.accounts.verify_delegate(deposit_entry)?;
 
-    deposit_entry.lockup.cooldown_requested = true;
-    deposit_entry.lockup.cooldown_ends_at = curr_ts
-        .checked_add(COOLDOWN_SECS)
-        .ok_or(MplStakingError::InvalidTimestampArguments)?;
+    let cooldown_deposit_entry_index = if amount == deposit_entry.amount_deposited_native {
+        deposit_entry.lockup.cooldown_requested = true;
+        deposit_entry.lockup.cooldown_ends_at = cooldown_ends_at;
+        deposit_entry_index
+    } else {
+        let index = voter.split_deposit(deposit_entry_index, amount)?;
+        let unlocked_entry = voter.active_deposit_mut(index)?;
+        unlocked_entry.lockup.cooldown_requested = true;
+        unlocked_entry.lockup.cooldown_ends_at = cooldown_ends_at;
+        index
+    };
+    // tokens in cooldown don't vote with the delegate anymore
+    let deposit_entry = voter.active_deposit_mut(cooldown_deposit_entry_index)?;
+    sync_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &registrar,
+        &ctx.accounts.voter_authority.key(),
+        deposit_entry,
+        ctx.remaining_accounts,
+    )?;
 
     let rewards_program = ctx.accounts.rewards_program.to_account_info();
     let reward_pool = ctx.accounts.reward_pool.to_account_info();
@@ -52,11 +92,32 @@ pub fn unlock_tokens(ctx: Context<Stake>, deposit_entry_index: u8) -> Result<()>
         mining,
         deposit_authority,
         delegate_mining,
-        deposit_entry.amount_deposited_native,
+        amount,
         owner.key,
         signers_seeds,
         delegate_wallet_addr,
     )?;
 
+    emit!(TokensUnlocked {
+        voter: ctx.accounts.voter.key(),
+        deposit_entry_index,
+        cooldown_deposit_entry_index,
+        mint: registrar
+            .voting_mint(deposit_entry.voting_mint_config_idx as usize)?
+            .mint,
+        amount,
+        lockup_period: deposit_entry.lockup.period,
+        cooldown_ends_at: deposit_entry.lockup.cooldown_ends_at,
+        delegate: deposit_entry.delegate,
+    });
+
+    if cooldown_deposit_entry_index != deposit_entry_index {
+        // the registrar can only be borrowed mutably once it's no longer passed to CPIs
+        drop(registrar);
+        ctx.accounts
+            .registrar
+            .open_deposit_entry(deposit_entry.voting_mint_config_idx as usize)?;
+    }
+
     Ok(())
 }
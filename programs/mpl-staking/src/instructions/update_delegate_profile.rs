@@ -0,0 +1,44 @@
+use crate::events::DelegateProfileUpdated;
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{DelegateProfile, Registrar};
+
+#[derive(Accounts)]
+pub struct UpdateDelegateProfile<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"delegate-profile".as_ref(), delegate.key().as_ref()],
+        bump = delegate_profile.load()?.bump,
+        has_one = registrar,
+        has_one = delegate)]
+    pub delegate_profile: AccountLoader<'info, DelegateProfile>,
+    pub delegate: Signer<'info>,
+}
+
+/// Changes whether the delegate accepts new stake, how much of it and the URI of
+/// its metadata.
+///
+/// Stake that was already accepted stays with the delegate, even if the profile
+/// is deactivated or `max_accepted_stake` drops below `accepted_stake`.
+pub fn update_delegate_profile(
+    ctx: Context<UpdateDelegateProfile>,
+    active: bool,
+    max_accepted_stake: u64,
+    metadata_uri: String,
+) -> Result<()> {
+    let delegate_profile = &mut ctx.accounts.delegate_profile.load_mut()?;
+    delegate_profile.active = active;
+    delegate_profile.max_accepted_stake = max_accepted_stake;
+    delegate_profile.set_metadata_uri(&metadata_uri)?;
+
+    emit!(DelegateProfileUpdated {
+        registrar: delegate_profile.registrar,
+        delegate: delegate_profile.delegate,
+        active,
+        max_accepted_stake,
+        metadata_uri,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::voter::MaxVoterWeightRecord;
+use anchor_lang::prelude::*;
+use mplx_staking_states::state::{Registrar, RegistrarLoader};
+
+// Remaining accounts must be all the token mints that have registered
+// as voting mints.
+#[derive(Accounts)]
+pub struct UpdateMaxVoterWeightRecord<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"max-voter-weight-record".as_ref()],
+        bump = registrar.load()?.max_voter_weight_record_bump,
+        constraint = max_voter_weight_record.realm == registrar.load()?.realm,
+        constraint = max_voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub max_voter_weight_record: Account<'info, MaxVoterWeightRecord>,
+}
+
+/// Calculates the max vote weight of the registrar and writes it into a
+/// `MaxVoterWeightRecord` account to be used by the SPL governance program.
+///
+/// The max vote weight is the vote weight a voter would have if they had
+/// deposited the whole supply of every voting mint, see
+/// `DynamicRegistrar::max_vote_weight_from_supplies`.
+///
+/// Like update_voter_weight_record, this must be called in the same
+/// transaction as the governance instruction that needs it.
+pub fn update_max_voter_weight_record(ctx: Context<UpdateMaxVoterWeightRecord>) -> Result<()> {
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+    let record = &mut ctx.accounts.max_voter_weight_record;
+    record.max_voter_weight = registrar.max_vote_weight(ctx.remaining_accounts)?;
+    record.max_voter_weight_expiry = Some(Clock::get()?.slot);
+
+    Ok(())
+}
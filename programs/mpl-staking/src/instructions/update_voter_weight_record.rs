@@ -1,6 +1,11 @@
-use crate::voter::VoterWeightRecord;
+use crate::{
+    clock_unix_timestamp, delegated_stake::find_delegated_stake, voter::VoterWeightRecord,
+};
 use anchor_lang::prelude::*;
-use mplx_staking_states::state::{Registrar, Voter};
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{Registrar, RegistrarLoader, Voter, VoterLoader},
+};
 
 #[derive(Accounts)]
 pub struct UpdateVoterWeightRecord<'info> {
@@ -33,10 +38,26 @@ pub struct UpdateVoterWeightRecord<'info> {
 ///
 /// This "revise" instruction must be called immediately before voting, in
 /// the same transaction.
+///
+/// The voter's `DelegatedStake` can be passed as remaining account to add the
+/// tokens that vote with the voter as their delegate. They keep doing so after
+/// delegate voting is turned off, until their deposits are changed again.
 pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
-    let voter = ctx.accounts.voter.load()?;
+    let registrar = ctx.accounts.registrar.load_registrar()?;
+    let voter = ctx.accounts.voter.load_voter()?;
+    let mut voter_weight = voter.weight(&registrar, clock_unix_timestamp())?;
+    if let Some(delegated_stake) = find_delegated_stake(
+        &ctx.accounts.registrar.key(),
+        &voter.voter_authority,
+        ctx.remaining_accounts,
+    )? {
+        voter_weight = voter_weight
+            .checked_add(delegated_stake.load()?.weight(&registrar)?)
+            .ok_or(MplStakingError::VoterWeightOverflow)?;
+    }
+
     let record = &mut ctx.accounts.voter_weight_record;
-    record.voter_weight = voter.weight()?;
+    record.voter_weight = voter_weight;
     record.voter_weight_expiry = Some(Clock::get()?.slot);
 
     Ok(())
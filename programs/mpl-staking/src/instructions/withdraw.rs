@@ -1,5 +1,6 @@
 use crate::{
     clock_unix_timestamp,
+    events::Withdrawn,
     voter::{load_token_owner_record, VoterWeightRecord},
 };
 use anchor_lang::prelude::*;
@@ -7,13 +8,17 @@ use anchor_spl::token::{self, Token, TokenAccount};
 use mpl_common_constants::constants::GOVERNANCE_PROGRAM_ID;
 use mplx_staking_states::{
     error::MplStakingError,
-    state::{DepositEntry, LockupKind, LockupPeriod, Registrar, Voter},
+    state::{
+        DepositEntry, LockupKind, LockupPeriod, Registrar, RegistrarLoader, Voter, VoterLoader,
+    },
     voter_seeds,
 };
 use spl_governance::state::realm::get_governing_token_holding_address_seeds;
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
+    /// Only needs to be writable if the deposit entry gets closed and the deposit
+    /// entries of its mint are counted, see `RegistrarLoader::close_deposit_entry`.
     pub registrar: AccountLoader<'info, Registrar>,
 
     // checking the PDA address it just an extra precaution,
@@ -84,13 +89,16 @@ impl<'info> Withdraw<'info> {
 
 /// Withdraws tokens from a deposit entry, if they are unlocked
 ///
+/// Constant deposits must have been unlocked and cooled down first. Vesting
+/// deposits don't need to be unlocked, their vested tokens can be withdrawn.
+///
 /// `deposit_entry_index`: The deposit entry to withdraw from.
 /// `amount` is in units of the native currency being withdrawn.
 pub fn withdraw(ctx: Context<Withdraw>, deposit_entry_index: u8, amount: u64) -> Result<()> {
     let slashing_penalty = {
         // Load the accounts.
-        let registrar = &ctx.accounts.registrar.load()?;
-        let voter = &mut ctx.accounts.voter.load_mut()?;
+        let registrar = ctx.accounts.registrar.load_registrar()?;
+        let voter = &mut ctx.accounts.voter.load_voter_mut()?;
 
         require!(
             !voter.is_tokenflow_restricted(),
@@ -105,7 +113,7 @@ pub fn withdraw(ctx: Context<Withdraw>, deposit_entry_index: u8, amount: u64) ->
         let token_owner_record = load_token_owner_record(
             &voter.voter_authority,
             &ctx.accounts.token_owner_record.to_account_info(),
-            registrar,
+            &registrar,
         )?;
         token_owner_record.assert_can_withdraw_governing_tokens()?;
 
@@ -127,7 +135,7 @@ pub fn withdraw(ctx: Context<Withdraw>, deposit_entry_index: u8, amount: u64) ->
         }
 
         require_gte!(
-            deposit_entry.amount_unlocked(),
+            deposit_entry.amount_unlocked(curr_ts)?,
             amount,
             MplStakingError::InsufficientUnlockedTokens
         );
@@ -153,34 +161,42 @@ pub fn withdraw(ctx: Context<Withdraw>, deposit_entry_index: u8, amount: u64) ->
             .checked_sub(slashing_penalty)
             .ok_or(MplStakingError::ArithmeticOverflow)?;
 
-        msg!(
-            "Withdrew amount {} at deposit index {} with lockup kind {:?} and {} seconds left",
-            amount,
+        emit!(Withdrawn {
+            voter: ctx.accounts.voter.key(),
             deposit_entry_index,
-            deposit_entry.lockup.kind,
-            deposit_entry.lockup.seconds_left(curr_ts),
-        );
-
-        if deposit_entry.amount_deposited_native == 0
-            && deposit_entry.lockup.kind != LockupKind::None
+            mint: ctx.accounts.destination.mint,
+            amount,
+            slashing_penalty,
+            amount_deposited_native: deposit_entry.amount_deposited_native,
+            lockup_kind: deposit_entry.lockup.kind,
+            lockup_period: deposit_entry.lockup.period,
+        });
+
+        let close_deposit_entry = deposit_entry.amount_deposited_native == 0
+            && deposit_entry.lockup.kind == LockupKind::Constant
             && deposit_entry.lockup.period != LockupPeriod::None
-            && deposit_entry.slashing_penalty == 0
-        {
+            && deposit_entry.slashing_penalty == 0;
+        if close_deposit_entry {
             *deposit_entry = DepositEntry::default();
             deposit_entry.is_used = false;
         }
 
         // Update the voter weight record
         let record = &mut ctx.accounts.voter_weight_record;
-        record.voter_weight = voter.weight()?;
+        record.voter_weight = voter.weight(&registrar, curr_ts)?;
         record.voter_weight_expiry = Some(Clock::get()?.slot);
 
+        if close_deposit_entry {
+            drop(registrar);
+            ctx.accounts.registrar.close_deposit_entry(mint_idx)?;
+        }
+
         slashing_penalty
     };
 
     // Transfer the tokens
     {
-        let voter = ctx.accounts.voter.load()?;
+        let voter = ctx.accounts.voter.load_voter()?;
         let destination = ctx.accounts.destination.to_account_info();
         let voter_seeds = voter_seeds!(voter);
         token::transfer(
@@ -4,11 +4,12 @@ use mplx_staking_states::{
     error::MplStakingError,
     state::{
         lockup::{LockupKind, LockupPeriod},
-        DepositEntry, Registrar, Voter,
+        DepositEntry, Registrar, Voter, VotingPowerMode,
     },
 };
 
 pub mod cpi_instructions;
+mod delegated_stake;
 pub mod events;
 mod governance;
 mod instructions;
@@ -58,6 +59,10 @@ declare_id!("9XZ7Ku7FYGVk3veKba6BRKTFXoYJyh4b4ZHC6MfaTUE8");
 /// weight of an individual voter, one can convert B into A via the given
 /// exchange rate, which must be fixed.
 ///
+/// With a voting power mode that rewards lockups, every supply is also scaled
+/// by the largest lockup multiplier, so no voter can exceed the max vote weight.
+/// `get_max_vote_weight` returns it as return data.
+///
 /// Note that the above also implies that the `max_vote_weight` must fit into
 /// a u64.
 #[program]
@@ -77,8 +82,71 @@ pub mod mpl_staking {
         ctx: Context<ConfigureVotingMint>,
         idx: u16,
         grant_authority: Option<Pubkey>,
+        digit_shift: i8,
+        baseline_vote_weight_scaled_factor: u64,
+    ) -> Result<()> {
+        instructions::configure_voting_mint(
+            ctx,
+            idx,
+            grant_authority,
+            digit_shift,
+            baseline_vote_weight_scaled_factor,
+        )
+    }
+
+    pub fn grow_registrar(ctx: Context<GrowRegistrar>, additional_voting_mints: u8) -> Result<()> {
+        instructions::grow_registrar(ctx, additional_voting_mints)
+    }
+
+    pub fn migrate_registrar(ctx: Context<MigrateRegistrar>) -> Result<()> {
+        instructions::migrate_registrar(ctx)
+    }
+
+    pub fn remove_voting_mint(ctx: Context<RemoveVotingMint>, idx: u16) -> Result<()> {
+        instructions::remove_voting_mint(ctx, idx)
+    }
+
+    pub fn recount_voting_mint_deposits(
+        ctx: Context<RecountVotingMintDeposits>,
+        idx: u16,
+        open_deposit_entries: u64,
+    ) -> Result<()> {
+        instructions::recount_voting_mint_deposits(ctx, idx, open_deposit_entries)
+    }
+
+    pub fn set_voting_power_mode(
+        ctx: Context<SetVotingPowerMode>,
+        voting_power_mode: VotingPowerMode,
+    ) -> Result<()> {
+        instructions::set_voting_power_mode(ctx, voting_power_mode)
+    }
+
+    pub fn set_delegate_voting(
+        ctx: Context<SetDelegateVoting>,
+        delegate_voting: bool,
+    ) -> Result<()> {
+        instructions::set_delegate_voting(ctx, delegate_voting)
+    }
+
+    pub fn set_voting_multipliers(
+        ctx: Context<SetVotingMultipliers>,
+        multipliers: [u64; 4],
+    ) -> Result<()> {
+        instructions::set_voting_multipliers(ctx, multipliers)
+    }
+
+    pub fn set_registrar_params(
+        ctx: Context<SetRegistrarParams>,
+        cooldown_secs: u64,
+        delegate_update_diff_threshold: u64,
+        min_own_weighted_stake: u64,
     ) -> Result<()> {
-        instructions::configure_voting_mint(ctx, idx, grant_authority)
+        instructions::set_registrar_params(
+            ctx,
+            cooldown_secs,
+            delegate_update_diff_threshold,
+            min_own_weighted_stake,
+        )
     }
 
     pub fn create_voter(
@@ -89,6 +157,27 @@ pub mod mpl_staking {
         instructions::create_voter(ctx, voter_bump, voter_weight_record_bump)
     }
 
+    pub fn create_delegated_stake(ctx: Context<CreateDelegatedStake>) -> Result<()> {
+        instructions::create_delegated_stake(ctx)
+    }
+
+    pub fn create_delegate_profile(
+        ctx: Context<CreateDelegateProfile>,
+        max_accepted_stake: u64,
+        metadata_uri: String,
+    ) -> Result<()> {
+        instructions::create_delegate_profile(ctx, max_accepted_stake, metadata_uri)
+    }
+
+    pub fn update_delegate_profile(
+        ctx: Context<UpdateDelegateProfile>,
+        active: bool,
+        max_accepted_stake: u64,
+        metadata_uri: String,
+    ) -> Result<()> {
+        instructions::update_delegate_profile(ctx, active, max_accepted_stake, metadata_uri)
+    }
+
     pub fn create_deposit_entry(
         ctx: Context<CreateDepositEntry>,
         deposit_entry_index: u8,
@@ -117,8 +206,63 @@ pub mod mpl_staking {
         instructions::update_voter_weight_record(ctx)
     }
 
-    pub fn unlock_tokens(ctx: Context<Stake>, deposit_entry_index: u8) -> Result<()> {
-        instructions::unlock_tokens(ctx, deposit_entry_index)
+    pub fn create_max_voter_weight_record(
+        ctx: Context<CreateMaxVoterWeightRecord>,
+        max_voter_weight_record_bump: u8,
+    ) -> Result<()> {
+        instructions::create_max_voter_weight_record(ctx, max_voter_weight_record_bump)
+    }
+
+    pub fn update_max_voter_weight_record(ctx: Context<UpdateMaxVoterWeightRecord>) -> Result<()> {
+        instructions::update_max_voter_weight_record(ctx)
+    }
+
+    pub fn get_max_vote_weight(ctx: Context<GetMaxVoteWeight>) -> Result<u64> {
+        instructions::get_max_vote_weight(ctx)
+    }
+
+    pub fn unlock_tokens(ctx: Context<Stake>, deposit_entry_index: u8, amount: u64) -> Result<()> {
+        instructions::unlock_tokens(ctx, deposit_entry_index, amount)
+    }
+
+    pub fn cancel_unlock(
+        ctx: Context<Stake>,
+        deposit_entry_index: u8,
+        new_lockup_period: LockupPeriod,
+    ) -> Result<()> {
+        instructions::cancel_unlock(ctx, deposit_entry_index, new_lockup_period)
+    }
+
+    pub fn set_auto_renew(
+        ctx: Context<SetAutoRenew>,
+        deposit_entry_index: u8,
+        auto_renew: bool,
+    ) -> Result<()> {
+        instructions::set_auto_renew(ctx, deposit_entry_index, auto_renew)
+    }
+
+    pub fn renew_stake(ctx: Context<RenewStake>, deposit_entry_index: u8) -> Result<()> {
+        instructions::renew_stake(ctx, deposit_entry_index)
+    }
+
+    pub fn merge_deposits(
+        ctx: Context<Stake>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+    ) -> Result<()> {
+        instructions::merge_deposits(ctx, source_deposit_entry_index, target_deposit_entry_index)
+    }
+
+    pub fn split_deposit(
+        ctx: Context<SplitDeposit>,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::split_deposit(ctx, deposit_entry_index, amount)
+    }
+
+    pub fn grow_voter(ctx: Context<GrowVoter>, additional_deposit_entries: u8) -> Result<()> {
+        instructions::grow_voter(ctx, additional_deposit_entries)
     }
 
     pub fn close_voter<'info>(ctx: Context<'_, '_, '_, 'info, CloseVoter<'info>>) -> Result<()> {
@@ -130,7 +274,21 @@ pub mod mpl_staking {
         deposit_entry_begin: u8,
         deposit_entry_count: u8,
     ) -> Result<()> {
-        instructions::log_voter_info(ctx, deposit_entry_begin, deposit_entry_count)
+        instructions::log_voter_info(ctx, deposit_entry_begin, deposit_entry_count, None)
+    }
+
+    pub fn log_voter_info_at(
+        ctx: Context<LogVoterInfo>,
+        deposit_entry_begin: u8,
+        deposit_entry_count: u8,
+        guaranteed_at_ts: u64,
+    ) -> Result<()> {
+        instructions::log_voter_info(
+            ctx,
+            deposit_entry_begin,
+            deposit_entry_count,
+            Some(guaranteed_at_ts),
+        )
     }
 
     pub fn stake(
@@ -181,16 +339,57 @@ pub mod mpl_staking {
         instructions::change_delegate(ctx, deposit_entry_index)
     }
 
-    pub fn restrict_tokenflow(ctx: Context<Penalty>) -> Result<()> {
-        instructions::restrict_tokenflow(ctx)
+    pub fn change_delegate_batch(
+        ctx: Context<ChangeDelegate>,
+        deposit_entry_indices: Vec<u8>,
+    ) -> Result<()> {
+        instructions::change_delegate_batch(ctx, deposit_entry_indices)
+    }
+
+    pub fn revoke_stale_delegation(
+        ctx: Context<RevokeStaleDelegation>,
+        deposit_entry_index: u8,
+    ) -> Result<()> {
+        instructions::revoke_stale_delegation(ctx, deposit_entry_index)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn grant(
+        ctx: Context<Grant>,
+        voter_bump: u8,
+        voter_weight_record_bump: u8,
+        kind: LockupKind,
+        period: LockupPeriod,
+        periods: u32,
+        allow_clawback: bool,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::grant(
+            ctx,
+            voter_bump,
+            voter_weight_record_bump,
+            kind,
+            period,
+            periods,
+            allow_clawback,
+            amount,
+        )
+    }
+
+    pub fn clawback(ctx: Context<Clawback>, deposit_entry_index: u8) -> Result<()> {
+        instructions::clawback(ctx, deposit_entry_index)
+    }
+
+    pub fn restrict_tokenflow(ctx: Context<Penalty>, reason: u8) -> Result<()> {
+        instructions::restrict_tokenflow(ctx, reason)
     }
 
-    pub fn allow_tokenflow(ctx: Context<Penalty>) -> Result<()> {
-        instructions::allow_tokenflow(ctx)
+    pub fn allow_tokenflow(ctx: Context<Penalty>, reason: u8) -> Result<()> {
+        instructions::allow_tokenflow(ctx, reason)
     }
 
-    pub fn restrict_batch_minting(ctx: Context<Penalty>, until_ts: u64) -> Result<()> {
-        instructions::restrict_batch_minting(ctx, until_ts)
+    pub fn restrict_batch_minting(ctx: Context<Penalty>, until_ts: u64, reason: u8) -> Result<()> {
+        instructions::restrict_batch_minting(ctx, until_ts, reason)
     }
 
     pub fn slash(
@@ -198,20 +397,24 @@ pub mod mpl_staking {
         deposit_entry_index: u8,
         amount: u64,
         mining_owner: Pubkey,
+        reason: u8,
     ) -> Result<()> {
-        instructions::slash(ctx, deposit_entry_index, amount, mining_owner)
+        instructions::slash(ctx, deposit_entry_index, amount, mining_owner, reason)
     }
 
     pub fn decrease_rewards(
         ctx: Context<DecreaseRewards>,
         decreased_weighted_stake_number: u64,
+        reason: u8,
     ) -> Result<()> {
-        instructions::decrease_rewards(ctx, decreased_weighted_stake_number)
+        instructions::decrease_rewards(ctx, decreased_weighted_stake_number, reason)
     }
 }
 
 #[derive(Accounts)]
 pub struct Stake<'info> {
+    /// Only needs to be writable if a deposit entry is opened or closed and the
+    /// deposit entries of its mint are counted, see `RegistrarLoader::open_deposit_entry`.
     pub registrar: AccountLoader<'info, Registrar>,
 
     // checking the PDA address it just an extra precaution,
@@ -1,4 +1,4 @@
-use crate::vote_weight_record;
+use crate::{max_voter_weight_record, vote_weight_record};
 use anchor_lang::prelude::*;
 use mplx_staking_states::{error::MplStakingError, state::Registrar};
 use spl_governance::state::token_owner_record;
@@ -26,3 +26,8 @@ pub fn load_token_owner_record(
 // VoteWeightRecords are unique in that they are defined by the SPL governance
 // program, but they are actually owned by this program.
 vote_weight_record!(crate::ID);
+
+// Generate a MaxVoterWeightRecord Anchor wrapper, owned by the current program.
+// Like VoteWeightRecords, MaxVoterWeightRecords are defined by the SPL governance
+// program, but owned by this program.
+max_voter_weight_record!(crate::ID);
@@ -152,6 +152,7 @@ async fn decrease_rewards_poc() -> Result<(), TransportError> {
             decreased_weighted_stake_number,
             &voter,
             &context.rewards.program_id,
+            0,
         )
         .await?;
 
@@ -306,6 +307,7 @@ async fn penalty_is_too_severe() -> Result<(), TransportError> {
             decreased_weighted_stake_number,
             &voter,
             &context.rewards.program_id,
+            0,
         )
         .await
         .expect_err("Penalty is too big");
@@ -1,13 +1,15 @@
 use crate::*;
 use anchor_lang::InstructionData;
-use mplx_staking_states::state::{DepositEntry, Voter};
+use mplx_staking_states::state::{
+    DepositEntry, Registrar, Voter, VoterExtension, VotingMint, VotingPowerMode,
+};
 use solana_sdk::{
     instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     system_instruction::create_account,
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, mem::size_of, rc::Rc};
 
 pub const WRAPPED_POOL_SIZE: usize = 64480;
 
@@ -26,6 +28,7 @@ pub struct RegistrarCookie {
     pub registrar_bump: u8,
     pub realm_pubkey: Pubkey,
     pub realm_governing_token_mint_pubkey: Pubkey,
+    pub governance_program_id: Pubkey,
 }
 
 #[derive(Clone)]
@@ -69,7 +72,74 @@ impl AddinCookie {
             &registrar.reward_pool,
         );
 
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::ChangeDelegate {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter.authority.pubkey(),
+                delegate_voter: delegate_voter.address,
+                old_delegate_mining: *old_delegate_mining,
+                new_delegate_mining,
+                reward_pool: registrar.reward_pool,
+                deposit_mining,
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+        // only used if the deposit votes with its delegate
+        let old_delegate = voter
+            .get_deposit_entry(&self.solana, deposit_entry_index)
+            .await
+            .delegate;
+        accounts.extend(self.delegate_metas(
+            registrar,
+            &[old_delegate, delegate_voter.authority.pubkey()],
+        ));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&voter.authority]))
+            .await
+    }
+
+    pub async fn change_delegate_batch(
+        &self,
+        // accounts
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegate_voter: &VoterCookie,
+        old_delegate_mining: &Pubkey,
+        rewards_program: &Pubkey,
+        // params
+        deposit_entry_indices: Vec<u8>,
+    ) -> std::result::Result<(), BanksClientError> {
+        // all deposits share the delegate of the first one
+        let old_delegate = match deposit_entry_indices.first() {
+            Some(index) => voter.get_deposit_entry(&self.solana, *index).await.delegate,
+            None => voter.authority.pubkey(),
+        };
+        let data = InstructionData::data(&mpl_staking::instruction::ChangeDelegateBatch {
+            deposit_entry_indices,
+        });
+
+        let (deposit_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &voter.authority.pubkey(),
+            &registrar.reward_pool,
+        );
+
+        let (new_delegate_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &delegate_voter.authority.pubkey(),
+            &registrar.reward_pool,
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &mpl_staking::accounts::ChangeDelegate {
                 registrar: registrar.address,
                 voter: voter.address,
@@ -83,6 +153,10 @@ impl AddinCookie {
             },
             None,
         );
+        accounts.extend(self.delegate_metas(
+            registrar,
+            &[old_delegate, delegate_voter.authority.pubkey()],
+        ));
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -95,6 +169,109 @@ impl AddinCookie {
             .await
     }
 
+    pub async fn revoke_stale_delegation(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegate_voter: &VoterCookie,
+        rewards_program: &Pubkey,
+        deposit_entry_index: u8,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::RevokeStaleDelegation {
+            deposit_entry_index,
+        });
+
+        let (deposit_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &voter.authority.pubkey(),
+            &registrar.reward_pool,
+        );
+        let (old_delegate_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &delegate_voter.authority.pubkey(),
+            &registrar.reward_pool,
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::RevokeStaleDelegation {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter.authority.pubkey(),
+                delegate_voter: delegate_voter.address,
+                old_delegate_mining,
+                reward_pool: registrar.reward_pool,
+                deposit_mining,
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+        accounts.extend(self.delegate_metas(registrar, &[delegate_voter.authority.pubkey()]));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // permissionless, only the payer signs
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    /// Writable `DelegateProfile` and `DelegatedStake` accounts of the delegates, to be
+    /// passed as remaining accounts. They don't need to exist unless a deposit is staked
+    /// with the delegate.
+    pub fn delegate_metas(
+        &self,
+        registrar: &RegistrarCookie,
+        delegates: &[Pubkey],
+    ) -> Vec<anchor_lang::prelude::AccountMeta> {
+        delegates
+            .iter()
+            .flat_map(|delegate| {
+                let (delegate_profile, _) =
+                    find_delegate_profile_addr(&self.program_id, &registrar.address, delegate);
+                let (delegated_stake, _) =
+                    find_delegated_stake_addr(&self.program_id, &registrar.address, delegate);
+                [
+                    anchor_lang::prelude::AccountMeta::new(delegate_profile, false),
+                    anchor_lang::prelude::AccountMeta::new(delegated_stake, false),
+                    self.token_owner_record_meta(registrar, delegate),
+                ]
+            })
+            .collect()
+    }
+
+    /// Clients pass the registrar writable to instructions that may open or close
+    /// deposit entries only once it has been grown, since it counts them from then on.
+    async fn mark_registrar_writable_if_grown(
+        &self,
+        registrar: Pubkey,
+        accounts: &mut [anchor_lang::prelude::AccountMeta],
+    ) {
+        let grown = self.solana.get_account_data(registrar).await.len()
+            > Registrar::space(Registrar::INLINE_VOTING_MINTS);
+        for account in accounts.iter_mut().filter(|a| a.pubkey == registrar) {
+            account.is_writable = grown;
+        }
+    }
+
+    /// Token owner record of `owner`, which instructions need to check that moving vote
+    /// weight away from them doesn't affect outstanding votes.
+    pub fn token_owner_record_meta(
+        &self,
+        registrar: &RegistrarCookie,
+        owner: &Pubkey,
+    ) -> anchor_lang::prelude::AccountMeta {
+        let token_owner_record =
+            spl_governance::state::token_owner_record::get_token_owner_record_address(
+                &registrar.governance_program_id,
+                &registrar.realm_pubkey,
+                &registrar.realm_governing_token_mint_pubkey,
+                owner,
+            );
+        anchor_lang::prelude::AccountMeta::new_readonly(token_owner_record, false)
+    }
+
     pub async fn create_registrar(
         &self,
         realm: &GovernanceRealmCookie,
@@ -182,6 +359,7 @@ impl AddinCookie {
             realm_pubkey: realm.realm,
             reward_pool: reward_pool.pubkey(),
             realm_governing_token_mint_pubkey: community_token_mint,
+            governance_program_id: realm.governance.program_id,
         };
 
         (registrar_cookie, reward_pool.pubkey())
@@ -215,7 +393,7 @@ impl AddinCookie {
         let (delegate_mining, _) =
             find_deposit_mining_addr(rewards_program, &delegate, &registrar.reward_pool);
 
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &mpl_staking::accounts::Stake {
                 registrar: registrar.address,
                 voter: voter.address,
@@ -228,6 +406,10 @@ impl AddinCookie {
             },
             None,
         );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+        accounts.extend(self.delegate_metas(registrar, &[delegate]));
+        accounts.push(self.token_owner_record_meta(registrar, &voter.authority.pubkey()));
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -245,17 +427,47 @@ impl AddinCookie {
         &self,
         registrar: &RegistrarCookie,
         authority: &Keypair,
-        _payer: &Keypair,
+        payer: &Keypair,
         index: u16,
         mint: &MintCookie,
         grant_authority: Option<Pubkey>,
         other_mints: Option<&[Pubkey]>,
     ) -> VotingMintConfigCookie {
+        self.configure_voting_mint_with_rate(
+            registrar,
+            authority,
+            payer,
+            index,
+            mint,
+            grant_authority,
+            other_mints,
+            0,
+            VotingMint::SCALED_FACTOR_BASE,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn configure_voting_mint_with_rate(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        index: u16,
+        mint: &MintCookie,
+        grant_authority: Option<Pubkey>,
+        other_mints: Option<&[Pubkey]>,
+        digit_shift: i8,
+        baseline_vote_weight_scaled_factor: u64,
+    ) -> std::result::Result<VotingMintConfigCookie, BanksClientError> {
         let deposit_mint = mint.pubkey.unwrap();
 
         let data = InstructionData::data(&mpl_staking::instruction::ConfigureVotingMint {
             idx: index,
             grant_authority,
+            digit_shift,
+            baseline_vote_weight_scaled_factor,
         });
 
         let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
@@ -263,6 +475,8 @@ impl AddinCookie {
                 mint: deposit_mint,
                 registrar: registrar.address,
                 realm_authority: authority.pubkey(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
             },
             None,
         );
@@ -283,59 +497,29 @@ impl AddinCookie {
         }];
 
         self.solana
-            .process_transaction(&instructions, Some(&[authority]))
-            .await
-            .unwrap();
+            .process_transaction(&instructions, Some(&[authority, payer]))
+            .await?;
 
-        VotingMintConfigCookie { mint: mint.clone() }
+        Ok(VotingMintConfigCookie { mint: mint.clone() })
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn create_voter(
+    pub async fn grow_registrar(
         &self,
         registrar: &RegistrarCookie,
-        token_owner_record: &TokenOwnerRecordCookie,
-        authority: &Keypair,
+        realm_authority: &Keypair,
         payer: &Keypair,
-        reward_pool: &Pubkey,
-        deposit_mining: &Pubkey,
-        rewards_program: &Pubkey,
-    ) -> VoterCookie {
-        let (voter, voter_bump) = Pubkey::find_program_address(
-            &[
-                &registrar.address.to_bytes(),
-                b"voter".as_ref(),
-                &authority.pubkey().to_bytes(),
-            ],
-            &self.program_id,
-        );
-        let (voter_weight_record, voter_weight_record_bump) = Pubkey::find_program_address(
-            &[
-                &registrar.address.to_bytes(),
-                b"voter-weight-record".as_ref(),
-                &authority.pubkey().to_bytes(),
-            ],
-            &self.program_id,
-        );
-
-        let data = InstructionData::data(&mpl_staking::instruction::CreateVoter {
-            voter_bump,
-            voter_weight_record_bump,
+        additional_voting_mints: u8,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::GrowRegistrar {
+            additional_voting_mints,
         });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::CreateVoter {
-                voter,
-                voter_weight_record,
+            &mpl_staking::accounts::GrowRegistrar {
                 registrar: registrar.address,
-                voter_authority: authority.pubkey(),
+                realm_authority: realm_authority.pubkey(),
                 payer: payer.pubkey(),
                 system_program: solana_sdk::system_program::id(),
-                rent: solana_program::sysvar::rent::id(),
-                instructions: solana_program::sysvar::instructions::id(),
-                reward_pool: *reward_pool,
-                deposit_mining: *deposit_mining,
-                rewards_program: *rewards_program,
             },
             None,
         );
@@ -347,54 +531,1128 @@ impl AddinCookie {
         }];
 
         self.solana
-            .process_transaction(&instructions, Some(&[payer, authority]))
+            .process_transaction(&instructions, Some(&[payer, realm_authority]))
             .await
-            .unwrap();
+    }
 
-        let authority = Keypair::from_bytes(&authority.to_bytes()).unwrap();
+    pub async fn migrate_registrar(
+        &self,
+        registrar: &RegistrarCookie,
+        payer: &Keypair,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::MigrateRegistrar {});
 
-        VoterCookie {
-            address: voter,
-            authority,
-            voter_weight_record,
-            token_owner_record: token_owner_record.address,
-        }
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::MigrateRegistrar {
+                registrar: registrar.address,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[payer]))
+            .await
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn create_deposit_entry(
+    pub async fn remove_voting_mint(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-        delegate_voter: &VoterCookie,
-        voting_mint: &VotingMintConfigCookie,
-        deposit_entry_index: u8,
-        lockup_kind: LockupKind,
-        period: LockupPeriod,
+        realm_authority: &Keypair,
+        idx: u16,
     ) -> std::result::Result<(), BanksClientError> {
-        let vault = voter.vault_address(voting_mint);
+        let data = InstructionData::data(&mpl_staking::instruction::RemoveVotingMint { idx });
 
-        let data = InstructionData::data(&mpl_staking::instruction::CreateDepositEntry {
-            deposit_entry_index,
-            kind: lockup_kind,
-            period,
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::RemoveVotingMint {
+                registrar: registrar.address,
+                realm_authority: realm_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[realm_authority]))
+            .await
+    }
+
+    pub async fn recount_voting_mint_deposits(
+        &self,
+        registrar: &RegistrarCookie,
+        realm_authority: &Keypair,
+        idx: u16,
+        open_deposit_entries: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::RecountVotingMintDeposits {
+            idx,
+            open_deposit_entries,
         });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::CreateDepositEntry {
-                vault,
+            &mpl_staking::accounts::RecountVotingMintDeposits {
+                registrar: registrar.address,
+                realm_authority: realm_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[realm_authority]))
+            .await
+    }
+
+    pub async fn set_voting_multipliers(
+        &self,
+        registrar: &RegistrarCookie,
+        realm_authority: &Keypair,
+        multipliers: [u64; 4],
+    ) -> std::result::Result<(), BanksClientError> {
+        let data =
+            InstructionData::data(&mpl_staking::instruction::SetVotingMultipliers { multipliers });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::SetVotingMultipliers {
+                registrar: registrar.address,
+                realm_authority: realm_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[realm_authority]))
+            .await
+    }
+
+    pub async fn set_registrar_params(
+        &self,
+        registrar: &RegistrarCookie,
+        realm_authority: &Keypair,
+        cooldown_secs: u64,
+        delegate_update_diff_threshold: u64,
+        min_own_weighted_stake: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::SetRegistrarParams {
+            cooldown_secs,
+            delegate_update_diff_threshold,
+            min_own_weighted_stake,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::SetRegistrarParams {
+                registrar: registrar.address,
+                realm_authority: realm_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[realm_authority]))
+            .await
+    }
+
+    pub async fn set_voting_power_mode(
+        &self,
+        registrar: &RegistrarCookie,
+        realm_authority: &Keypair,
+        voting_power_mode: VotingPowerMode,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::SetVotingPowerMode {
+            voting_power_mode,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::SetVotingPowerMode {
+                registrar: registrar.address,
+                realm_authority: realm_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[realm_authority]))
+            .await
+    }
+
+    pub async fn set_delegate_voting(
+        &self,
+        registrar: &RegistrarCookie,
+        realm_authority: &Keypair,
+        delegate_voting: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data =
+            InstructionData::data(&mpl_staking::instruction::SetDelegateVoting { delegate_voting });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::SetDelegateVoting {
+                registrar: registrar.address,
+                realm_authority: realm_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[realm_authority]))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_voter(
+        &self,
+        registrar: &RegistrarCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        reward_pool: &Pubkey,
+        deposit_mining: &Pubkey,
+        rewards_program: &Pubkey,
+    ) -> VoterCookie {
+        let (voter, voter_bump) = Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"voter".as_ref(),
+                &authority.pubkey().to_bytes(),
+            ],
+            &self.program_id,
+        );
+        let (voter_weight_record, voter_weight_record_bump) = Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"voter-weight-record".as_ref(),
+                &authority.pubkey().to_bytes(),
+            ],
+            &self.program_id,
+        );
+
+        let data = InstructionData::data(&mpl_staking::instruction::CreateVoter {
+            voter_bump,
+            voter_weight_record_bump,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::CreateVoter {
+                voter,
+                voter_weight_record,
+                registrar: registrar.address,
+                voter_authority: authority.pubkey(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                rent: solana_program::sysvar::rent::id(),
+                instructions: solana_program::sysvar::instructions::id(),
+                reward_pool: *reward_pool,
+                deposit_mining: *deposit_mining,
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[payer, authority]))
+            .await
+            .unwrap();
+
+        let authority = Keypair::from_bytes(&authority.to_bytes()).unwrap();
+
+        VoterCookie {
+            address: voter,
+            authority,
+            voter_weight_record,
+            token_owner_record: token_owner_record.address,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_delegated_stake(
+        &self,
+        registrar: &RegistrarCookie,
+        delegate_voter: &VoterCookie,
+        payer: &Keypair,
+    ) -> std::result::Result<Pubkey, BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::CreateDelegatedStake {});
+
+        let (delegated_stake, _) = find_delegated_stake_addr(
+            &self.program_id,
+            &registrar.address,
+            &delegate_voter.authority.pubkey(),
+        );
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::CreateDelegatedStake {
+                registrar: registrar.address,
+                delegate_voter: delegate_voter.address,
+                delegated_stake,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[payer]))
+            .await?;
+
+        Ok(delegated_stake)
+    }
+
+    pub async fn create_delegate_profile(
+        &self,
+        registrar: &RegistrarCookie,
+        delegate_voter: &VoterCookie,
+        payer: &Keypair,
+        max_accepted_stake: u64,
+        metadata_uri: &str,
+    ) -> std::result::Result<Pubkey, BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::CreateDelegateProfile {
+            max_accepted_stake,
+            metadata_uri: metadata_uri.to_string(),
+        });
+
+        let (delegate_profile, _) = find_delegate_profile_addr(
+            &self.program_id,
+            &registrar.address,
+            &delegate_voter.authority.pubkey(),
+        );
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::CreateDelegateProfile {
+                registrar: registrar.address,
+                voter: delegate_voter.address,
+                voter_authority: delegate_voter.authority.pubkey(),
+                delegate_profile,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[payer, &delegate_voter.authority]))
+            .await?;
+
+        Ok(delegate_profile)
+    }
+
+    pub async fn update_delegate_profile(
+        &self,
+        registrar: &RegistrarCookie,
+        delegate: &Keypair,
+        active: bool,
+        max_accepted_stake: u64,
+        metadata_uri: &str,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::UpdateDelegateProfile {
+            active,
+            max_accepted_stake,
+            metadata_uri: metadata_uri.to_string(),
+        });
+
+        let (delegate_profile, _) =
+            find_delegate_profile_addr(&self.program_id, &registrar.address, &delegate.pubkey());
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::UpdateDelegateProfile {
+                registrar: registrar.address,
+                delegate_profile,
+                delegate: delegate.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[delegate]))
+            .await
+    }
+
+    pub async fn create_deposit_entry(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegate_voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        deposit_entry_index: u8,
+        lockup_kind: LockupKind,
+        period: LockupPeriod,
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(voting_mint);
+
+        let data = InstructionData::data(&mpl_staking::instruction::CreateDepositEntry {
+            deposit_entry_index,
+            kind: lockup_kind,
+            period,
+        });
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::CreateDepositEntry {
+                vault,
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter.authority.pubkey(),
+                payer: voter.authority.pubkey(),
+                deposit_mint: voting_mint.mint.pubkey.unwrap(),
+                system_program: solana_sdk::system_program::id(),
+                token_program: spl_token::id(),
+                associated_token_program: spl_associated_token_account::id(),
+                delegate_voter: delegate_voter.address,
+            },
+            None,
+        );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+        if delegate_voter.address != voter.address {
+            let (delegate_profile, _) = find_delegate_profile_addr(
+                &self.program_id,
+                &registrar.address,
+                &delegate_voter.authority.pubkey(),
+            );
+            accounts.push(anchor_lang::prelude::AccountMeta::new_readonly(
+                delegate_profile,
+                false,
+            ));
+        }
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&voter.authority]))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn deposit(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        deposit_authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(voting_mint);
+
+        let data = InstructionData::data(&mpl_staking::instruction::Deposit {
+            deposit_entry_index,
+            amount,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Deposit {
+                registrar: registrar.address,
+                voter: voter.address,
+                vault,
+                deposit_token: token_address,
+                deposit_authority: deposit_authority.pubkey(),
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[deposit_authority]))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn extend_stake(
+        &self,
+        // accounts
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+        delegate: &Pubkey,
+        rewards_program: &Pubkey,
+        // params
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        new_lockup_period: LockupPeriod,
+        additional_amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::ExtendStake {
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            new_lockup_period,
+            additional_amount,
+        });
+
+        let (deposit_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &voter_authority.pubkey(),
+            &registrar.reward_pool,
+        );
+
+        let (delegate_mining, _) =
+            find_deposit_mining_addr(rewards_program, delegate, &registrar.reward_pool);
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Stake {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                delegate: *delegate,
+                delegate_mining,
+                reward_pool: registrar.reward_pool,
+                deposit_mining,
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+        accounts.extend(self.delegate_metas(registrar, &[*delegate]));
+        accounts.push(self.token_owner_record_meta(registrar, &voter_authority.pubkey()));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[voter_authority]))
+            .await
+    }
+
+    /// Unlocks the whole deposit.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn unlock_tokens(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegate_voter: &VoterCookie,
+        deposit_entry_index: u8,
+        reward_pool: &Pubkey,
+        rewards_program: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let amount = voter
+            .get_deposit_entry(&self.solana, deposit_entry_index)
+            .await
+            .amount_deposited_native;
+        self.unlock_tokens_amount(
+            registrar,
+            voter,
+            delegate_voter,
+            deposit_entry_index,
+            reward_pool,
+            rewards_program,
+            amount,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn unlock_tokens_amount(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegate_voter: &VoterCookie,
+        deposit_entry_index: u8,
+        reward_pool: &Pubkey,
+        rewards_program: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::UnlockTokens {
+            deposit_entry_index,
+            amount,
+        });
+
+        let (deposit_mining, _) =
+            find_deposit_mining_addr(rewards_program, &voter.authority.pubkey(), reward_pool);
+
+        let (delegate_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &delegate_voter.authority.pubkey(),
+            reward_pool,
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Stake {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter.authority.pubkey(),
+                delegate: delegate_voter.authority.pubkey(),
+                reward_pool: *reward_pool,
+                deposit_mining,
+                delegate_mining,
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+        accounts.extend(self.delegate_metas(registrar, &[delegate_voter.authority.pubkey()]));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&voter.authority]))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn cancel_unlock(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegate_voter: &VoterCookie,
+        deposit_entry_index: u8,
+        new_lockup_period: LockupPeriod,
+        reward_pool: &Pubkey,
+        rewards_program: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::CancelUnlock {
+            deposit_entry_index,
+            new_lockup_period,
+        });
+
+        let (deposit_mining, _) =
+            find_deposit_mining_addr(rewards_program, &voter.authority.pubkey(), reward_pool);
+
+        let (delegate_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &delegate_voter.authority.pubkey(),
+            reward_pool,
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Stake {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter.authority.pubkey(),
+                delegate: delegate_voter.authority.pubkey(),
+                reward_pool: *reward_pool,
+                deposit_mining,
+                delegate_mining,
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+        accounts.extend(self.delegate_metas(registrar, &[delegate_voter.authority.pubkey()]));
+        accounts.push(self.token_owner_record_meta(registrar, &voter.authority.pubkey()));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&voter.authority]))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn merge_deposits(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegate_voter: &VoterCookie,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        reward_pool: &Pubkey,
+        rewards_program: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::MergeDeposits {
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+        });
+
+        let (deposit_mining, _) =
+            find_deposit_mining_addr(rewards_program, &voter.authority.pubkey(), reward_pool);
+
+        let (delegate_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &delegate_voter.authority.pubkey(),
+            reward_pool,
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Stake {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter.authority.pubkey(),
+                delegate: delegate_voter.authority.pubkey(),
+                reward_pool: *reward_pool,
+                deposit_mining,
+                delegate_mining,
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+        accounts.extend(self.delegate_metas(registrar, &[delegate_voter.authority.pubkey()]));
+        accounts.push(self.token_owner_record_meta(registrar, &voter.authority.pubkey()));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&voter.authority]))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn withdraw(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        realm_treasury: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(voting_mint);
+
+        let data = InstructionData::data(&mpl_staking::instruction::Withdraw {
+            deposit_entry_index,
+            amount,
+        });
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Withdraw {
+                registrar: registrar.address,
+                voter: voter.address,
+                token_owner_record: voter.token_owner_record,
+                voter_weight_record: voter.voter_weight_record,
+                vault,
+                realm_treasury,
+                destination: token_address,
+                voter_authority: authority.pubkey(),
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[authority]))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn grant(
+        &self,
+        registrar: &RegistrarCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        voter_authority: &Keypair,
+        voting_mint: &VotingMintConfigCookie,
+        grant_authority: &Keypair,
+        deposit_token: Pubkey,
+        payer: &Keypair,
+        kind: LockupKind,
+        period: LockupPeriod,
+        periods: u32,
+        allow_clawback: bool,
+        amount: u64,
+        rewards_program: &Pubkey,
+    ) -> std::result::Result<VoterCookie, BanksClientError> {
+        let (voter, voter_bump) = Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"voter".as_ref(),
+                &voter_authority.pubkey().to_bytes(),
+            ],
+            &self.program_id,
+        );
+        let (voter_weight_record, voter_weight_record_bump) = Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"voter-weight-record".as_ref(),
+                &voter_authority.pubkey().to_bytes(),
+            ],
+            &self.program_id,
+        );
+        let deposit_mint = voting_mint.mint.pubkey.unwrap();
+        let vault =
+            spl_associated_token_account::get_associated_token_address(&voter, &deposit_mint);
+        let (deposit_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &voter_authority.pubkey(),
+            &registrar.reward_pool,
+        );
+
+        let data = InstructionData::data(&mpl_staking::instruction::Grant {
+            voter_bump,
+            voter_weight_record_bump,
+            kind,
+            period,
+            periods,
+            allow_clawback,
+            amount,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Grant {
+                registrar: registrar.address,
+                voter,
+                voter_authority: voter_authority.pubkey(),
+                voter_weight_record,
+                vault,
+                deposit_token,
+                grant_authority: grant_authority.pubkey(),
+                payer: payer.pubkey(),
+                deposit_mint,
+                reward_pool: registrar.reward_pool,
+                deposit_mining,
+                rewards_program: *rewards_program,
+                system_program: solana_sdk::system_program::id(),
+                token_program: spl_token::id(),
+                associated_token_program: spl_associated_token_account::id(),
+                rent: solana_program::sysvar::rent::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[payer, grant_authority]))
+            .await?;
+
+        Ok(VoterCookie {
+            address: voter,
+            authority: Keypair::from_bytes(&voter_authority.to_bytes()).unwrap(),
+            voter_weight_record,
+            token_owner_record: token_owner_record.address,
+        })
+    }
+
+    pub async fn clawback(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        grant_authority: &Keypair,
+        destination: Pubkey,
+        deposit_entry_index: u8,
+        rewards_program: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let (deposit_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &voter.authority.pubkey(),
+            &registrar.reward_pool,
+        );
+        let delegate = voter
+            .get_deposit_entry(&self.solana, deposit_entry_index)
+            .await
+            .delegate;
+        let (delegate_mining, _) =
+            find_deposit_mining_addr(rewards_program, &delegate, &registrar.reward_pool);
+
+        let data = InstructionData::data(&mpl_staking::instruction::Clawback {
+            deposit_entry_index,
+        });
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::Clawback {
+                registrar: registrar.address,
+                voter: voter.address,
+                grant_authority: grant_authority.pubkey(),
+                voter_weight_record: voter.voter_weight_record,
+                vault: voter.vault_address(voting_mint),
+                destination,
+                reward_pool: registrar.reward_pool,
+                deposit_mining,
+                delegate_mining,
+                rewards_program: *rewards_program,
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+        accounts.extend(self.delegate_metas(registrar, &[delegate]));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[grant_authority]))
+            .await
+    }
+
+    pub async fn close_voter(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        voter_authority: &Keypair,
+        rewards_program: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(voting_mint);
+
+        let (deposit_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &voter_authority.pubkey(),
+            &registrar.reward_pool,
+        );
+
+        let data = InstructionData::data(&mpl_staking::instruction::CloseVoter {});
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::CloseVoter {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                deposit_mining,
+                reward_pool: registrar.reward_pool,
+                sol_destination: voter_authority.pubkey(),
+                token_program: spl_token::id(),
+                rewards_program: *rewards_program,
+            },
+            None,
+        );
+        self.mark_registrar_writable_if_grown(registrar.address, &mut accounts)
+            .await;
+        accounts.push(anchor_lang::prelude::AccountMeta::new(vault, false));
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[voter_authority]))
+            .await
+    }
+
+    /// Like `update_voter_weight_record`, adding the tokens delegated to the voter.
+    pub async fn update_voter_weight_record_with_delegated_stake(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> std::result::Result<mpl_staking::voter::VoterWeightRecord, BanksClientError> {
+        let mut instruction = self.update_voter_weight_record_instruction(registrar, voter);
+        let (delegated_stake, _) = find_delegated_stake_addr(
+            &self.program_id,
+            &registrar.address,
+            &voter.authority.pubkey(),
+        );
+        instruction
+            .accounts
+            .push(anchor_lang::prelude::AccountMeta::new_readonly(
+                delegated_stake,
+                false,
+            ));
+
+        self.solana
+            .process_transaction(&[instruction], None)
+            .await?;
+
+        Ok(self
+            .solana
+            .get_account::<mpl_staking::voter::VoterWeightRecord>(voter.voter_weight_record)
+            .await)
+    }
+
+    pub fn update_voter_weight_record_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> Instruction {
+        let data = InstructionData::data(&mpl_staking::instruction::UpdateVoterWeightRecord {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::UpdateVoterWeightRecord {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_weight_record: voter.voter_weight_record,
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    pub async fn update_voter_weight_record(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> std::result::Result<mpl_staking::voter::VoterWeightRecord, BanksClientError> {
+        let instructions = vec![self.update_voter_weight_record_instruction(registrar, voter)];
+
+        self.solana.process_transaction(&instructions, None).await?;
+
+        Ok(self
+            .solana
+            .get_account::<mpl_staking::voter::VoterWeightRecord>(voter.voter_weight_record)
+            .await)
+    }
+
+    pub fn max_voter_weight_record_address(&self, registrar: &RegistrarCookie) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"max-voter-weight-record".as_ref(),
+            ],
+            &self.program_id,
+        )
+    }
+
+    pub async fn create_max_voter_weight_record(
+        &self,
+        registrar: &RegistrarCookie,
+        payer: &Keypair,
+        voting_mints: &[Pubkey],
+    ) -> std::result::Result<Pubkey, BanksClientError> {
+        let (max_voter_weight_record, max_voter_weight_record_bump) =
+            self.max_voter_weight_record_address(registrar);
+
+        let data = InstructionData::data(&mpl_staking::instruction::CreateMaxVoterWeightRecord {
+            max_voter_weight_record_bump,
+        });
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::CreateMaxVoterWeightRecord {
+                registrar: registrar.address,
+                max_voter_weight_record,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+        for mint in voting_mints {
+            accounts.push(anchor_lang::prelude::AccountMeta::new_readonly(
+                *mint, false,
+            ));
+        }
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(&[payer]))
+            .await?;
+
+        Ok(max_voter_weight_record)
+    }
+
+    pub async fn update_max_voter_weight_record(
+        &self,
+        registrar: &RegistrarCookie,
+        voting_mints: &[Pubkey],
+    ) -> std::result::Result<mpl_staking::voter::MaxVoterWeightRecord, BanksClientError> {
+        let (max_voter_weight_record, _) = self.max_voter_weight_record_address(registrar);
+
+        let data = InstructionData::data(&mpl_staking::instruction::UpdateMaxVoterWeightRecord {});
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::UpdateMaxVoterWeightRecord {
                 registrar: registrar.address,
-                voter: voter.address,
-                voter_authority: voter.authority.pubkey(),
-                payer: voter.authority.pubkey(),
-                deposit_mint: voting_mint.mint.pubkey.unwrap(),
-                system_program: solana_sdk::system_program::id(),
-                token_program: spl_token::id(),
-                associated_token_program: spl_associated_token_account::id(),
-                delegate_voter: delegate_voter.address,
+                max_voter_weight_record,
             },
             None,
         );
+        for mint in voting_mints {
+            accounts.push(anchor_lang::prelude::AccountMeta::new_readonly(
+                *mint, false,
+            ));
+        }
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -402,40 +1660,32 @@ impl AddinCookie {
             data,
         }];
 
-        self.solana
-            .process_transaction(&instructions, Some(&[&voter.authority]))
-            .await
+        self.solana.process_transaction(&instructions, None).await?;
+
+        Ok(self
+            .solana
+            .get_account::<mpl_staking::voter::MaxVoterWeightRecord>(max_voter_weight_record)
+            .await)
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn deposit(
+    pub async fn get_max_vote_weight(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-        voting_mint: &VotingMintConfigCookie,
-        deposit_authority: &Keypair,
-        token_address: Pubkey,
-        deposit_entry_index: u8,
-        amount: u64,
-    ) -> std::result::Result<(), BanksClientError> {
-        let vault = voter.vault_address(voting_mint);
-
-        let data = InstructionData::data(&mpl_staking::instruction::Deposit {
-            deposit_entry_index,
-            amount,
-        });
+        voting_mints: &[Pubkey],
+    ) -> std::result::Result<u64, BanksClientError> {
+        let data = InstructionData::data(&mpl_staking::instruction::GetMaxVoteWeight {});
 
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::Deposit {
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::GetMaxVoteWeight {
                 registrar: registrar.address,
-                voter: voter.address,
-                vault,
-                deposit_token: token_address,
-                deposit_authority: deposit_authority.pubkey(),
-                token_program: spl_token::id(),
             },
             None,
         );
+        for mint in voting_mints {
+            accounts.push(anchor_lang::prelude::AccountMeta::new_readonly(
+                *mint, false,
+            ));
+        }
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -443,55 +1693,34 @@ impl AddinCookie {
             data,
         }];
 
-        self.solana
-            .process_transaction(&instructions, Some(&[deposit_authority]))
-            .await
+        self.solana.process_transaction(&instructions, None).await?;
+
+        let return_data = self.solana.program_output().return_data;
+        let return_data = base64::decode(return_data.last().expect("no return data")).unwrap();
+        Ok(u64::from_le_bytes(return_data.try_into().unwrap()))
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn extend_stake(
+    pub async fn close_deposit_entry(
         &self,
-        // accounts
-        registrar: &RegistrarCookie,
         voter: &VoterCookie,
-        voter_authority: &Keypair,
-        delegate: &Pubkey,
-        rewards_program: &Pubkey,
-        // params
-        source_deposit_entry_index: u8,
-        target_deposit_entry_index: u8,
-        new_lockup_period: LockupPeriod,
-        additional_amount: u64,
+        authority: &Keypair,
+        deposit_entry_index: u8,
     ) -> std::result::Result<(), BanksClientError> {
-        let data = InstructionData::data(&mpl_staking::instruction::ExtendStake {
-            source_deposit_entry_index,
-            target_deposit_entry_index,
-            new_lockup_period,
-            additional_amount,
+        let data = InstructionData::data(&mpl_staking::instruction::CloseDepositEntry {
+            deposit_entry_index,
         });
 
-        let (deposit_mining, _) = find_deposit_mining_addr(
-            rewards_program,
-            &voter_authority.pubkey(),
-            &registrar.reward_pool,
-        );
-
-        let (delegate_mining, _) =
-            find_deposit_mining_addr(rewards_program, delegate, &registrar.reward_pool);
-
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::Stake {
-                registrar: registrar.address,
+        let registrar = voter.get_voter(&self.solana).await.registrar;
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &mpl_staking::accounts::CloseDepositEntry {
+                registrar,
                 voter: voter.address,
-                voter_authority: voter_authority.pubkey(),
-                delegate: *delegate,
-                delegate_mining,
-                reward_pool: registrar.reward_pool,
-                deposit_mining,
-                rewards_program: *rewards_program,
+                voter_authority: authority.pubkey(),
             },
             None,
         );
+        self.mark_registrar_writable_if_grown(registrar, &mut accounts)
+            .await;
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -500,43 +1729,26 @@ impl AddinCookie {
         }];
 
         self.solana
-            .process_transaction(&instructions, Some(&[voter_authority]))
+            .process_transaction(&instructions, Some(&[authority]))
             .await
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn unlock_tokens(
+    pub async fn grow_voter(
         &self,
-        registrar: &RegistrarCookie,
         voter: &VoterCookie,
-        delegate_voter: &VoterCookie,
-        deposit_entry_index: u8,
-        reward_pool: &Pubkey,
-        rewards_program: &Pubkey,
+        payer: &Keypair,
+        additional_deposit_entries: u8,
     ) -> std::result::Result<(), BanksClientError> {
-        let data = InstructionData::data(&mpl_staking::instruction::UnlockTokens {
-            deposit_entry_index,
+        let data = InstructionData::data(&mpl_staking::instruction::GrowVoter {
+            additional_deposit_entries,
         });
 
-        let (deposit_mining, _) =
-            find_deposit_mining_addr(rewards_program, &voter.authority.pubkey(), reward_pool);
-
-        let (delegate_mining, _) = find_deposit_mining_addr(
-            rewards_program,
-            &delegate_voter.authority.pubkey(),
-            reward_pool,
-        );
-
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::Stake {
-                registrar: registrar.address,
+            &mpl_staking::accounts::GrowVoter {
                 voter: voter.address,
                 voter_authority: voter.authority.pubkey(),
-                delegate: delegate_voter.authority.pubkey(),
-                reward_pool: *reward_pool,
-                deposit_mining,
-                delegate_mining,
-                rewards_program: *rewards_program,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
             },
             None,
         );
@@ -548,40 +1760,26 @@ impl AddinCookie {
         }];
 
         self.solana
-            .process_transaction(&instructions, Some(&[&voter.authority]))
+            .process_transaction(&instructions, Some(&[payer, &voter.authority]))
             .await
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn withdraw(
+    pub async fn set_auto_renew(
         &self,
-        registrar: &RegistrarCookie,
         voter: &VoterCookie,
-        voting_mint: &VotingMintConfigCookie,
         authority: &Keypair,
-        token_address: Pubkey,
-        realm_treasury: Pubkey,
         deposit_entry_index: u8,
-        amount: u64,
+        auto_renew: bool,
     ) -> std::result::Result<(), BanksClientError> {
-        let vault = voter.vault_address(voting_mint);
-
-        let data = InstructionData::data(&mpl_staking::instruction::Withdraw {
+        let data = InstructionData::data(&mpl_staking::instruction::SetAutoRenew {
             deposit_entry_index,
-            amount,
+            auto_renew,
         });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::Withdraw {
-                registrar: registrar.address,
+            &mpl_staking::accounts::SetAutoRenew {
                 voter: voter.address,
-                token_owner_record: voter.token_owner_record,
-                voter_weight_record: voter.voter_weight_record,
-                vault,
-                realm_treasury,
-                destination: token_address,
                 voter_authority: authority.pubkey(),
-                token_program: spl_token::id(),
             },
             None,
         );
@@ -597,38 +1795,29 @@ impl AddinCookie {
             .await
     }
 
-    pub async fn close_voter(
+    pub async fn split_deposit(
         &self,
-        registrar: &RegistrarCookie,
         voter: &VoterCookie,
-        voting_mint: &VotingMintConfigCookie,
-        voter_authority: &Keypair,
-        rewards_program: &Pubkey,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+        amount: u64,
     ) -> std::result::Result<(), BanksClientError> {
-        let vault = voter.vault_address(voting_mint);
-
-        let (deposit_mining, _) = find_deposit_mining_addr(
-            rewards_program,
-            &voter_authority.pubkey(),
-            &registrar.reward_pool,
-        );
-
-        let data = InstructionData::data(&mpl_staking::instruction::CloseVoter {});
+        let data = InstructionData::data(&mpl_staking::instruction::SplitDeposit {
+            deposit_entry_index,
+            amount,
+        });
 
+        let registrar = voter.get_voter(&self.solana).await.registrar;
         let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::CloseVoter {
-                registrar: registrar.address,
+            &mpl_staking::accounts::SplitDeposit {
+                registrar,
                 voter: voter.address,
-                voter_authority: voter_authority.pubkey(),
-                deposit_mining,
-                reward_pool: registrar.reward_pool,
-                sol_destination: voter_authority.pubkey(),
-                token_program: spl_token::id(),
-                rewards_program: *rewards_program,
+                voter_authority: authority.pubkey(),
             },
             None,
         );
-        accounts.push(anchor_lang::prelude::AccountMeta::new(vault, false));
+        self.mark_registrar_writable_if_grown(registrar, &mut accounts)
+            .await;
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -637,63 +1826,43 @@ impl AddinCookie {
         }];
 
         self.solana
-            .process_transaction(&instructions, Some(&[voter_authority]))
+            .process_transaction(&instructions, Some(&[authority]))
             .await
     }
 
-    pub fn update_voter_weight_record_instruction(
-        &self,
-        registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-    ) -> Instruction {
-        let data = InstructionData::data(&mpl_staking::instruction::UpdateVoterWeightRecord {});
-
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::UpdateVoterWeightRecord {
-                registrar: registrar.address,
-                voter: voter.address,
-                voter_weight_record: voter.voter_weight_record,
-                system_program: solana_sdk::system_program::id(),
-            },
-            None,
-        );
-
-        Instruction {
-            program_id: self.program_id,
-            accounts,
-            data,
-        }
-    }
-
-    pub async fn update_voter_weight_record(
+    /// Renews the stake without the voter's signature, the payer signs alone.
+    pub async fn renew_stake(
         &self,
         registrar: &RegistrarCookie,
         voter: &VoterCookie,
-    ) -> std::result::Result<mpl_staking::voter::VoterWeightRecord, BanksClientError> {
-        let instructions = vec![self.update_voter_weight_record_instruction(registrar, voter)];
-
-        self.solana.process_transaction(&instructions, None).await?;
-
-        Ok(self
-            .solana
-            .get_account::<mpl_staking::voter::VoterWeightRecord>(voter.voter_weight_record)
-            .await)
-    }
-
-    pub async fn close_deposit_entry(
-        &self,
-        voter: &VoterCookie,
-        authority: &Keypair,
+        delegate_voter: &VoterCookie,
         deposit_entry_index: u8,
+        reward_pool: &Pubkey,
+        rewards_program: &Pubkey,
     ) -> std::result::Result<(), BanksClientError> {
-        let data = InstructionData::data(&mpl_staking::instruction::CloseDepositEntry {
+        let data = InstructionData::data(&mpl_staking::instruction::RenewStake {
             deposit_entry_index,
         });
 
+        let (deposit_mining, _) =
+            find_deposit_mining_addr(rewards_program, &voter.authority.pubkey(), reward_pool);
+
+        let (delegate_mining, _) = find_deposit_mining_addr(
+            rewards_program,
+            &delegate_voter.authority.pubkey(),
+            reward_pool,
+        );
+
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &mpl_staking::accounts::CloseDepositEntry {
+            &mpl_staking::accounts::RenewStake {
+                registrar: registrar.address,
                 voter: voter.address,
-                voter_authority: authority.pubkey(),
+                voter_authority: voter.authority.pubkey(),
+                delegate: delegate_voter.authority.pubkey(),
+                reward_pool: *reward_pool,
+                deposit_mining,
+                delegate_mining,
+                rewards_program: *rewards_program,
             },
             None,
         );
@@ -704,9 +1873,7 @@ impl AddinCookie {
             data,
         }];
 
-        self.solana
-            .process_transaction(&instructions, Some(&[authority]))
-            .await
+        self.solana.process_transaction(&instructions, None).await
     }
 
     pub async fn log_voter_info(
@@ -714,11 +1881,21 @@ impl AddinCookie {
         registrar: &RegistrarCookie,
         voter: &VoterCookie,
         deposit_entry_begin: u8,
+        guaranteed_at_ts: Option<u64>,
     ) {
-        let data = InstructionData::data(&mpl_staking::instruction::LogVoterInfo {
-            deposit_entry_begin,
-            deposit_entry_count: 8,
-        });
+        let data = match guaranteed_at_ts {
+            Some(guaranteed_at_ts) => {
+                InstructionData::data(&mpl_staking::instruction::LogVoterInfoAt {
+                    deposit_entry_begin,
+                    deposit_entry_count: 8,
+                    guaranteed_at_ts,
+                })
+            }
+            None => InstructionData::data(&mpl_staking::instruction::LogVoterInfo {
+                deposit_entry_begin,
+                deposit_entry_count: 8,
+            }),
+        };
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &mpl_staking::accounts::LogVoterInfo {
@@ -770,9 +1947,12 @@ impl AddinCookie {
         realm_authority: &Keypair,
         voter: &VoterCookie,
         until_ts: u64,
+        reason: u8,
     ) -> std::result::Result<(), BanksClientError> {
-        let data =
-            InstructionData::data(&mpl_staking::instruction::RestrictBatchMinting { until_ts });
+        let data = InstructionData::data(&mpl_staking::instruction::RestrictBatchMinting {
+            until_ts,
+            reason,
+        });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &mpl_staking::accounts::Penalty {
@@ -804,9 +1984,11 @@ impl AddinCookie {
         decreased_weighted_stake_number: u64,
         voter: &VoterCookie,
         rewards_program: &Pubkey,
+        reason: u8,
     ) -> std::result::Result<(), BanksClientError> {
         let data = InstructionData::data(&mpl_staking::instruction::DecreaseRewards {
             decreased_weighted_stake_number,
+            reason,
         });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
@@ -843,11 +2025,13 @@ impl AddinCookie {
         amount: u64,
         mining_owner: &Pubkey,
         rewards_program: &Pubkey,
+        reason: u8,
     ) -> std::result::Result<(), BanksClientError> {
         let data = InstructionData::data(&mpl_staking::instruction::Slash {
             deposit_entry_index,
             amount,
             mining_owner: *mining_owner,
+            reason,
         });
 
         let (deposit_mining, _) = find_deposit_mining_addr(
@@ -856,7 +2040,7 @@ impl AddinCookie {
             &registrar.reward_pool,
         );
 
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &mpl_staking::accounts::Slashing {
                 registrar: registrar.address,
                 voter: voter.address,
@@ -868,6 +2052,11 @@ impl AddinCookie {
             },
             None,
         );
+        let delegate = voter
+            .get_deposit_entry(&self.solana, deposit_entry_index)
+            .await
+            .delegate;
+        accounts.extend(self.delegate_metas(registrar, &[delegate]));
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -885,8 +2074,9 @@ impl AddinCookie {
         registrar: &RegistrarCookie,
         realm_authority: &Keypair,
         voter: &VoterCookie,
+        reason: u8,
     ) -> std::result::Result<(), BanksClientError> {
-        let data = InstructionData::data(&mpl_staking::instruction::RestrictTokenflow {});
+        let data = InstructionData::data(&mpl_staking::instruction::RestrictTokenflow { reason });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &mpl_staking::accounts::Penalty {
@@ -913,8 +2103,9 @@ impl AddinCookie {
         registrar: &RegistrarCookie,
         realm_authority: &Keypair,
         voter: &VoterCookie,
+        reason: u8,
     ) -> std::result::Result<(), BanksClientError> {
-        let data = InstructionData::data(&mpl_staking::instruction::AllowTokenflow {});
+        let data = InstructionData::data(&mpl_staking::instruction::AllowTokenflow { reason });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &mpl_staking::accounts::Penalty {
@@ -1011,7 +2202,25 @@ impl VoterCookie {
 
     pub async fn get_deposit_entry(&self, solana: &SolanaCookie, deposit_id: u8) -> DepositEntry {
         let voter = Self::get_voter(self, solana).await;
-        voter.deposits[deposit_id as usize]
+        let deposit_id = deposit_id as usize;
+        if deposit_id < voter.deposits.len() {
+            return voter.deposits[deposit_id];
+        }
+
+        // entries of a grown voter follow the voter and its extension header
+        let data = solana.get_account_data(self.address).await;
+        let offset = 8
+            + size_of::<Voter>()
+            + size_of::<VoterExtension>()
+            + (deposit_id - voter.deposits.len()) * size_of::<DepositEntry>();
+        unsafe { std::ptr::read_unaligned(data[offset..].as_ptr() as *const DepositEntry) }
+    }
+
+    pub async fn get_deposits_len(&self, solana: &SolanaCookie) -> usize {
+        let data = solana.get_account_data(self.address).await;
+        let extension_len = data.len() - 8 - size_of::<Voter>();
+        Voter::INLINE_DEPOSIT_ENTRIES
+            + extension_len.saturating_sub(size_of::<VoterExtension>()) / size_of::<DepositEntry>()
     }
 
     pub fn vault_address(&self, mint: &VotingMintConfigCookie) -> Pubkey {
@@ -56,6 +56,7 @@ impl AddPacked for ProgramTest {
 pub struct ProgramOutput {
     pub logs: Vec<String>,
     pub data: Vec<String>,
+    pub return_data: Vec<String>,
 }
 struct LoggerWrapper {
     inner: env_logger::Logger,
@@ -77,6 +78,11 @@ impl Log for LoggerWrapper {
                 self.output.write().unwrap().logs.push(data.into());
             } else if let Some(data) = msg.strip_prefix("Program data: ") {
                 self.output.write().unwrap().data.push(data.into());
+            } else if let Some(data) = msg.strip_prefix("Program return: ") {
+                // the program id comes before the data
+                if let Some((_, data)) = data.split_once(' ') {
+                    self.output.write().unwrap().return_data.push(data.into());
+                }
             }
         }
         self.inner.log(record);
@@ -152,6 +158,15 @@ impl TestContext {
                 pubkey: None,
                 authority: Keypair::new(),
             }, // symbol: "USDC".to_string()
+            MintCookie {
+                index: 2,
+                decimals: 6,
+                unit: 10u64.pow(6) as f64,
+                base_lot: 0 as f64,
+                quote_lot: 0 as f64,
+                pubkey: None,
+                authority: Keypair::new(),
+            },
         ];
         // Add mints in loop
         for mint in mints.iter_mut() {
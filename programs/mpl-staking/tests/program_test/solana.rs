@@ -107,6 +107,29 @@ impl SolanaCookie {
         keypair.pubkey()
     }
 
+    #[allow(dead_code)]
+    pub async fn mint_to(
+        &self,
+        mint: &Pubkey,
+        mint_authority: &Keypair,
+        destination: &Pubkey,
+        amount: u64,
+    ) {
+        let instructions = [spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            destination,
+            &mint_authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()];
+
+        self.process_transaction(&instructions, Some(&[mint_authority]))
+            .await
+            .unwrap();
+    }
+
     #[allow(dead_code)]
     pub async fn get_account_data(&self, address: Pubkey) -> Vec<u8> {
         self.context
@@ -120,6 +143,21 @@ impl SolanaCookie {
             .to_vec()
     }
 
+    /// Replaces the data of an account, e.g. to recreate an account layout
+    /// that can't be created by the program anymore.
+    #[allow(dead_code)]
+    pub async fn set_account_data(&self, address: Pubkey, data: Vec<u8>) {
+        let mut context = self.context.borrow_mut();
+        let mut account = context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        account.data = data;
+        context.set_account(&address, &account.into());
+    }
+
     pub async fn get_account<T: AccountDeserialize>(&self, address: Pubkey) -> T {
         let data = self.get_account_data(address).await;
         let mut data_slice: &[u8] = &data;
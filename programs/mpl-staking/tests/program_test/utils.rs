@@ -89,6 +89,36 @@ pub fn find_deposit_mining_addr(
     )
 }
 
+pub fn find_delegated_stake_addr(
+    program_id: &Pubkey,
+    registrar: &Pubkey,
+    delegate: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &registrar.to_bytes(),
+            "delegated-stake".as_bytes(),
+            &delegate.to_bytes(),
+        ],
+        program_id,
+    )
+}
+
+pub fn find_delegate_profile_addr(
+    program_id: &Pubkey,
+    registrar: &Pubkey,
+    delegate: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &registrar.to_bytes(),
+            "delegate-profile".as_bytes(),
+            &delegate.to_bytes(),
+        ],
+        program_id,
+    )
+}
+
 pub async fn advance_clock_by_ts(context: &mut ProgramTestContext, ts: i64) {
     let old_clock = context
         .banks_client
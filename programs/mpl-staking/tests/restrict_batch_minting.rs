@@ -1,6 +1,10 @@
 use anchor_spl::token::TokenAccount;
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
 use mpl_common_constants::constants::REALM_NAME;
-use mplx_staking_states::state::{LockupKind, LockupPeriod};
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
 use program_test::*;
 use solana_program_test::*;
 use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
@@ -154,7 +158,268 @@ async fn restrict_batch_minting_poc() -> Result<(), TransportError> {
 
     context
         .addin
-        .restrict_batch_minting(&registrar, &realm_authority, &voter, distribution_ends_at)
+        .restrict_batch_minting(
+            &registrar,
+            &realm_authority,
+            &voter,
+            distribution_ends_at,
+            0,
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn restrict_batch_minting_forbids_staking() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            REALM_NAME,
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let deposit_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(deposit_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    context
+        .addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+    let mngo_voting_mint = context
+        .addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    // TODO: ??? voter_authority == deposit_authority ???
+    let voter_authority = deposit_authority;
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+
+    let voter = context
+        .addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    let depositer_token_account = context.users[1].token_accounts[0];
+
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await?;
+
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            deposit_authority,
+            depositer_token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    context
+        .addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            5000,
+        )
+        .await?;
+
+    let distribution_ends_at = context
+        .solana
+        .context
+        .borrow_mut()
+        .banks_client
+        .get_sysvar::<solana_program::clock::Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp as u64
+        + 86400;
+
+    context
+        .addin
+        .restrict_batch_minting(
+            &registrar,
+            &realm_authority,
+            &voter,
+            distribution_ends_at,
+            0,
+        )
+        .await?;
+
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            2,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await?;
+    context
+        .addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            2,
+            1000,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::BatchMintingRestricted);
+    context
+        .addin
+        .extend_stake(
+            &registrar,
+            &voter,
+            voter_authority,
+            &voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            LockupPeriod::OneYear,
+            1000,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::BatchMintingRestricted);
+
+    // delegating to another voter counts as batch minting too
+    let delegate_authority = &context.users[2].key;
+    let delegate_token_owner_record = realm
+        .create_token_owner_record(delegate_authority.pubkey(), payer)
+        .await;
+    let (delegate_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &delegate_authority.pubkey(),
+        &rewards_pool,
+    );
+    let delegate_voter = context
+        .addin
+        .create_voter(
+            &registrar,
+            &delegate_token_owner_record,
+            delegate_authority,
+            payer,
+            &rewards_pool,
+            &delegate_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &mngo_voting_mint,
+            3,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::BatchMintingRestricted);
+
+    // the restriction can be lifted by passing a past timestamp
+    context
+        .addin
+        .restrict_batch_minting(&registrar, &realm_authority, &voter, 0, 0)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            2,
+            1000,
+        )
         .await?;
 
     Ok(())
@@ -168,7 +168,7 @@ async fn test_all_deposits() -> Result<(), TransportError> {
         .unwrap();
 
     // logging can take a lot of cu/mem
-    addin.log_voter_info(&registrar, &voter, 0).await;
+    addin.log_voter_info(&registrar, &voter, 0, None).await;
 
     Ok(())
 }
@@ -0,0 +1,191 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_auto_renew() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (deposit_entry_index, kind, period) in [
+        (0, LockupKind::None, LockupPeriod::None),
+        (1, LockupKind::Constant, LockupPeriod::ThreeMonths),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                kind,
+                period,
+            )
+            .await?;
+    }
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            10_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            10_000,
+        )
+        .await?;
+
+    let renew = || {
+        addin.renew_stake(
+            &registrar,
+            &voter,
+            &voter,
+            1,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+    };
+
+    // only Constant deposits can be renewed
+    addin
+        .set_auto_renew(&voter, voter_authority, 0, true)
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidLockupKind);
+
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            LockupPeriod::ThreeMonths.to_secs() as i64,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    renew()
+        .await
+        .assert_on_chain_err(MplStakingError::AutoRenewNotEnabled);
+
+    addin.set_time_offset(&registrar, &realm_authority, 0).await;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .set_auto_renew(&voter, voter_authority, 1, true)
+        .await?;
+    let deposit = voter.get_deposit_entry(&context.solana, 1).await;
+    assert!(deposit.auto_renew);
+    renew()
+        .await
+        .assert_on_chain_err(MplStakingError::DepositStillLocked);
+
+    // once expired, anyone can lock the deposit up again for the same period
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            LockupPeriod::ThreeMonths.to_secs() as i64 + 86_400,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    renew().await?;
+
+    let deposit = voter.get_deposit_entry(&context.solana, 1).await;
+    let curr_ts = context.solana.get_clock().await.unix_timestamp as u64;
+    assert_eq!({ deposit.amount_deposited_native }, 10_000);
+    assert_eq!({ deposit.lockup.period }, LockupPeriod::ThreeMonths);
+    assert_eq!({ deposit.lockup.start_ts }, curr_ts);
+    assert_eq!(
+        { deposit.lockup.end_ts },
+        curr_ts + LockupPeriod::ThreeMonths.to_secs()
+    );
+
+    addin
+        .unlock_tokens(
+            &registrar,
+            &voter,
+            &voter,
+            1,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DepositStillLocked);
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use anchor_spl::token::TokenAccount;
 use assert_custom_on_chain_error::AssertCustomOnChainErr;
 use mplx_staking_states::{
     error::MplStakingError,
-    state::{LockupKind, LockupPeriod},
+    state::{DelegateProfile, LockupKind, LockupPeriod, Voter},
 };
 use program_test::*;
 use solana_program_test::*;
@@ -146,6 +146,10 @@ async fn change_from_own_delegate_to_new_delegate() -> Result<(), TransportError
             6_000_000,
         )
         .await?;
+    context
+        .addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
 
     // CREATE VOTER
     let voter = context
@@ -748,6 +752,10 @@ async fn change_from_own_delegate_to_new_delegate_and_back_with_cooldown(
             6_000_000,
         )
         .await?;
+    context
+        .addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
 
     // CREATE VOTER
     let voter = context
@@ -985,6 +993,10 @@ async fn change_from_own_delegate_to_new_delegate_and_back_instantly() -> Result
             6_000_000,
         )
         .await?;
+    context
+        .addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
 
     // CREATE VOTER
     let voter = context
@@ -1086,3 +1098,450 @@ async fn change_from_own_delegate_to_new_delegate_and_back_instantly() -> Result
 
     Ok(())
 }
+
+#[tokio::test]
+async fn change_delegate_of_many_deposits() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let delegate_authority = &context.users[2].key;
+    let (delegate_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &delegate_authority.pubkey(),
+        &rewards_pool,
+    );
+    let delegate_voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            delegate_authority,
+            payer,
+            &rewards_pool,
+            &delegate_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+    for (deposit_entry_index, kind, period) in [
+        (0, LockupKind::None, LockupPeriod::None),
+        (1, LockupKind::Constant, LockupPeriod::OneYear),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &delegate_voter,
+                &delegate_voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                kind,
+                period,
+            )
+            .await?;
+    }
+    addin
+        .deposit(
+            &registrar,
+            &delegate_voter,
+            &mngo_voting_mint,
+            delegate_authority,
+            context.users[2].token_accounts[0],
+            0,
+            6_000_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &delegate_voter,
+            delegate_authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            6_000_000,
+        )
+        .await?;
+    let delegate_profile = addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            30_000,
+        )
+        .await?;
+    for deposit_entry_index in 1..=3 {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                LockupKind::Constant,
+                LockupPeriod::OneYear,
+            )
+            .await?;
+        addin
+            .stake(
+                &registrar,
+                &voter,
+                voter_authority.pubkey(),
+                &context.rewards.program_id,
+                0,
+                deposit_entry_index,
+                10_000,
+            )
+            .await?;
+    }
+
+    addin
+        .change_delegate_batch(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &deposit_mining,
+            &context.rewards.program_id,
+            vec![],
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::NoDepositEntryIndices);
+    addin
+        .change_delegate_batch(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &deposit_mining,
+            &context.rewards.program_id,
+            vec![1, 2, 1],
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DuplicateDepositEntryIndex);
+    addin
+        .change_delegate_batch(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &deposit_mining,
+            &context.rewards.program_id,
+            vec![1; Voter::MAX_DEPOSIT_ENTRIES + 1],
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::TooManyDepositEntryIndices);
+
+    advance_clock_by_ts(&mut context.solana.context.borrow_mut(), 5 * 86400 + 1).await;
+    addin
+        .change_delegate_batch(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &deposit_mining,
+            &context.rewards.program_id,
+            vec![1, 2, 3],
+        )
+        .await?;
+    for deposit_entry_index in 1..=3 {
+        let deposit = voter
+            .get_deposit_entry(&context.solana, deposit_entry_index)
+            .await;
+        assert_eq!(deposit.delegate, delegate_authority.pubkey());
+    }
+    let profile = context
+        .solana
+        .get_account::<DelegateProfile>(delegate_profile)
+        .await;
+    assert_eq!({ profile.accepted_stake }, 30_000);
+
+    // every deposit is throttled on its own
+    addin
+        .change_delegate_batch(
+            &registrar,
+            &voter,
+            &voter,
+            &delegate_mining,
+            &context.rewards.program_id,
+            vec![1, 2],
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateUpdateIsTooSoon);
+
+    advance_clock_by_ts(&mut context.solana.context.borrow_mut(), 5 * 86400 + 1).await;
+    addin
+        .change_delegate_batch(
+            &registrar,
+            &voter,
+            &voter,
+            &delegate_mining,
+            &context.rewards.program_id,
+            vec![1, 2],
+        )
+        .await?;
+    let deposit = voter.get_deposit_entry(&context.solana, 3).await;
+    assert_eq!(deposit.delegate, delegate_authority.pubkey());
+    let profile = context
+        .solana
+        .get_account::<DelegateProfile>(delegate_profile)
+        .await;
+    assert_eq!({ profile.accepted_stake }, 10_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn revoke_stale_delegation() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let delegate_authority = &context.users[2].key;
+    let (delegate_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &delegate_authority.pubkey(),
+        &rewards_pool,
+    );
+    let delegate_voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            delegate_authority,
+            payer,
+            &rewards_pool,
+            &delegate_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (voter, token_account, amount) in [
+        (
+            &delegate_voter,
+            context.users[2].token_accounts[0],
+            6_000_000,
+        ),
+        (&voter, context.users[1].token_accounts[0], 10_000),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                voter,
+                voter,
+                &mngo_voting_mint,
+                0,
+                LockupKind::None,
+                LockupPeriod::None,
+            )
+            .await?;
+        addin
+            .create_deposit_entry(
+                &registrar,
+                voter,
+                voter,
+                &mngo_voting_mint,
+                1,
+                LockupKind::Constant,
+                LockupPeriod::OneYear,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                voter,
+                &mngo_voting_mint,
+                &voter.authority,
+                token_account,
+                0,
+                amount,
+            )
+            .await?;
+        addin
+            .stake(
+                &registrar,
+                voter,
+                voter.authority.pubkey(),
+                &context.rewards.program_id,
+                0,
+                1,
+                amount,
+            )
+            .await?;
+    }
+    addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
+
+    // a deposit that isn't delegated has nothing to revoke
+    addin
+        .revoke_stale_delegation(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &context.rewards.program_id,
+            1,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::SameDelegate);
+
+    advance_clock_by_ts(&mut context.solana.context.borrow_mut(), 5 * 86400 + 1).await;
+    addin
+        .change_delegate(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &deposit_mining,
+            &context.rewards.program_id,
+            1,
+        )
+        .await?;
+
+    // the delegate voter must be the current delegate of the deposit
+    addin
+        .revoke_stale_delegation(&registrar, &voter, &voter, &context.rewards.program_id, 1)
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidDelegate);
+
+    // the delegate still owns enough weighted stake
+    addin
+        .revoke_stale_delegation(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &context.rewards.program_id,
+            1,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateStillQualified);
+
+    Ok(())
+}
@@ -330,6 +330,7 @@ async fn claim_is_restricted() -> Result<(), TransportError> {
             &claim_setup.registrar,
             &claim_setup.realm_authority,
             &claim_setup.voter,
+            0,
         )
         .await
         .unwrap();
@@ -388,6 +389,7 @@ async fn claim_is_allowed() -> Result<(), TransportError> {
             &claim_setup.registrar,
             &claim_setup.realm_authority,
             &claim_setup.voter,
+            0,
         )
         .await?;
 
@@ -417,6 +419,7 @@ async fn claim_is_allowed() -> Result<(), TransportError> {
             &claim_setup.registrar,
             &claim_setup.realm_authority,
             &claim_setup.voter,
+            0,
         )
         .await?;
 
@@ -0,0 +1,283 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{DelegateProfile, LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_delegate_profile() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let delegate_authority = &context.users[2].key;
+    let (delegate_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &delegate_authority.pubkey(),
+        &rewards_pool,
+    );
+    let delegate_voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            delegate_authority,
+            payer,
+            &rewards_pool,
+            &delegate_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+    for (deposit_entry_index, kind, period) in [
+        (0, LockupKind::None, LockupPeriod::None),
+        (1, LockupKind::Constant, LockupPeriod::OneYear),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &delegate_voter,
+                &delegate_voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                kind,
+                period,
+            )
+            .await?;
+    }
+    addin
+        .deposit(
+            &registrar,
+            &delegate_voter,
+            &mngo_voting_mint,
+            delegate_authority,
+            context.users[2].token_accounts[0],
+            0,
+            6_000_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &delegate_voter,
+            delegate_authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            6_000_000,
+        )
+        .await?;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10_000,
+        )
+        .await?;
+
+    // voters can only be chosen as a delegate once they opted in
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateProfileNotFound);
+
+    let delegate_profile = addin
+        .create_delegate_profile(
+            &registrar,
+            &delegate_voter,
+            payer,
+            5_000,
+            "https://example.com/delegate.json",
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await?;
+
+    // the delegate accepts at most its max stake
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            delegate_authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            6_000,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateStakeLimitExceeded);
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            delegate_authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            4_000,
+        )
+        .await?;
+    let profile = context
+        .solana
+        .get_account::<DelegateProfile>(delegate_profile)
+        .await;
+    assert_eq!({ profile.accepted_stake }, 4_000);
+    assert!(profile.active);
+
+    // an inactive delegate doesn't accept new stake
+    addin
+        .update_delegate_profile(
+            &registrar,
+            delegate_authority,
+            false,
+            5_000,
+            "https://example.com/delegate.json",
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            delegate_authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            1_000,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateNotActive);
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &mngo_voting_mint,
+            2,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateNotActive);
+
+    // but the stake it already accepted can still be unlocked
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            LockupPeriod::ThreeMonths.to_secs() as i64,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .unlock_tokens(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            1,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let profile = context
+        .solana
+        .get_account::<DelegateProfile>(delegate_profile)
+        .await;
+    assert_eq!({ profile.accepted_stake }, 0);
+
+    Ok(())
+}
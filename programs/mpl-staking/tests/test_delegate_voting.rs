@@ -0,0 +1,313 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{DelegatedStake, LockupKind, LockupPeriod, VotingPowerMode},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_delegate_voting() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let delegate_authority = &context.users[2].key;
+    let delegate_token_owner_record = realm
+        .create_token_owner_record(delegate_authority.pubkey(), payer)
+        .await;
+    let (delegate_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &delegate_authority.pubkey(),
+        &rewards_pool,
+    );
+    let delegate_voter = addin
+        .create_voter(
+            &registrar,
+            &delegate_token_owner_record,
+            delegate_authority,
+            payer,
+            &rewards_pool,
+            &delegate_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (voter, token_account, amount) in [
+        (
+            &delegate_voter,
+            context.users[2].token_accounts[0],
+            6_000_000,
+        ),
+        (&voter, context.users[1].token_accounts[0], 10_000),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                voter,
+                voter,
+                &mngo_voting_mint,
+                0,
+                LockupKind::None,
+                LockupPeriod::None,
+            )
+            .await?;
+        addin
+            .create_deposit_entry(
+                &registrar,
+                voter,
+                voter,
+                &mngo_voting_mint,
+                1,
+                LockupKind::Constant,
+                LockupPeriod::OneYear,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                voter,
+                &mngo_voting_mint,
+                &voter.authority,
+                token_account,
+                0,
+                amount,
+            )
+            .await?;
+        addin
+            .stake(
+                &registrar,
+                voter,
+                voter.authority.pubkey(),
+                &context.rewards.program_id,
+                0,
+                1,
+                amount,
+            )
+            .await?;
+    }
+
+    // delegated deposits don't get the lockup multiplier, which the max vote weight
+    // of the other voting power modes would count
+    addin
+        .set_voting_power_mode(
+            &registrar,
+            &realm_authority,
+            VotingPowerMode::PeriodMultiplier,
+        )
+        .await?;
+    addin
+        .set_delegate_voting(&registrar, &realm_authority, true)
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateVotingRequiresFlatVotingPower);
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .set_voting_power_mode(&registrar, &realm_authority, VotingPowerMode::Flat)
+        .await?;
+    addin
+        .set_delegate_voting(&registrar, &realm_authority, true)
+        .await?;
+    addin
+        .set_voting_power_mode(&registrar, &realm_authority, VotingPowerMode::Decaying)
+        .await
+        .assert_on_chain_err(MplStakingError::DelegateVotingRequiresFlatVotingPower);
+    let delegated_stake = addin
+        .create_delegated_stake(&registrar, &delegate_voter, payer)
+        .await?;
+    addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
+
+    // deposits only vote with their delegate once they're delegated again
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 10_000);
+
+    advance_clock_by_ts(&mut context.solana.context.borrow_mut(), 5 * 86400 + 1).await;
+    addin
+        .change_delegate(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &deposit_mining,
+            &context.rewards.program_id,
+            1,
+        )
+        .await?;
+    let deposit = voter.get_deposit_entry(&context.solana, 1).await;
+    assert_eq!({ deposit.delegated_vote_amount }, 10_000);
+    let delegated = context
+        .solana
+        .get_account::<DelegatedStake>(delegated_stake)
+        .await;
+    assert_eq!({ delegated.amounts }[0], 10_000);
+
+    context.solana.advance_clock_by_slots(2).await;
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 0);
+    let record = addin
+        .update_voter_weight_record(&registrar, &delegate_voter)
+        .await?;
+    assert_eq!(record.voter_weight, 6_000_000);
+    let record = addin
+        .update_voter_weight_record_with_delegated_stake(&registrar, &delegate_voter)
+        .await?;
+    assert_eq!(record.voter_weight, 6_010_000);
+
+    // turning delegate voting off leaves delegated deposits with their delegate
+    // until they're changed again
+    addin
+        .set_delegate_voting(&registrar, &realm_authority, false)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 0);
+    let record = addin
+        .update_voter_weight_record_with_delegated_stake(&registrar, &delegate_voter)
+        .await?;
+    assert_eq!(record.voter_weight, 6_010_000);
+
+    // the deposit can't be taken away from its delegate while the delegate has
+    // outstanding votes, since they were cast with its weight
+    let mint_governance = realm
+        .create_mint_governance(
+            context.mints[0].pubkey.unwrap(),
+            &context.mints[0].authority,
+            &voter,
+            voter_authority,
+            payer,
+            addin.update_voter_weight_record_instruction(&registrar, &voter),
+        )
+        .await;
+    let proposal = realm
+        .create_proposal(
+            mint_governance.address,
+            voter_authority,
+            &voter,
+            payer,
+            addin.update_voter_weight_record_instruction(&registrar, &voter),
+        )
+        .await?;
+    realm
+        .cast_vote(
+            mint_governance.address,
+            &proposal,
+            &delegate_voter,
+            delegate_authority,
+            payer,
+            addin.update_voter_weight_record_instruction(&registrar, &delegate_voter),
+        )
+        .await?;
+
+    advance_clock_by_ts(&mut context.solana.context.borrow_mut(), 5 * 86400 + 1).await;
+    let (old_delegate_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &delegate_authority.pubkey(),
+        &rewards_pool,
+    );
+    addin
+        .change_delegate(
+            &registrar,
+            &voter,
+            &voter,
+            &old_delegate_mining,
+            &context.rewards.program_id,
+            1,
+        )
+        .await
+        .expect_err("the delegate has an outstanding vote");
+
+    realm
+        .relinquish_vote(
+            mint_governance.address,
+            &proposal,
+            delegate_voter.token_owner_record,
+            delegate_authority,
+            payer.pubkey(),
+        )
+        .await?;
+
+    // moving the deposit back to its owner stops counting it for the delegate
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .change_delegate(
+            &registrar,
+            &voter,
+            &voter,
+            &old_delegate_mining,
+            &context.rewards.program_id,
+            1,
+        )
+        .await?;
+    let delegated = context
+        .solana
+        .get_account::<DelegatedStake>(delegated_stake)
+        .await;
+    assert_eq!({ delegated.amounts }[0], 0);
+    context.solana.advance_clock_by_slots(2).await;
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 10_000);
+
+    Ok(())
+}
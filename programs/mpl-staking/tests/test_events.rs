@@ -0,0 +1,314 @@
+use mpl_staking::events::*;
+use mplx_staking_states::state::{LockupKind, LockupPeriod, COOLDOWN_SECS};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+fn deserialize_event<T: anchor_lang::Event>(event: &str) -> Option<T> {
+    let data = base64::decode(event).ok()?;
+    if data.len() < 8 || data[0..8] != T::discriminator() {
+        return None;
+    }
+    T::try_from_slice(&data[8..]).ok()
+}
+
+fn find_event<T: anchor_lang::Event>(context: &TestContext) -> T {
+    context
+        .solana
+        .program_output()
+        .data
+        .iter()
+        .find_map(|data| deserialize_event::<T>(data))
+        .expect("event not emitted")
+}
+
+#[tokio::test]
+async fn test_events() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+    let mint = context.mints[0].pubkey.unwrap();
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (deposit_entry_index, kind, period) in [
+        (0, LockupKind::None, LockupPeriod::None),
+        (1, LockupKind::Constant, LockupPeriod::ThreeMonths),
+        (2, LockupKind::None, LockupPeriod::None),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                kind,
+                period,
+            )
+            .await?;
+    }
+
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            10_000,
+        )
+        .await?;
+    let event = find_event::<Deposited>(&context);
+    assert_eq!(event.voter, voter.address);
+    assert_eq!(event.deposit_entry_index, 0);
+    assert_eq!(event.mint, mint);
+    assert_eq!(event.amount, 10_000);
+    assert_eq!(event.amount_deposited_native, 10_000);
+    assert_eq!(event.lockup_kind, LockupKind::None);
+
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            6_000,
+        )
+        .await?;
+    let event = find_event::<Staked>(&context);
+    assert_eq!(event.source_deposit_entry_index, 0);
+    assert_eq!(event.target_deposit_entry_index, 1);
+    assert_eq!(event.mint, mint);
+    assert_eq!(event.amount, 6_000);
+    assert_eq!(event.lockup_period, LockupPeriod::ThreeMonths);
+    assert_eq!(
+        event.lockup_end_ts - event.lockup_start_ts,
+        LockupPeriod::ThreeMonths.to_secs()
+    );
+    assert_eq!(event.delegate, voter_authority.pubkey());
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            realm.community_token_account,
+            0,
+            1_000,
+        )
+        .await?;
+    let event = find_event::<Withdrawn>(&context);
+    assert_eq!(event.deposit_entry_index, 0);
+    assert_eq!(event.amount, 1_000);
+    assert_eq!(event.slashing_penalty, 0);
+    assert_eq!(event.amount_deposited_native, 3_000);
+
+    addin
+        .close_deposit_entry(&voter, voter_authority, 2)
+        .await?;
+    let event = find_event::<DepositEntryClosed>(&context);
+    assert_eq!(event.voter, voter.address);
+    assert_eq!(event.deposit_entry_index, 2);
+    assert_eq!(event.voting_mint_config_index, 0);
+
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            LockupPeriod::ThreeMonths.to_secs() as i64,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .unlock_tokens(
+            &registrar,
+            &voter,
+            &voter,
+            1,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let event = find_event::<TokensUnlocked>(&context);
+    assert_eq!(event.deposit_entry_index, 1);
+    assert_eq!(event.amount, 6_000);
+    assert_eq!(
+        event.cooldown_ends_at,
+        context.solana.get_clock().await.unix_timestamp as u64 + COOLDOWN_SECS
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_grant_events() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+    let grant_authority = &context.users[2].key;
+    let grant_funds = context.users[2].token_accounts[0];
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, _) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            Some(grant_authority.pubkey()),
+            None,
+        )
+        .await;
+    let mint = context.mints[0].pubkey.unwrap();
+
+    let voter = addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Daily,
+            LockupPeriod::None,
+            10,
+            true,
+            10_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let event = find_event::<Granted>(&context);
+    assert_eq!(event.voter, voter.address);
+    assert_eq!(event.voter_authority, voter_authority.pubkey());
+    assert_eq!(event.grant_authority, grant_authority.pubkey());
+    assert_eq!(event.deposit_entry_index, 0);
+    assert_eq!(event.mint, mint);
+    assert_eq!(event.amount, 10_000);
+    assert_eq!(event.lockup_kind, LockupKind::Daily);
+    assert_eq!(
+        event.lockup_end_ts - event.lockup_start_ts,
+        10 * 24 * 60 * 60
+    );
+    assert!(event.allow_clawback);
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 4 * 24 * 60 * 60)
+        .await;
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            0,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let event = find_event::<ClawedBack>(&context);
+    assert_eq!(event.voter, voter.address);
+    assert_eq!(event.deposit_entry_index, 0);
+    assert_eq!(event.mint, mint);
+    assert_eq!(event.amount, 6_000);
+    assert_eq!(event.amount_deposited_native, 4_000);
+    assert_eq!(event.destination, grant_funds);
+
+    Ok(())
+}
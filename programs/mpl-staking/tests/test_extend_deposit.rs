@@ -955,6 +955,10 @@ async fn prolongs_with_delegate() -> Result<(), TransportError> {
             6_000_000,
         )
         .await?;
+    context
+        .addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
 
     // CREATE VOTER
     let voter = context
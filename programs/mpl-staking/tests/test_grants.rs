@@ -0,0 +1,730 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_grant_and_clawback() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+    let grant_authority = &context.users[2].key;
+    let grant_funds = context.users[2].token_accounts[0];
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, _) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            Some(grant_authority.pubkey()),
+            None,
+        )
+        .await;
+
+    let initial_funds = context.solana.token_account_balance(grant_funds).await;
+
+    // only the grant authority may grant
+    addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            payer,
+            context.users[0].token_accounts[0],
+            payer,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+            0,
+            true,
+            10_000,
+            &context.rewards.program_id,
+        )
+        .await
+        .expect_err("only the grant authority can grant");
+
+    // the grant creates the voter
+    let voter = addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+            0,
+            true,
+            10_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let deposit_entry = voter.get_deposit_entry(&context.solana, 0).await;
+    assert!(deposit_entry.is_used);
+    assert!(deposit_entry.allow_clawback);
+    assert_eq!(deposit_entry.lockup.kind, LockupKind::Constant);
+    assert_eq!(deposit_entry.lockup.period, LockupPeriod::ThreeMonths);
+    assert_eq!({ deposit_entry.amount_deposited_native }, 10_000);
+    assert_eq!(deposit_entry.delegate, voter_authority.pubkey());
+    assert_eq!(
+        mngo_voting_mint
+            .vault_balance(&context.solana, &voter)
+            .await,
+        10_000
+    );
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 10_000);
+
+    // grants to an existing voter use the next free deposit entry
+    addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Constant,
+            LockupPeriod::OneYear,
+            0,
+            false,
+            5_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let deposit_entry = voter.get_deposit_entry(&context.solana, 1).await;
+    assert!(deposit_entry.is_used);
+    assert!(!deposit_entry.allow_clawback);
+    assert_eq!({ deposit_entry.amount_deposited_native }, 5_000);
+
+    // only the grant authority may claw back, and only if allowed
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            payer,
+            context.users[0].token_accounts[0],
+            0,
+            &context.rewards.program_id,
+        )
+        .await
+        .expect_err("only the grant authority can claw back");
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            1,
+            &context.rewards.program_id,
+        )
+        .await
+        .expect_err("the grant doesn't allow clawback");
+
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            0,
+            &context.rewards.program_id,
+        )
+        .await?;
+    assert!(!voter.get_deposit_entry(&context.solana, 0).await.is_used);
+    assert_eq!(
+        context.solana.token_account_balance(grant_funds).await,
+        initial_funds - 5_000
+    );
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 5_000);
+
+    // vested grants can't be clawed back
+    addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Constant,
+            LockupPeriod::Flex,
+            0,
+            true,
+            1_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    addin
+        .set_time_offset(&registrar, &realm_authority, 6 * 24 * 60 * 60)
+        .await;
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            0,
+            &context.rewards.program_id,
+        )
+        .await
+        .expect_err("the grant has vested");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_clawback_vesting_grant() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+    let grant_authority = &context.users[2].key;
+    let grant_funds = context.users[2].token_accounts[0];
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, _) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            Some(grant_authority.pubkey()),
+            None,
+        )
+        .await;
+
+    let initial_funds = context.solana.token_account_balance(grant_funds).await;
+    let initial_voter_funds = context.solana.token_account_balance(voter_mngo).await;
+
+    // vesting grants need a number of periods, not a lockup period
+    addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Monthly,
+            LockupPeriod::OneYear,
+            12,
+            true,
+            12_000,
+            &context.rewards.program_id,
+        )
+        .await
+        .expect_err("vesting grants don't have a lockup period");
+    addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Monthly,
+            LockupPeriod::None,
+            12,
+            true,
+            0,
+            &context.rewards.program_id,
+        )
+        .await
+        .expect_err("a grant must not be empty");
+
+    let voter = addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Monthly,
+            LockupPeriod::None,
+            12,
+            true,
+            12_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let deposit_entry = voter.get_deposit_entry(&context.solana, 0).await;
+    assert_eq!(deposit_entry.lockup.kind, LockupKind::Monthly);
+    assert_eq!(deposit_entry.lockup.periods_total().unwrap(), 12);
+    assert_eq!({ deposit_entry.amount_initially_locked_native }, 12_000);
+
+    // the voter can't add own tokens the grant authority could claw back
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            1_000,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::DepositingIsForbidded);
+
+    // three of twelve months have vested
+    addin
+        .set_time_offset(&registrar, &realm_authority, 90 * 24 * 60 * 60 + 1)
+        .await;
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            0,
+            &context.rewards.program_id,
+        )
+        .await?;
+    assert_eq!(
+        context.solana.token_account_balance(grant_funds).await,
+        initial_funds - 3_000
+    );
+
+    // the vested tokens stay with the voter
+    let deposit_entry = voter.get_deposit_entry(&context.solana, 0).await;
+    assert!(deposit_entry.is_used);
+    assert!(!deposit_entry.allow_clawback);
+    assert_eq!({ deposit_entry.amount_deposited_native }, 3_000);
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 3_000);
+
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            0,
+            &context.rewards.program_id,
+        )
+        .await
+        .expect_err("the grant was already clawed back");
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            realm.community_token_account,
+            0,
+            3_000,
+        )
+        .await?;
+    assert_eq!(
+        context.solana.token_account_balance(voter_mngo).await,
+        initial_voter_funds + 3_000
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_grant_with_existing_mining() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+    let grant_authority = &context.users[2].key;
+    let grant_funds = context.users[2].token_accounts[0];
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            Some(grant_authority.pubkey()),
+            None,
+        )
+        .await;
+
+    // anyone may initialize the mining account of the voter before it exists
+    context
+        .rewards
+        .initialize_mining(&rewards_pool, &voter_authority.pubkey(), payer)
+        .await?;
+
+    let voter = addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+            0,
+            true,
+            10_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let deposit_entry = voter.get_deposit_entry(&context.solana, 0).await;
+    assert!(deposit_entry.is_used);
+    assert_eq!({ deposit_entry.amount_deposited_native }, 10_000);
+
+    // the same goes for a voter that is closed and granted to again
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            0,
+            &context.rewards.program_id,
+        )
+        .await?;
+    addin
+        .close_voter(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            &context.rewards.program_id,
+        )
+        .await?;
+    context
+        .rewards
+        .initialize_mining(&rewards_pool, &voter_authority.pubkey(), payer)
+        .await?;
+    addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+            0,
+            true,
+            10_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    assert!(voter.get_deposit_entry(&context.solana, 0).await.is_used);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_clawback_delegated_grant() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+    let grant_authority = &context.users[2].key;
+    let grant_funds = context.users[2].token_accounts[0];
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            Some(grant_authority.pubkey()),
+            None,
+        )
+        .await;
+
+    // CREATE DELEGATE
+    let delegate_authority = &context.users[3].key;
+    let delegate_token_account = context.users[3].token_accounts[0];
+    let (delegate_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &delegate_authority.pubkey(),
+        &rewards_pool,
+    );
+    let delegate_voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            delegate_authority,
+            payer,
+            &rewards_pool,
+            &delegate_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &delegate_voter,
+            &delegate_voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &delegate_voter,
+            &delegate_voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Constant,
+            LockupPeriod::OneYear,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &delegate_voter,
+            &mngo_voting_mint,
+            delegate_authority,
+            delegate_token_account,
+            0,
+            6_000_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &delegate_voter,
+            delegate_authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            6_000_000,
+        )
+        .await?;
+    addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
+
+    let initial_funds = context.solana.token_account_balance(grant_funds).await;
+    let voter = addin
+        .grant(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            payer,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+            0,
+            true,
+            10_000,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+
+    // the delegate of a grant can be changed like for any other stake
+    addin
+        .set_time_offset(&registrar, &realm_authority, 6 * 24 * 60 * 60)
+        .await;
+    addin
+        .change_delegate(
+            &registrar,
+            &voter,
+            &delegate_voter,
+            &deposit_mining,
+            &context.rewards.program_id,
+            0,
+        )
+        .await?;
+    assert_eq!(
+        voter.get_deposit_entry(&context.solana, 0).await.delegate,
+        delegate_authority.pubkey()
+    );
+
+    // the clawback takes the stake away from the mining of the delegate too
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            grant_authority,
+            grant_funds,
+            0,
+            &context.rewards.program_id,
+        )
+        .await?;
+    assert!(!voter.get_deposit_entry(&context.solana, 0).await.is_used);
+    assert_eq!(
+        context.solana.token_account_balance(grant_funds).await,
+        initial_funds
+    );
+
+    // the delegate can still unlock its own stake, which is all that is left on its mining
+    addin
+        .set_time_offset(&registrar, &realm_authority, 366 * 24 * 60 * 60)
+        .await;
+    addin
+        .unlock_tokens(
+            &registrar,
+            &delegate_voter,
+            &delegate_voter,
+            1,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod, Registrar},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_grow_registrar() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+    addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            1,
+            &context.mints[1],
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+
+    // only the realm authority may grow the registrar, and only up to the max
+    addin
+        .grow_registrar(&registrar, voter_authority, payer, 1)
+        .await
+        .expect_err("only the realm authority can grow the registrar");
+    addin
+        .grow_registrar(
+            &registrar,
+            &realm_authority,
+            payer,
+            (Registrar::MAX_VOTING_MINTS - 1) as u8,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::TooManyVotingMints);
+
+    addin
+        .grow_registrar(&registrar, &realm_authority, payer, 1)
+        .await?;
+    let data = context.solana.get_account_data(registrar.address).await;
+    assert_eq!(data.len(), Registrar::space(3));
+
+    // the configured voting mints keep their indexes
+    let lp_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            2,
+            &context.mints[2],
+            None,
+            Some(&[
+                context.mints[0].pubkey.unwrap(),
+                context.mints[1].pubkey.unwrap(),
+            ]),
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (deposit_entry_index, voting_mint, token_account, amount) in [
+        (
+            0,
+            &mngo_voting_mint,
+            context.users[1].token_accounts[0],
+            10_000,
+        ),
+        (
+            1,
+            &lp_voting_mint,
+            context.users[1].token_accounts[2],
+            3_000,
+        ),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                voting_mint,
+                deposit_entry_index,
+                LockupKind::None,
+                LockupPeriod::None,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                voting_mint,
+                voter_authority,
+                token_account,
+                deposit_entry_index,
+                amount,
+            )
+            .await?;
+    }
+    let deposit = voter.get_deposit_entry(&context.solana, 0).await;
+    assert_eq!(deposit.voting_mint_config_idx, 0);
+    let deposit = voter.get_deposit_entry(&context.solana, 1).await;
+    assert_eq!(deposit.voting_mint_config_idx, 2);
+
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 13_000);
+
+    // growing again keeps the voting mints that are already configured
+    addin
+        .grow_registrar(&registrar, &realm_authority, payer, 2)
+        .await?;
+    let data = context.solana.get_account_data(registrar.address).await;
+    assert_eq!(data.len(), Registrar::space(5));
+    context.solana.advance_clock_by_slots(2).await;
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 13_000);
+
+    Ok(())
+}
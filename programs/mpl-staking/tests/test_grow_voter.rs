@@ -0,0 +1,139 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_grow_voter() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    let create_deposit_entry = |deposit_entry_index: u8| {
+        addin.create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            deposit_entry_index,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+    };
+
+    assert_eq!(voter.get_deposits_len(&context.solana).await, 32);
+    create_deposit_entry(32)
+        .await
+        .assert_on_chain_err(MplStakingError::OutOfBoundsDepositEntryIndex);
+
+    addin.grow_voter(&voter, payer, 16).await?;
+    assert_eq!(voter.get_deposits_len(&context.solana).await, 48);
+
+    // the new deposit entries work like the ones stored in the voter
+    create_deposit_entry(40).await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            40,
+            5_000,
+        )
+        .await?;
+    let deposit = voter.get_deposit_entry(&context.solana, 40).await;
+    assert!(deposit.is_used);
+    assert_eq!({ deposit.amount_deposited_native }, 5_000);
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 5_000);
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            realm.community_token_account,
+            40,
+            2_000,
+        )
+        .await?;
+    let deposit = voter.get_deposit_entry(&context.solana, 40).await;
+    assert_eq!({ deposit.amount_deposited_native }, 3_000);
+
+    // deposit entries are addressed by a u8
+    addin
+        .grow_voter(&voter, payer, 255)
+        .await
+        .assert_on_chain_err(MplStakingError::TooManyDepositEntries);
+
+    Ok(())
+}
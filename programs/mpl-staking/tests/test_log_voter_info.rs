@@ -1,5 +1,5 @@
 use anchor_spl::token::TokenAccount;
-use mplx_staking_states::state::{LockupKind, LockupPeriod};
+use mplx_staking_states::state::{LockupKind, LockupPeriod, COOLDOWN_SECS};
 use program_test::*;
 use solana_program_test::*;
 use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
@@ -149,13 +149,14 @@ async fn test_log_voter_info() -> Result<(), TransportError> {
         .await;
     context.solana.advance_clock_by_slots(2).await;
 
-    addin.log_voter_info(&registrar, &voter, 0).await;
+    addin.log_voter_info(&registrar, &voter, 0, None).await;
     let data_log = context.solana.program_output().data;
     assert_eq!(data_log.len(), 3);
 
     let voter_event = deserialize_event::<mpl_staking::events::VoterInfo>(&data_log[0]).unwrap();
     assert_eq!(voter_event.voting_power_baseline, 12000);
     assert_eq!(voter_event.voting_power, 12000);
+    assert!(voter_event.voting_power_locked_guaranteed.is_none());
 
     let deposit_event =
         deserialize_event::<mpl_staking::events::DepositEntryInfo>(&data_log[1]).unwrap();
@@ -174,6 +175,27 @@ async fn test_log_voter_info() -> Result<(), TransportError> {
     let locking = deposit_event.locking.unwrap();
     assert!(locking.vesting.is_none());
     assert_eq!(locking.amount, 12000);
+    let end_ts = locking.end_timestamp.unwrap();
+
+    // the staked tokens are still locked up a month later
+    let curr_ts = context.solana.get_clock().await.unix_timestamp as u64;
+    addin
+        .log_voter_info(&registrar, &voter, 0, Some(curr_ts + 30 * 24 * 60 * 60))
+        .await;
+    let data_log = context.solana.program_output().data;
+    let voter_event = deserialize_event::<mpl_staking::events::VoterInfo>(&data_log[0]).unwrap();
+    assert_eq!(voter_event.voting_power_locked_guaranteed, Some(12000));
+    let deposit_event =
+        deserialize_event::<mpl_staking::events::DepositEntryInfo>(&data_log[1]).unwrap();
+    assert_eq!(deposit_event.voting_power_locked_guaranteed, Some(0));
+
+    // but may be withdrawn once the lockup and the cooldown are over
+    addin
+        .log_voter_info(&registrar, &voter, 0, Some(end_ts + COOLDOWN_SECS))
+        .await;
+    let data_log = context.solana.program_output().data;
+    let voter_event = deserialize_event::<mpl_staking::events::VoterInfo>(&data_log[0]).unwrap();
+    assert_eq!(voter_event.voting_power_locked_guaranteed, Some(0));
 
     Ok(())
 }
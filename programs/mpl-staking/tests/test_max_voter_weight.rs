@@ -0,0 +1,190 @@
+use mplx_staking_states::state::{LockupKind, LockupPeriod};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_max_voter_weight() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let voter_usdc = context.users[1].token_accounts[1];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+    let usdc_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            1,
+            &context.mints[1],
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+    let voting_mints = [
+        context.mints[0].pubkey.unwrap(),
+        context.mints[1].pubkey.unwrap(),
+    ];
+
+    // the test mints start out without supply
+    context
+        .solana
+        .mint_to(
+            &voting_mints[0],
+            &context.mints[0].authority,
+            &voter_mngo,
+            10_000,
+        )
+        .await;
+    context
+        .solana
+        .mint_to(
+            &voting_mints[1],
+            &context.mints[1].authority,
+            &voter_usdc,
+            2_000,
+        )
+        .await;
+
+    // all voting mints are required
+    addin
+        .create_max_voter_weight_record(&registrar, payer, &voting_mints[..1])
+        .await
+        .expect_err("all voting mints must be passed");
+
+    let max_voter_weight_record = addin
+        .create_max_voter_weight_record(&registrar, payer, &voting_mints)
+        .await?;
+    let record = context
+        .solana
+        .get_account::<mpl_staking::voter::MaxVoterWeightRecord>(max_voter_weight_record)
+        .await;
+    assert_eq!(record.realm, realm.realm);
+    assert_eq!(
+        record.governing_token_mint,
+        context.mints[0].pubkey.unwrap()
+    );
+    assert_eq!(record.max_voter_weight, 12_000);
+
+    // the record can only be created once
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .create_max_voter_weight_record(&registrar, payer, &voting_mints)
+        .await
+        .expect_err("record already exists");
+
+    context
+        .solana
+        .mint_to(
+            &voting_mints[0],
+            &context.mints[0].authority,
+            &voter_mngo,
+            5_000,
+        )
+        .await;
+    let record = addin
+        .update_max_voter_weight_record(&registrar, &voting_mints)
+        .await?;
+    assert_eq!(record.max_voter_weight, 17_000);
+    assert_eq!(
+        record.max_voter_weight_expiry,
+        Some(context.solana.get_clock().await.slot)
+    );
+    assert_eq!(
+        addin.get_max_vote_weight(&registrar, &voting_mints).await?,
+        17_000
+    );
+
+    // a voter holding the whole supply has the max vote weight
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+    for (deposit_entry_index, voting_mint, token_account, amount) in [
+        (0, &mngo_voting_mint, voter_mngo, 15_000),
+        (1, &usdc_voting_mint, voter_usdc, 2_000),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                voting_mint,
+                deposit_entry_index,
+                LockupKind::None,
+                LockupPeriod::None,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                voting_mint,
+                voter_authority,
+                token_account,
+                deposit_entry_index,
+                amount,
+            )
+            .await?;
+    }
+    let voter_weight_record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(voter_weight_record.voter_weight, record.max_voter_weight);
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_merge_deposits() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (deposit_entry_index, kind, period) in [
+        (0, LockupKind::None, LockupPeriod::None),
+        (1, LockupKind::Constant, LockupPeriod::SixMonths),
+        (2, LockupKind::Constant, LockupPeriod::ThreeMonths),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                kind,
+                period,
+            )
+            .await?;
+    }
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            10_000,
+        )
+        .await?;
+    for (target_deposit_entry_index, amount) in [(1, 6_000), (2, 4_000)] {
+        addin
+            .stake(
+                &registrar,
+                &voter,
+                voter.authority.pubkey(),
+                &context.rewards.program_id,
+                0,
+                target_deposit_entry_index,
+                amount,
+            )
+            .await?;
+    }
+
+    let merge = |source_deposit_entry_index: u8, target_deposit_entry_index: u8| {
+        addin.merge_deposits(
+            &registrar,
+            &voter,
+            &voter,
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+    };
+
+    merge(1, 1)
+        .await
+        .assert_on_chain_err(MplStakingError::DepositsNotMergeable);
+    merge(0, 1)
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidLockupKind);
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 30 * 86_400)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    merge(2, 1).await?;
+
+    // the merged deposit is locked up for the stricter period again, starting now
+    let merged_at = context.solana.get_clock().await.unix_timestamp as u64;
+    let merged = voter.get_deposit_entry(&context.solana, 1).await;
+    assert_eq!({ merged.amount_deposited_native }, 10_000);
+    assert_eq!({ merged.lockup.period }, LockupPeriod::SixMonths);
+    assert_eq!({ merged.lockup.start_ts }, merged_at);
+    assert_eq!(
+        { merged.lockup.end_ts },
+        merged_at + LockupPeriod::SixMonths.to_secs()
+    );
+
+    // the source entry is free again
+    let source = voter.get_deposit_entry(&context.solana, 2).await;
+    assert!(!source.is_used);
+
+    // merging freshly staked tokens into a nearly expired long lockup doesn't let them
+    // unlock with it
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            1_000,
+        )
+        .await?;
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            30 * 86_400 + LockupPeriod::SixMonths.to_secs() as i64 - 86_400,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            2,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            2,
+            1_000,
+        )
+        .await?;
+    let source_end_ts = voter
+        .get_deposit_entry(&context.solana, 2)
+        .await
+        .lockup
+        .end_ts;
+    context.solana.advance_clock_by_slots(2).await;
+    merge(2, 1).await?;
+
+    let merged_at = context.solana.get_clock().await.unix_timestamp as u64;
+    let merged = voter.get_deposit_entry(&context.solana, 1).await;
+    assert_eq!({ merged.amount_deposited_native }, 11_000);
+    assert_eq!({ merged.lockup.period }, LockupPeriod::SixMonths);
+    assert_eq!({ merged.lockup.start_ts }, merged_at);
+    assert_eq!(
+        { merged.lockup.end_ts },
+        merged_at + LockupPeriod::SixMonths.to_secs()
+    );
+    assert!({ merged.lockup.end_ts } > source_end_ts);
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod, COOLDOWN_SECS},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_partial_unlock() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (deposit_entry_index, kind, period) in [
+        (0, LockupKind::None, LockupPeriod::None),
+        (1, LockupKind::Constant, LockupPeriod::ThreeMonths),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                kind,
+                period,
+            )
+            .await?;
+    }
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            10_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            10_000,
+        )
+        .await?;
+
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            LockupPeriod::ThreeMonths.to_secs() as i64,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    let unlock = |amount: u64| {
+        addin.unlock_tokens_amount(
+            &registrar,
+            &voter,
+            &voter,
+            1,
+            &rewards_pool,
+            &context.rewards.program_id,
+            amount,
+        )
+    };
+
+    unlock(0)
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidAmount);
+    unlock(10_001)
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidAmount);
+
+    // the unlocked part moves into the first free entry, the rest stays staked
+    unlock(4_000).await?;
+    let staked = voter.get_deposit_entry(&context.solana, 1).await;
+    assert_eq!({ staked.amount_deposited_native }, 6_000);
+    assert!(!{ staked.lockup.cooldown_requested });
+    let cooling_down = voter.get_deposit_entry(&context.solana, 2).await;
+    assert!(cooling_down.is_used);
+    assert_eq!({ cooling_down.amount_deposited_native }, 4_000);
+    assert_eq!(cooling_down.lockup.kind, LockupKind::Constant);
+    assert!({ cooling_down.lockup.cooldown_requested });
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            realm.community_token_account,
+            2,
+            4_000,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidTimestampArguments);
+
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            (LockupPeriod::ThreeMonths.to_secs() + COOLDOWN_SECS) as i64,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            realm.community_token_account,
+            2,
+            4_000,
+        )
+        .await?;
+    let cooling_down = voter.get_deposit_entry(&context.solana, 2).await;
+    assert_eq!({ cooling_down.amount_deposited_native }, 0);
+
+    Ok(())
+}
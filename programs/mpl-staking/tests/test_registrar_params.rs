@@ -0,0 +1,169 @@
+use mplx_staking_states::state::{LockupKind, LockupPeriod, SECONDS_PER_DAY};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+fn deserialize_event<T: anchor_lang::Event>(event: &str) -> Option<T> {
+    let data = base64::decode(event).ok()?;
+    if data.len() < 8 || data[0..8] != T::discriminator() {
+        return None;
+    }
+    T::try_from_slice(&data[8..]).ok()
+}
+
+#[tokio::test]
+async fn test_registrar_params() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    let cooldown_secs = SECONDS_PER_DAY;
+
+    // only the realm authority may change the params
+    addin
+        .set_registrar_params(&registrar, voter_authority, cooldown_secs, 0, 0)
+        .await
+        .expect_err("only the realm authority can change the params");
+
+    addin
+        .set_registrar_params(&registrar, &realm_authority, cooldown_secs, 0, 0)
+        .await?;
+    let data_log = context.solana.program_output().data;
+    let event =
+        deserialize_event::<mpl_staking::events::RegistrarParamsChanged>(&data_log[0]).unwrap();
+    assert_eq!(event.registrar, registrar.address);
+    assert_eq!(event.cooldown_secs, cooldown_secs);
+    assert_eq!(event.delegate_update_diff_threshold, 0);
+    assert_eq!(event.min_own_weighted_stake, 0);
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Constant,
+            LockupPeriod::Flex,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            10_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            10_000,
+        )
+        .await?;
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 6 * SECONDS_PER_DAY as i64)
+        .await;
+    addin
+        .unlock_tokens(
+            &registrar,
+            &voter,
+            &voter,
+            1,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+        .await?;
+
+    // the cooldown lasts as long as configured
+    let curr_ts = context.solana.get_clock().await.unix_timestamp as u64;
+    let deposit_entry = voter.get_deposit_entry(&context.solana, 1).await;
+    assert_eq!(
+        { deposit_entry.lockup.cooldown_ends_at },
+        curr_ts + cooldown_secs
+    );
+
+    Ok(())
+}
@@ -0,0 +1,234 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_remove_voting_mint() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_usdc = context.users[1].token_accounts[1];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    // voting mints of a registrar that hasn't been grown can't be removed
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 0)
+        .await
+        .assert_on_chain_err(MplStakingError::RegistrarNotGrown);
+
+    addin
+        .grow_registrar(&registrar, &realm_authority, payer, 1)
+        .await?;
+    let usdc_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            2,
+            &context.mints[1],
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+
+    // the deposits of voting mints configured before growing aren't counted
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 0)
+        .await
+        .assert_on_chain_err(MplStakingError::VotingMintHasDeposits);
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 1)
+        .await
+        .assert_on_chain_err(MplStakingError::VotingMintNotFound);
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &usdc_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &usdc_voting_mint,
+            voter_authority,
+            voter_usdc,
+            0,
+            1_000,
+        )
+        .await?;
+
+    addin
+        .remove_voting_mint(&registrar, voter_authority, 2)
+        .await
+        .expect_err("only the realm authority can remove a voting mint");
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 2)
+        .await
+        .assert_on_chain_err(MplStakingError::VotingMintHasDeposits);
+
+    // an empty deposit entry still counts until it's closed
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &usdc_voting_mint,
+            voter_authority,
+            voter_usdc,
+            realm.community_token_account,
+            0,
+            1_000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 2)
+        .await
+        .assert_on_chain_err(MplStakingError::VotingMintHasDeposits);
+
+    addin
+        .close_deposit_entry(&voter, voter_authority, 0)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 2)
+        .await?;
+
+    // the removed mint can't be deposited anymore and its index can be reused
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &usdc_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::VotingMintNotFound);
+    addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            2,
+            &context.mints[2],
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+
+    // once the realm authority has backfilled its count, a voting mint configured
+    // before growing is tracked like any other
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .recount_voting_mint_deposits(&registrar, voter_authority, 0, 1)
+        .await
+        .expect_err("only the realm authority can recount deposit entries");
+    addin
+        .recount_voting_mint_deposits(&registrar, &realm_authority, 0, 1)
+        .await?;
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 0)
+        .await
+        .assert_on_chain_err(MplStakingError::VotingMintHasDeposits);
+
+    // the count can't be lowered below the deposit entries counted already
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .recount_voting_mint_deposits(&registrar, &realm_authority, 0, 0)
+        .await
+        .assert_on_chain_err(MplStakingError::DepositEntriesUndercounted);
+
+    addin
+        .close_deposit_entry(&voter, voter_authority, 1)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .remove_voting_mint(&registrar, &realm_authority, 0)
+        .await?;
+
+    Ok(())
+}
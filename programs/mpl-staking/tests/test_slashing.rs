@@ -1,6 +1,6 @@
 use anchor_spl::token::TokenAccount;
 use mpl_common_constants::constants::REALM_NAME;
-use mplx_staking_states::state::{LockupKind, LockupPeriod};
+use mplx_staking_states::state::{LockupKind, LockupPeriod, PenaltyKind};
 use program_test::*;
 use solana_program_test::*;
 use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
@@ -150,12 +150,21 @@ async fn slash_success() -> Result<(), TransportError> {
             5000,
             &voter_authority.pubkey(),
             &context.rewards.program_id,
+            7,
         )
         .await
         .unwrap();
     let deposit_entry = voter.get_deposit_entry(&context.solana, 1).await;
     assert_eq!(deposit_entry.slashing_penalty, 5000);
 
+    // the slashing is kept in the voter's penalty log
+    let voter_account = voter.get_voter(&context.solana).await;
+    let penalties: Vec<_> = voter_account
+        .recent_penalties()
+        .map(|record| (record.kind, record.reason))
+        .collect();
+    assert_eq!(penalties, [(PenaltyKind::Slash, 7)]);
+
     let voter_authority_ata = context
         .rewards
         .solana
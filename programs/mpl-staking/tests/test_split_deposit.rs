@@ -0,0 +1,168 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_split_deposit() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (deposit_entry_index, kind, period) in [
+        (0, LockupKind::None, LockupPeriod::None),
+        (1, LockupKind::Constant, LockupPeriod::ThreeMonths),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                &mngo_voting_mint,
+                deposit_entry_index,
+                kind,
+                period,
+            )
+            .await?;
+    }
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            10_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            10_000,
+        )
+        .await?;
+
+    addin
+        .split_deposit(&voter, voter_authority, 0, 1_000)
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidLockupKind);
+    addin
+        .split_deposit(&voter, voter_authority, 1, 10_000)
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidAmount);
+
+    addin
+        .split_deposit(&voter, voter_authority, 1, 4_000)
+        .await?;
+    let original = voter.get_deposit_entry(&context.solana, 1).await;
+    let split = voter.get_deposit_entry(&context.solana, 2).await;
+    assert_eq!({ original.amount_deposited_native }, 6_000);
+    assert!(split.is_used);
+    assert_eq!({ split.amount_deposited_native }, 4_000);
+    assert_eq!(
+        split.voting_mint_config_idx,
+        original.voting_mint_config_idx
+    );
+    assert_eq!({ split.delegate }, { original.delegate });
+    assert_eq!({ split.lockup.period }, { original.lockup.period });
+    assert_eq!({ split.lockup.start_ts }, { original.lockup.start_ts });
+    assert_eq!({ split.lockup.end_ts }, { original.lockup.end_ts });
+
+    // both parts can be unlocked on their own
+    addin
+        .set_time_offset(
+            &registrar,
+            &realm_authority,
+            LockupPeriod::ThreeMonths.to_secs() as i64,
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .unlock_tokens(
+            &registrar,
+            &voter,
+            &voter,
+            2,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+        .await?;
+    let original = voter.get_deposit_entry(&context.solana, 1).await;
+    let split = voter.get_deposit_entry(&context.solana, 2).await;
+    assert!(!{ original.lockup.cooldown_requested });
+    assert!({ split.lockup.cooldown_requested });
+
+    Ok(())
+}
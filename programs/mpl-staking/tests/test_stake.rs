@@ -155,6 +155,10 @@ async fn stake_with_delegate() -> Result<(), TransportError> {
             6_000_000,
         )
         .await?;
+    context
+        .addin
+        .create_delegate_profile(&registrar, &delegate_voter, payer, u64::MAX, "")
+        .await?;
 
     // Create voter and stake with delegate
     // test deposit and stake
@@ -0,0 +1,180 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_vesting() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    // Flex lasts five days, so a fifth of the tokens vests every day
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::Daily,
+            LockupPeriod::Flex,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            5_000,
+        )
+        .await?;
+
+    let withdraw = |amount: u64| {
+        addin.withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            realm.community_token_account,
+            0,
+            amount,
+        )
+    };
+
+    withdraw(1)
+        .await
+        .assert_on_chain_err(MplStakingError::InsufficientUnlockedTokens);
+
+    // vesting deposits don't need to be unlocked
+    addin
+        .unlock_tokens(
+            &registrar,
+            &voter,
+            &voter,
+            0,
+            &rewards_pool,
+            &context.rewards.program_id,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidLockupKind);
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 86_400)
+        .await;
+    withdraw(1_001)
+        .await
+        .assert_on_chain_err(MplStakingError::InsufficientUnlockedTokens);
+    withdraw(1_000).await?;
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 3 * 86_400)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    withdraw(2_000).await?;
+    let deposit = voter.get_deposit_entry(&context.solana, 0).await;
+    assert_eq!({ deposit.amount_deposited_native }, 2_000);
+
+    // the remaining tokens vest over the remaining days, together with new ones
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            2_000,
+        )
+        .await?;
+    withdraw(1)
+        .await
+        .assert_on_chain_err(MplStakingError::InsufficientUnlockedTokens);
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 4 * 86_400)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    withdraw(2_000).await?;
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 5 * 86_400)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    withdraw(2_000).await?;
+
+    let deposit = voter.get_deposit_entry(&context.solana, 0).await;
+    assert_eq!({ deposit.amount_deposited_native }, 0);
+
+    Ok(())
+}
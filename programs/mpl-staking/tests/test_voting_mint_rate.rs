@@ -0,0 +1,205 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{LockupKind, LockupPeriod, Registrar, VotingMint},
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_voting_mint_rate() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+    let voting_mints = [
+        context.mints[0].pubkey.unwrap(),
+        context.mints[1].pubkey.unwrap(),
+        context.mints[2].pubkey.unwrap(),
+    ];
+
+    // a scaled factor of 0 would silently remove the vote weight of the mint
+    addin
+        .configure_voting_mint_with_rate(
+            &registrar,
+            &realm_authority,
+            payer,
+            1,
+            &context.mints[1],
+            None,
+            Some(&voting_mints[..1]),
+            0,
+            0,
+        )
+        .await
+        .assert_on_chain_err(MplStakingError::InvalidVoteWeightScaledFactor);
+
+    // a rate for a voting mint that was configured before the registrar was grown moves
+    // it to the grown registrar, one and a half votes per token
+    addin
+        .configure_voting_mint_with_rate(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+            0,
+            VotingMint::SCALED_FACTOR_BASE * 3 / 2,
+        )
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .get_account_data(registrar.address)
+            .await
+            .len(),
+        Registrar::space(Registrar::INLINE_VOTING_MINTS + 1)
+    );
+
+    // half a vote per token
+    let usdc_voting_mint = addin
+        .configure_voting_mint_with_rate(
+            &registrar,
+            &realm_authority,
+            payer,
+            1,
+            &context.mints[1],
+            None,
+            Some(&voting_mints[..1]),
+            0,
+            VotingMint::SCALED_FACTOR_BASE / 2,
+        )
+        .await?;
+    // three decimals more than the governance token, two votes per token
+    let lp_voting_mint = addin
+        .configure_voting_mint_with_rate(
+            &registrar,
+            &realm_authority,
+            payer,
+            2,
+            &context.mints[2],
+            None,
+            Some(&voting_mints[..2]),
+            -3,
+            2 * VotingMint::SCALED_FACTOR_BASE,
+        )
+        .await?;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    for (deposit_entry_index, voting_mint, mint_idx, amount) in [
+        (0, &mngo_voting_mint, 0, 10_000),
+        (1, &usdc_voting_mint, 1, 4_000),
+        (2, &lp_voting_mint, 2, 5_000),
+    ] {
+        let token_account = context.users[1].token_accounts[mint_idx];
+        // the test mints start out without supply
+        context
+            .solana
+            .mint_to(
+                &voting_mints[mint_idx],
+                &context.mints[mint_idx].authority,
+                &token_account,
+                amount,
+            )
+            .await;
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter,
+                voting_mint,
+                deposit_entry_index,
+                LockupKind::None,
+                LockupPeriod::None,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                voting_mint,
+                voter_authority,
+                token_account,
+                deposit_entry_index,
+                amount,
+            )
+            .await?;
+    }
+
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 15_000 + 2_000 + 10);
+
+    // the voter holds the whole supply of every voting mint
+    let max_voter_weight_record = addin
+        .create_max_voter_weight_record(&registrar, payer, &voting_mints)
+        .await?;
+    let max_record = context
+        .solana
+        .get_account::<mpl_staking::voter::MaxVoterWeightRecord>(max_voter_weight_record)
+        .await;
+    assert_eq!(max_record.max_voter_weight, record.voter_weight);
+
+    Ok(())
+}
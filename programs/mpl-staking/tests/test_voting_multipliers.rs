@@ -0,0 +1,235 @@
+use assert_custom_on_chain_error::AssertCustomOnChainErr;
+use mplx_staking_states::{
+    error::MplStakingError,
+    state::{
+        LockupKind, LockupPeriod, Registrar, RegistrarParams, VotingMultipliers, VotingPowerMode,
+    },
+};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_voting_multipliers() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    let multipliers = [1, 3, 5, 8];
+
+    // only the realm authority may change the multipliers
+    addin
+        .set_voting_multipliers(&registrar, voter_authority, multipliers)
+        .await
+        .expect_err("only the realm authority can change the multipliers");
+
+    // multipliers must be monotonic
+    addin
+        .set_voting_multipliers(&registrar, &realm_authority, [1, 5, 3, 8])
+        .await
+        .expect_err("multipliers must not decrease");
+
+    addin
+        .set_voting_multipliers(&registrar, &realm_authority, multipliers)
+        .await?;
+    addin
+        .set_voting_power_mode(
+            &registrar,
+            &realm_authority,
+            VotingPowerMode::PeriodMultiplier,
+        )
+        .await?;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Constant,
+            LockupPeriod::ThreeMonths,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            10_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            10_000,
+        )
+        .await?;
+
+    // the lockup lasts as long as in the rewards program
+    let deposit_entry = voter.get_deposit_entry(&context.solana, 1).await;
+    assert_eq!(
+        deposit_entry.lockup.end_ts - deposit_entry.lockup.start_ts,
+        LockupPeriod::ThreeMonths.to_secs()
+    );
+
+    // while the voting power uses the configured multiplier
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 10_000 * multipliers[1]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_migrate_registrar() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, _) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+
+    // a registrar created before the voting multipliers and the params existed
+    let mut data = context.solana.get_account_data(registrar.address).await;
+    data.truncate(Registrar::LEGACY_SPACE);
+    context
+        .solana
+        .set_account_data(registrar.address, data)
+        .await;
+
+    addin
+        .set_voting_multipliers(&registrar, &realm_authority, [1, 2, 3, 4])
+        .await
+        .expect_err("the registrar must be migrated first");
+
+    addin.migrate_registrar(&registrar, payer).await?;
+    let data = context.solana.get_account_data(registrar.address).await;
+    assert_eq!(data.len(), Registrar::space(Registrar::INLINE_VOTING_MINTS));
+    let migrated = context
+        .solana
+        .get_account::<Registrar>(registrar.address)
+        .await;
+    assert_eq!(
+        { migrated.voting_multipliers.multipliers },
+        VotingMultipliers::default().multipliers
+    );
+    assert_eq!(
+        { migrated.params.cooldown_secs },
+        RegistrarParams::default().cooldown_secs
+    );
+
+    // the registrar works as before and can only be migrated once
+    addin
+        .set_voting_multipliers(&registrar, &realm_authority, [1, 2, 3, 4])
+        .await?;
+    addin
+        .migrate_registrar(&registrar, payer)
+        .await
+        .assert_on_chain_err(MplStakingError::RegistrarAlreadyMigrated);
+
+    Ok(())
+}
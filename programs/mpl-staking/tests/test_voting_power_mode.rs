@@ -0,0 +1,167 @@
+use mplx_staking_states::state::{LockupKind, LockupPeriod, VotingPowerMode};
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+#[tokio::test]
+async fn test_voting_power_mode() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), payer)
+        .await;
+
+    let fill_authority = Keypair::from_bytes(&context.users[3].key.to_bytes()).unwrap();
+    let distribution_authority = Keypair::new();
+    let (registrar, rewards_pool) = context
+        .addin
+        .create_registrar(
+            &realm,
+            &realm_authority,
+            payer,
+            &fill_authority.pubkey(),
+            &distribution_authority.pubkey(),
+            &context.rewards.program_id,
+        )
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            None,
+            None,
+        )
+        .await;
+
+    let (deposit_mining, _) = find_deposit_mining_addr(
+        &context.rewards.program_id,
+        &voter_authority.pubkey(),
+        &rewards_pool,
+    );
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            voter_authority,
+            payer,
+            &rewards_pool,
+            &deposit_mining,
+            &context.rewards.program_id,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            LockupPeriod::None,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Constant,
+            LockupPeriod::OneYear,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            12_000,
+        )
+        .await?;
+    addin
+        .stake(
+            &registrar,
+            &voter,
+            voter.authority.pubkey(),
+            &context.rewards.program_id,
+            0,
+            1,
+            10_000,
+        )
+        .await?;
+
+    // only the realm authority may change the mode
+    addin
+        .set_voting_power_mode(
+            &registrar,
+            voter_authority,
+            VotingPowerMode::PeriodMultiplier,
+        )
+        .await
+        .expect_err("only the realm authority can change the mode");
+
+    // by default every token is one vote
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 12_000);
+
+    addin
+        .set_voting_power_mode(
+            &registrar,
+            &realm_authority,
+            VotingPowerMode::PeriodMultiplier,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(
+        record.voter_weight,
+        2_000 + 10_000 * LockupPeriod::OneYear.multiplier()
+    );
+
+    addin
+        .set_voting_power_mode(&registrar, &realm_authority, VotingPowerMode::Decaying)
+        .await?;
+    addin
+        .set_time_offset(&registrar, &realm_authority, 180 * 86400)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert!(record.voter_weight > 12_000);
+    assert!(record.voter_weight < 2_000 + 10_000 * LockupPeriod::OneYear.multiplier());
+
+    // once the lockup has expired, the bonus is gone
+    addin
+        .set_time_offset(&registrar, &realm_authority, 366 * 86400)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    let record = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(record.voter_weight, 12_000);
+
+    Ok(())
+}
@@ -222,7 +222,7 @@ async fn withdraw_is_restricted() -> Result<(), TransportError> {
 
     context
         .addin
-        .restrict_tokenflow(&registrar, &realm_authority, &voter)
+        .restrict_tokenflow(&registrar, &realm_authority, &voter, 0)
         .await?;
 
     context